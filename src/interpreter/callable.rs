@@ -0,0 +1,212 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::parser::{
+    ast::StmtId,
+    statement::{declaration, Statement},
+};
+
+use super::{environment::Environment, value::Value, Program};
+
+/// Host function callable from Lox. An `Err` becomes a runtime error on the line of the call.
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, String>;
+
+/// A user-defined function closed over the environment it was declared in.
+/// It keeps the program it came from alive, since REPL inputs each have their own AST.
+pub struct Function {
+    program: Rc<Program>,
+    declaration: StmtId,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl Function {
+    pub(crate) fn new(
+        program: &Rc<Program>,
+        declaration: StmtId,
+        closure: &Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        Self {
+            program: Rc::clone(program),
+            declaration,
+            closure: Rc::clone(closure),
+            is_initializer,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.declaration().get_name().get_name()
+    }
+
+    pub fn arity(&self) -> usize {
+        self.declaration().params().len()
+    }
+
+    pub(crate) fn program(&self) -> &Rc<Program> {
+        &self.program
+    }
+
+    pub(crate) fn closure(&self) -> &Rc<RefCell<Environment>> {
+        &self.closure
+    }
+
+    pub(crate) fn is_initializer(&self) -> bool {
+        self.is_initializer
+    }
+
+    pub(crate) fn declaration(&self) -> &declaration::Function {
+        match &self.program.ast()[self.declaration] {
+            Statement::Function(function) => function,
+            _ => unreachable!("function values are only created from function declarations"),
+        }
+    }
+
+    pub(crate) fn bind(&self, instance: Value) -> Function {
+        let environment = Environment::with_enclosing(&self.closure);
+        environment.borrow_mut().define("this", instance);
+
+        Function::new(
+            &self.program,
+            self.declaration,
+            &environment,
+            self.is_initializer,
+        )
+    }
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<fn {}>", self.name())
+    }
+}
+
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Value]) -> Result<Value, String> + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn call(&self, arguments: &[Value]) -> Result<Value, String> {
+        (self.function)(arguments)
+    }
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+#[derive(Debug)]
+pub struct Class {
+    name: String,
+    superclass: Option<Rc<Class>>,
+    methods: HashMap<String, Rc<Function>>,
+}
+
+impl Class {
+    pub fn new(
+        name: &str,
+        superclass: Option<Rc<Class>>,
+        methods: HashMap<String, Rc<Function>>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            superclass,
+            methods,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+        match self.methods.get(name) {
+            Some(method) => Some(Rc::clone(method)),
+            None => self.superclass.as_ref()?.find_method(name),
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.find_method("init")
+            .map(|initializer| initializer.arity())
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    class: Rc<Class>,
+    fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    pub fn new(class: &Rc<Class>) -> Self {
+        Self {
+            class: Rc::clone(class),
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn class(&self) -> &Rc<Class> {
+        &self.class
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<Value> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn set_field(&mut self, name: &str, value: Value) {
+        self.fields.insert(name.to_string(), value);
+    }
+}
+
+/// The global scope of an imported file, as bound by `import "path" as name;`.
+#[derive(Debug)]
+pub struct Module {
+    name: String,
+    globals: Rc<RefCell<Environment>>,
+}
+
+impl Module {
+    pub fn new(name: &str, globals: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            name: name.to_string(),
+            globals,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.globals.borrow().get(name)
+    }
+
+    pub(crate) fn globals(&self) -> &Rc<RefCell<Environment>> {
+        &self.globals
+    }
+}
@@ -0,0 +1,101 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::value::Value;
+
+/// A scope of variable bindings chained to the scope that encloses it.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self::default()))
+    }
+
+    pub fn with_enclosing(enclosing: &Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            values: HashMap::new(),
+            enclosing: Some(Rc::clone(enclosing)),
+        }))
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.enclosing.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Assigns to an existing binding, returning `false` if the name is undefined.
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+                None => false,
+            },
+        }
+    }
+
+    pub fn get_at(environment: &Rc<RefCell<Self>>, distance: usize, name: &str) -> Option<Value> {
+        Self::ancestor(environment, distance)
+            .borrow()
+            .values
+            .get(name)
+            .cloned()
+    }
+
+    pub fn assign_at(environment: &Rc<RefCell<Self>>, distance: usize, name: &str, value: Value) {
+        Self::ancestor(environment, distance)
+            .borrow_mut()
+            .define(name, value);
+    }
+
+    /// Bindings of this scope only, sorted by name.
+    pub fn values(&self) -> Vec<(String, Value)> {
+        let mut values: Vec<(String, Value)> = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+
+        values
+    }
+
+    /// The outermost scope, i.e. the globals of the file `environment` belongs to.
+    pub fn root(environment: &Rc<RefCell<Self>>) -> Rc<RefCell<Self>> {
+        let mut environment = Rc::clone(environment);
+
+        loop {
+            let enclosing = environment.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => environment = enclosing,
+                None => return environment,
+            }
+        }
+    }
+
+    fn ancestor(environment: &Rc<RefCell<Self>>, distance: usize) -> Rc<RefCell<Self>> {
+        let mut environment = Rc::clone(environment);
+
+        for _ in 0..distance {
+            let enclosing = environment.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => environment = enclosing,
+                None => break,
+            }
+        }
+
+        environment
+    }
+}
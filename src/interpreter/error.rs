@@ -0,0 +1,188 @@
+use std::{path::Path, rc::Rc};
+
+use crate::{module::file_name, tokenizer::Span};
+
+use super::value::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    span: Span,
+    msg: String,
+    /// The value of a `throw`, which `catch` receives as is.
+    thrown: Option<Value>,
+    /// The calls that were running when the error was raised, innermost first.
+    trace: Vec<TraceFrame>,
+}
+
+impl RuntimeError {
+    pub fn new(msg: &str, span: Span) -> Self {
+        Self {
+            span,
+            msg: msg.to_string(),
+            thrown: None,
+            trace: Vec::new(),
+        }
+    }
+
+    /// Uncaught, it is reported like any other runtime error with the value as
+    /// the message. A rethrown error value keeps its original line.
+    pub fn thrown(value: Value, span: Span) -> Self {
+        let span = match &value {
+            Value::Exception(exception) => Span::new(exception.line(), 0, 0),
+            _ => span,
+        };
+
+        Self {
+            span,
+            msg: value.to_string(),
+            thrown: Some(value),
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.msg
+    }
+
+    pub fn trace(&self) -> &[TraceFrame] {
+        &self.trace
+    }
+
+    /// The innermost frame reports the line of the error itself, the others
+    /// the line of the call they are waiting on.
+    pub(crate) fn set_trace(&mut self, mut trace: Vec<TraceFrame>) {
+        if let Some(frame) = trace.first_mut() {
+            frame.line = self.span.line;
+        }
+        self.trace = trace;
+    }
+
+    /// What a `catch` clause binds: the thrown value, or an error value
+    /// describing the error.
+    pub fn value(&self) -> Value {
+        match &self.thrown {
+            Some(value) => value.clone(),
+            None => Value::Exception(Rc::new(Exception::new(&self.msg, self.span.line))),
+        }
+    }
+}
+
+/// A runtime error caught by `catch`, with `message` and `line` properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exception {
+    message: Rc<str>,
+    line: usize,
+}
+
+impl Exception {
+    pub fn new(message: &str, line: usize) -> Self {
+        Self {
+            message: Rc::from(message),
+            line,
+        }
+    }
+
+    pub fn message(&self) -> &Rc<str> {
+        &self.message
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/// One call on the stack of a runtime error, printed like `[line 3] in f()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFrame {
+    /// `None` for the top level of a file.
+    function: Option<String>,
+    file: Option<String>,
+    line: usize,
+}
+
+impl TraceFrame {
+    pub fn new(function: Option<&str>, path: Option<&Path>, line: usize) -> Self {
+        Self {
+            function: function.map(str::to_string),
+            file: path.map(file_name),
+            line,
+        }
+    }
+
+    pub fn function(&self) -> Option<&str> {
+        self.function.as_deref()
+    }
+
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub(crate) fn set_line(&mut self, line: usize) {
+        self.line = line;
+    }
+}
+
+impl std::fmt::Display for TraceFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] in ", self.line)?;
+        match &self.function {
+            Some(function) => write!(f, "{function}()")?,
+            None => write!(f, "script")?,
+        }
+        match &self.file {
+            Some(file) => write!(f, " ({file})"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeError {
+    /// Errors raised at the top level of the script only print their line;
+    /// anything deeper prints the whole stack, with runs of the same frame
+    /// from deep recursion folded into one.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.trace.len() < 2 {
+            return write!(f, "{}\n[line {}]", self.msg, self.span.line);
+        }
+
+        write!(f, "{}", self.msg)?;
+        let mut frames = self.trace.iter().peekable();
+        while let Some(frame) = frames.next() {
+            write!(f, "\n{frame}")?;
+
+            let mut repeated = 0;
+            while frames.next_if_eq(&frame).is_some() {
+                repeated += 1;
+            }
+            if repeated > 0 {
+                write!(f, "\n[previous line repeated {repeated} more times]")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// Non-local exits out of statement execution.
+#[derive(Debug)]
+pub(crate) enum Unwind {
+    Error(RuntimeError),
+    Return(Value),
+    Break,
+    Continue,
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(err: RuntimeError) -> Self {
+        Unwind::Error(err)
+    }
+}
@@ -0,0 +1,1061 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{Stdout, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use callable::{Class, Function, Instance, Module, NativeFunction};
+use environment::Environment;
+use error::{RuntimeError, TraceFrame, Unwind};
+use resolver::Resolver;
+use value::{bitwise, bitwise_not, modulo, List, Value};
+
+use crate::{
+    module::{cycle_error, module_name, Loader},
+    parser::{
+        ast::{Ast, ExprId, Identifier, StmtId},
+        error::ParseExprError,
+        expression::{
+            binary::BinaryType,
+            logical::LogicalType,
+            unary::UnaryType,
+            update::{Update, UpdateKind},
+            Expression,
+        },
+        statement::{
+            control_flow::{For, ForIn, Try},
+            declaration::Import,
+            Statement,
+        },
+    },
+    stdlib::{list_index, map, NativeValue, Stdlib, FUNCTIONS},
+    tokenizer::Span,
+    vm::FRAMES_MAX,
+};
+
+pub mod callable;
+pub mod environment;
+pub mod error;
+mod natives;
+pub mod resolver;
+mod tests;
+pub mod value;
+
+/// A parsed AST together with the scope distances the resolver computed for it.
+#[derive(Debug)]
+pub struct Program {
+    ast: Ast,
+    locals: HashMap<ExprId, usize>,
+    /// The file the program was read from, which its imports are relative to.
+    path: Option<PathBuf>,
+}
+
+impl Program {
+    pub fn new(ast: Ast) -> Result<Rc<Self>, Vec<ParseExprError>> {
+        let locals = Resolver::resolve(&ast)?;
+
+        Ok(Rc::new(Self {
+            ast,
+            locals,
+            path: None,
+        }))
+    }
+
+    pub fn with_path(ast: Ast, path: PathBuf) -> Result<Rc<Self>, Vec<ParseExprError>> {
+        let locals = Resolver::resolve(&ast)?;
+
+        Ok(Rc::new(Self {
+            ast,
+            locals,
+            path: Some(path),
+        }))
+    }
+
+    pub fn ast(&self) -> &Ast {
+        &self.ast
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+/// Native stack Lox calls may use before they fail with a stack overflow. Every
+/// Lox call recurses on the Rust stack, so the default fits the 2 MiB a thread
+/// gets from `std::thread::spawn`.
+pub const STACK_LIMIT: usize = 1024 * 1024;
+
+/// Stack the binary runs on, enough for the call depth the VM allows. Debug
+/// builds use far more per call.
+pub const STACK_SIZE: usize = 512 * 1024 * 1024;
+
+/// Tree-walking interpreter. Output of `print` goes to `W`.
+pub struct Interpreter<W: Write = Stdout> {
+    /// Globals of the file currently running; every imported file has its own.
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    /// Natives every file's globals start out with.
+    builtins: Vec<(String, Value)>,
+    loader: Loader,
+    modules: HashMap<PathBuf, Rc<Module>>,
+    /// Files currently executing, outermost first.
+    loading: Vec<PathBuf>,
+    /// Calls currently running, outermost first, each at the line it called
+    /// the next one from.
+    frames: Vec<TraceFrame>,
+    /// Address of the outermost running call's stack frame.
+    stack_base: Option<usize>,
+    stack_limit: usize,
+    stdlib: Rc<Stdlib>,
+    output: W,
+}
+
+impl Interpreter<Stdout> {
+    pub fn new() -> Self {
+        Self::with_output(std::io::stdout())
+    }
+}
+
+impl Default for Interpreter<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn with_output(output: W) -> Self {
+        let globals = Environment::new();
+
+        let mut interpreter = Self {
+            environment: Rc::clone(&globals),
+            globals,
+            builtins: Vec::new(),
+            loader: Loader::default(),
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            frames: Vec::new(),
+            stack_base: None,
+            stack_limit: STACK_LIMIT,
+            stdlib: Rc::new(Stdlib::default()),
+            output,
+        };
+        for (name, arity, function) in FUNCTIONS {
+            let stdlib = Rc::clone(&interpreter.stdlib);
+            interpreter.define_native(name, *arity, move |arguments| {
+                let arguments: Vec<_> = arguments.iter().map(native_value).collect();
+                function(&stdlib, &arguments).map(from_native_value)
+            });
+        }
+        natives::define_list_natives(&mut interpreter);
+
+        interpreter
+    }
+
+    /// State of the standard library natives, e.g. to seed `random()`.
+    pub fn stdlib(&self) -> &Stdlib {
+        &self.stdlib
+    }
+
+    /// How imported files are tokenized and optimized.
+    pub fn set_loader(&mut self, loader: Loader) {
+        self.loader = loader;
+    }
+
+    /// Bytes of native stack Lox calls may use, for threads with more than the
+    /// [`STACK_LIMIT`] default.
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.stack_limit = bytes;
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Value]) -> Result<Value, String> + 'static,
+    ) {
+        let native = Value::Native(Rc::new(NativeFunction::new(name, arity, function)));
+        self.globals.borrow_mut().define(name, native.clone());
+        self.builtins.push((name.to_string(), native));
+    }
+
+    pub fn global(&self, name: &str) -> Option<Value> {
+        self.globals.borrow().get(name)
+    }
+
+    /// Global bindings sorted by name.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        self.globals.borrow().values()
+    }
+
+    pub fn interpret(&mut self, program: &Rc<Program>) -> Result<(), RuntimeError> {
+        if let Some(path) = program.path() {
+            self.loading.push(path.to_path_buf());
+        }
+
+        self.frames.push(TraceFrame::new(None, program.path(), 0));
+        let result = self.run(program).map_err(|err| self.traced(err));
+        self.frames.pop();
+
+        if program.path().is_some() {
+            self.loading.pop();
+        }
+
+        result
+    }
+
+    fn run(&mut self, program: &Rc<Program>) -> Result<(), RuntimeError> {
+        for statement in program.ast.program() {
+            match self.execute(program, *statement) {
+                Ok(()) => {}
+                Err(Unwind::Error(err)) => return Err(err),
+                Err(Unwind::Return(_)) => break,
+                Err(Unwind::Break | Unwind::Continue) => {
+                    unreachable!("the resolver rejects break and continue outside of loops")
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the root expression of an AST parsed in expression mode.
+    pub fn evaluate_root(&mut self, program: &Rc<Program>) -> Result<Value, RuntimeError> {
+        match program.ast.root() {
+            Some(root) => self.evaluate(program, root),
+            None => Ok(Value::Nil),
+        }
+    }
+
+    fn execute(&mut self, program: &Rc<Program>, id: StmtId) -> Result<(), Unwind> {
+        match &program.ast[id] {
+            Statement::Expression(expression) => {
+                self.evaluate(program, *expression)?;
+            }
+            Statement::Print(expression) => {
+                let value = self.evaluate(program, *expression)?;
+                writeln!(self.output, "{value}").map_err(|err| {
+                    RuntimeError::new(&err.to_string(), program.ast.statement_span(id))
+                })?;
+            }
+            Statement::Var(var) => {
+                let value = match var.initializer() {
+                    Some(initializer) => self.evaluate(program, initializer)?,
+                    None => Value::Nil,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(var.get_name().get_name(), value);
+            }
+            Statement::Block(statements) => {
+                let environment = Environment::with_enclosing(&self.environment);
+                self.execute_block(program, statements, environment)?;
+            }
+            Statement::If(if_statement) => {
+                if self
+                    .evaluate(program, if_statement.condition())?
+                    .is_truthy()
+                {
+                    self.execute(program, if_statement.then_branch())?;
+                } else if let Some(else_branch) = if_statement.else_branch() {
+                    self.execute(program, else_branch)?;
+                }
+            }
+            Statement::While(while_statement) => {
+                while self
+                    .evaluate(program, while_statement.condition())?
+                    .is_truthy()
+                {
+                    if !loop_continues(self.execute(program, while_statement.body()))? {
+                        break;
+                    }
+                }
+            }
+            Statement::For(for_statement) => {
+                let previous = Rc::clone(&self.environment);
+                self.environment = Environment::with_enclosing(&previous);
+
+                let result = self.execute_for(program, for_statement);
+                self.environment = previous;
+
+                result?;
+            }
+            Statement::ForIn(for_in) => self.execute_for_in(program, for_in)?,
+            Statement::Function(function) => {
+                let value = Function::new(program, id, &self.environment, false);
+                self.environment.borrow_mut().define(
+                    function.get_name().get_name(),
+                    Value::Function(Rc::new(value)),
+                );
+            }
+            Statement::Return(return_statement) => {
+                let value = match return_statement.value() {
+                    Some(value) => self.evaluate(program, value)?,
+                    None => Value::Nil,
+                };
+
+                return Err(Unwind::Return(value));
+            }
+            Statement::Import(import) => {
+                let module = self.import(program, import)?;
+
+                match import.alias() {
+                    Some(alias) => self
+                        .environment
+                        .borrow_mut()
+                        .define(alias.get_name(), Value::Module(module)),
+                    None => {
+                        for (name, value) in self.exports(&module) {
+                            self.globals.borrow_mut().define(&name, value);
+                        }
+                    }
+                }
+            }
+            Statement::Throw(value) => {
+                let value = self.evaluate(program, *value)?;
+
+                return Err(RuntimeError::thrown(value, program.ast.statement_span(id)).into());
+            }
+            Statement::Try(try_statement) => self.execute_try(program, try_statement)?,
+            Statement::Break(_) => return Err(Unwind::Break),
+            Statement::Continue(_) => return Err(Unwind::Continue),
+            Statement::Class(class) => {
+                let superclass = match class.superclass() {
+                    Some(superclass) => match self.evaluate(program, superclass)? {
+                        Value::Class(class) => Some(class),
+                        _ => {
+                            return Err(RuntimeError::new(
+                                "Superclass must be a class.",
+                                program.ast.span(superclass),
+                            )
+                            .into())
+                        }
+                    },
+                    None => None,
+                };
+
+                let name = class.get_name().get_name();
+                self.environment.borrow_mut().define(name, Value::Nil);
+
+                let enclosing = Rc::clone(&self.environment);
+                if let Some(superclass) = &superclass {
+                    self.environment = Environment::with_enclosing(&enclosing);
+                    self.environment
+                        .borrow_mut()
+                        .define("super", Value::Class(Rc::clone(superclass)));
+                }
+
+                let mut methods = HashMap::new();
+                for method in class.methods() {
+                    if let Statement::Function(function) = &program.ast[*method] {
+                        let method_name = function.get_name().get_name();
+                        let is_initializer = method_name == "init";
+                        let function =
+                            Function::new(program, *method, &self.environment, is_initializer);
+
+                        methods.insert(method_name.to_string(), Rc::new(function));
+                    }
+                }
+
+                self.environment = enclosing;
+
+                let class = Class::new(name, superclass, methods);
+                self.environment
+                    .borrow_mut()
+                    .assign(name, Value::Class(Rc::new(class)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The `finally` block runs however the rest ended, and if it ends early
+    /// itself, that replaces the earlier outcome.
+    fn execute_try(&mut self, program: &Rc<Program>, try_statement: &Try) -> Result<(), Unwind> {
+        let mut result = self.execute(program, try_statement.body());
+
+        if let (Err(Unwind::Error(err)), Some(catch)) = (&result, try_statement.catch()) {
+            let environment = Environment::with_enclosing(&self.environment);
+            environment
+                .borrow_mut()
+                .define(catch.get_name().get_name(), err.value());
+            result = self.execute_block(program, &[catch.body()], environment);
+        }
+
+        if let Some(finally) = try_statement.finally() {
+            self.execute(program, finally)?;
+        }
+
+        result
+    }
+
+    /// Runs the imported file in its own globals the first time it is seen.
+    fn import(&mut self, program: &Program, import: &Import) -> Result<Rc<Module>, RuntimeError> {
+        let path = Loader::resolve(program.path(), import.path());
+
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(Rc::clone(module));
+        }
+        if self.loading.contains(&path) {
+            return Err(RuntimeError::new(
+                &cycle_error(&self.loading, &path),
+                import.path_span(),
+            ));
+        }
+
+        let imported = self
+            .loader
+            .load(&path)
+            .map_err(|msg| RuntimeError::new(&msg, import.path_span()))?;
+
+        let globals = Environment::new();
+        for (name, value) in &self.builtins {
+            globals.borrow_mut().define(name, value.clone());
+        }
+
+        self.set_line(import.path_span().line);
+        let globals_before = std::mem::replace(&mut self.globals, Rc::clone(&globals));
+        let environment_before = std::mem::replace(&mut self.environment, Rc::clone(&globals));
+        let result = self.interpret(&imported);
+        self.globals = globals_before;
+        self.environment = environment_before;
+        result?;
+
+        let module = Rc::new(Module::new(&module_name(&path), globals));
+        self.modules.insert(path, Rc::clone(&module));
+
+        Ok(module)
+    }
+
+    /// Records the line the current frame is about to call into another from.
+    fn set_line(&mut self, line: usize) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.set_line(line);
+        }
+    }
+
+    /// Errors take the stack as it is in the frame that raised them, which is
+    /// the innermost one they leave without a trace yet.
+    fn traced(&self, mut err: RuntimeError) -> RuntimeError {
+        if err.trace().is_empty() {
+            err.set_trace(self.frames.iter().rev().cloned().collect());
+        }
+        err
+    }
+
+    /// The globals a module defined itself, leaving out the builtins.
+    fn exports(&self, module: &Module) -> Vec<(String, Value)> {
+        module
+            .globals()
+            .borrow()
+            .values()
+            .into_iter()
+            .filter(|export| !self.builtins.contains(export))
+            .collect()
+    }
+
+    fn execute_for(&mut self, program: &Rc<Program>, for_statement: &For) -> Result<(), Unwind> {
+        if let Some(initializer) = for_statement.initializer() {
+            self.execute(program, initializer)?;
+        }
+
+        loop {
+            if let Some(condition) = for_statement.condition() {
+                if !self.evaluate(program, condition)?.is_truthy() {
+                    break;
+                }
+            }
+
+            if !loop_continues(self.execute(program, for_statement.body()))? {
+                break;
+            }
+
+            if let Some(increment) = for_statement.increment() {
+                self.evaluate(program, increment)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the body once per element, each time in a fresh scope so closures
+    /// capture that iteration's element. Elements pushed by the body are visited too.
+    fn execute_for_in(&mut self, program: &Rc<Program>, for_in: &ForIn) -> Result<(), Unwind> {
+        let Value::List(list) = self.evaluate(program, for_in.iterable())? else {
+            return Err(RuntimeError::new(
+                "Can only iterate over lists.",
+                program.ast.span(for_in.iterable()),
+            )
+            .into());
+        };
+
+        let mut index = 0;
+        loop {
+            let Some(element) = list.borrow().get(index).cloned() else {
+                break;
+            };
+
+            let environment = Environment::with_enclosing(&self.environment);
+            environment
+                .borrow_mut()
+                .define(for_in.get_name().get_name(), element);
+            let result = self.execute_block(program, &[for_in.body()], environment);
+            if !loop_continues(result)? {
+                break;
+            }
+
+            index += 1;
+        }
+
+        Ok(())
+    }
+
+    fn execute_block(
+        &mut self,
+        program: &Rc<Program>,
+        statements: &[StmtId],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(program, *statement));
+
+        self.environment = previous;
+
+        result
+    }
+
+    fn evaluate(&mut self, program: &Rc<Program>, id: ExprId) -> Result<Value, RuntimeError> {
+        let ast = &program.ast;
+
+        let value = match &ast[id] {
+            Expression::Number(literal) => Value::Number(literal.parse().unwrap_or_default()),
+            Expression::String(value) => Value::String(Rc::from(value.as_str())),
+            Expression::True => Value::Bool(true),
+            Expression::False => Value::Bool(false),
+            Expression::Nil => Value::Nil,
+            Expression::Grouping(inner) => self.evaluate(program, *inner)?,
+            Expression::Unary(unary) => {
+                let operand = self.evaluate(program, unary.expression())?;
+
+                match (unary.get_type(), operand) {
+                    (UnaryType::Bang, operand) => Value::Bool(!operand.is_truthy()),
+                    (UnaryType::Minus, Value::Number(value)) => Value::Number(-value),
+                    (UnaryType::Tilde, Value::Number(value)) => Value::Number(
+                        bitwise_not(value)
+                            .map_err(|msg| RuntimeError::new(msg, unary.operator_span()))?,
+                    ),
+                    (UnaryType::Minus | UnaryType::Tilde, _) => {
+                        return Err(RuntimeError::new(
+                            "Operand must be a number.",
+                            unary.operator_span(),
+                        ))
+                    }
+                }
+            }
+            Expression::Binary(binary) => {
+                let left = self.evaluate(program, binary.left())?;
+                let right = self.evaluate(program, binary.right())?;
+
+                binary_operation(binary.get_type(), left, right)
+                    .map_err(|msg| RuntimeError::new(msg, binary.operator_span()))?
+            }
+            Expression::Logical(logical) => {
+                let left = self.evaluate(program, logical.left())?;
+
+                match (logical.get_type(), left.is_truthy()) {
+                    (LogicalType::Or, true) | (LogicalType::And, false) => left,
+                    _ => self.evaluate(program, logical.right())?,
+                }
+            }
+            Expression::Lambda(function) => {
+                let function = Function::new(program, *function, &self.environment, false);
+                Value::Function(Rc::new(function))
+            }
+            Expression::Conditional(conditional) => {
+                match self.evaluate(program, conditional.condition())?.is_truthy() {
+                    true => self.evaluate(program, conditional.then_branch())?,
+                    false => self.evaluate(program, conditional.else_branch())?,
+                }
+            }
+            Expression::Variable(name) => {
+                self.look_up(program, id, name.get_name(), name.get_span())?
+            }
+            Expression::Assign(assign) => {
+                let value = self.evaluate(program, assign.value())?;
+                self.assign(program, id, assign.get_name(), value.clone())?;
+
+                value
+            }
+            Expression::Update(update) => self.update(program, update)?,
+            Expression::Interpolation(parts) => {
+                let mut string = String::new();
+                for part in parts {
+                    string.push_str(&self.evaluate(program, *part)?.to_string());
+                }
+
+                Value::String(Rc::from(string))
+            }
+            Expression::Call(call) => {
+                let callee = self.evaluate(program, call.callee())?;
+                let arguments = call
+                    .arguments()
+                    .iter()
+                    .map(|argument| self.evaluate(program, *argument))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+
+                self.call(callee, arguments, call.paren_span())?
+            }
+            Expression::Get(get) => {
+                let object = self.evaluate(program, get.object())?;
+                property_of(&object, get.get_name())?
+            }
+            Expression::Set(set) => {
+                let object = self.evaluate(program, set.object())?;
+
+                let Value::Instance(instance) = object else {
+                    return Err(RuntimeError::new(
+                        "Only instances have fields.",
+                        set.get_name().get_span(),
+                    ));
+                };
+
+                let value = self.evaluate(program, set.value())?;
+                instance
+                    .borrow_mut()
+                    .set_field(set.get_name().get_name(), value.clone());
+
+                value
+            }
+            Expression::List(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.evaluate(program, *element))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+
+                Value::List(Rc::new(RefCell::new(elements)))
+            }
+            Expression::Map(entries) => {
+                let mut map = map::Map::default();
+                for (key, value) in entries {
+                    let key = self
+                        .evaluate(program, *key)?
+                        .to_key()
+                        .map_err(|msg| RuntimeError::new(&msg, ast.span(id)))?;
+                    map.insert(key, self.evaluate(program, *value)?);
+                }
+
+                Value::Map(Rc::new(RefCell::new(map)))
+            }
+            Expression::Index(index) => {
+                let object = self.evaluate(program, index.object())?;
+                let key = self.evaluate(program, index.index())?;
+
+                get_index(&object, &key)
+                    .map_err(|msg| RuntimeError::new(&msg, index.bracket_span()))?
+            }
+            Expression::SetIndex(set_index) => {
+                let object = self.evaluate(program, set_index.object())?;
+                let key = self.evaluate(program, set_index.index())?;
+                let value = self.evaluate(program, set_index.value())?;
+
+                set_index_of(&object, &key, value.clone())
+                    .map_err(|msg| RuntimeError::new(&msg, set_index.bracket_span()))?;
+
+                value
+            }
+            Expression::This => self.look_up(program, id, "this", ast.span(id))?,
+            Expression::Super(method) => {
+                let distance = program.locals.get(&id).copied().unwrap_or_default();
+                let superclass = Environment::get_at(&self.environment, distance, "super");
+                let object =
+                    Environment::get_at(&self.environment, distance.saturating_sub(1), "this");
+
+                let (Some(Value::Class(superclass)), Some(object)) = (superclass, object) else {
+                    return Err(RuntimeError::new(
+                        "Can't use 'super' outside of a class.",
+                        ast.span(id),
+                    ));
+                };
+
+                match superclass.find_method(method.get_name()) {
+                    Some(found) => Value::Function(Rc::new(found.bind(object))),
+                    None => {
+                        return Err(RuntimeError::new(
+                            &format!("Undefined property '{}'.", method.get_name()),
+                            method.get_span(),
+                        ))
+                    }
+                }
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn assign(
+        &self,
+        program: &Program,
+        id: ExprId,
+        name: &Identifier,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        match program.locals.get(&id) {
+            Some(distance) => {
+                Environment::assign_at(&self.environment, *distance, name.get_name(), value)
+            }
+            None => {
+                let assigned = self.globals.borrow_mut().assign(name.get_name(), value);
+
+                if !assigned {
+                    return Err(undefined_variable(name.get_name(), name.get_span()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the target of `update` once, writes the combined value back and
+    /// yields the old value for a postfix operator and the new one otherwise.
+    fn update(&mut self, program: &Rc<Program>, update: &Update) -> Result<Value, RuntimeError> {
+        let target = update.target();
+
+        let (old, new) = match &program.ast[target] {
+            Expression::Variable(name) => {
+                let old = self.look_up(program, target, name.get_name(), name.get_span())?;
+                let new = self.updated(program, update, old.clone())?;
+                self.assign(program, target, name, new.clone())?;
+
+                (old, new)
+            }
+            Expression::Get(get) => {
+                let object = self.evaluate(program, get.object())?;
+                let old = property_of(&object, get.get_name())?;
+                let new = self.updated(program, update, old.clone())?;
+
+                if let Value::Instance(instance) = object {
+                    instance
+                        .borrow_mut()
+                        .set_field(get.get_name().get_name(), new.clone());
+                }
+
+                (old, new)
+            }
+            Expression::Index(index) => {
+                let object = self.evaluate(program, index.object())?;
+                let key = self.evaluate(program, index.index())?;
+                let old = get_index(&object, &key)
+                    .map_err(|msg| RuntimeError::new(&msg, index.bracket_span()))?;
+                let new = self.updated(program, update, old.clone())?;
+
+                set_index_of(&object, &key, new.clone())
+                    .map_err(|msg| RuntimeError::new(&msg, index.bracket_span()))?;
+
+                (old, new)
+            }
+            _ => unreachable!("the parser only updates variables, properties and indexes"),
+        };
+
+        match update.kind() {
+            UpdateKind::Postfix => Ok(old),
+            UpdateKind::Compound(_) | UpdateKind::Prefix => Ok(new),
+        }
+    }
+
+    fn updated(
+        &mut self,
+        program: &Rc<Program>,
+        update: &Update,
+        old: Value,
+    ) -> Result<Value, RuntimeError> {
+        let operand = match update.value() {
+            Some(value) => self.evaluate(program, value)?,
+            None => Value::Number(1.0),
+        };
+
+        binary_operation(update.get_type(), old, operand)
+            .map_err(|msg| RuntimeError::new(msg, update.operator_span()))
+    }
+
+    fn look_up(
+        &self,
+        program: &Program,
+        id: ExprId,
+        name: &str,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let value = match program.locals.get(&id) {
+            Some(distance) => Environment::get_at(&self.environment, *distance, name),
+            None => self.globals.borrow().get(name),
+        };
+
+        value.ok_or_else(|| undefined_variable(name, span))
+    }
+
+    fn call(
+        &mut self,
+        callee: Value,
+        arguments: Vec<Value>,
+        paren: Span,
+    ) -> Result<Value, RuntimeError> {
+        let arity = match &callee {
+            Value::Function(function) => function.arity(),
+            Value::Native(native) => native.arity(),
+            Value::Class(class) => class.arity(),
+            _ => {
+                return Err(RuntimeError::new(
+                    "Can only call functions and classes.",
+                    paren,
+                ))
+            }
+        };
+
+        if arguments.len() != arity {
+            return Err(RuntimeError::new(
+                &format!("Expected {arity} arguments but got {}.", arguments.len()),
+                paren,
+            ));
+        }
+
+        self.set_line(paren.line);
+        match callee {
+            Value::Function(function) => self.call_function(&function, arguments, paren),
+            Value::Native(native) => native
+                .call(&arguments)
+                .map_err(|msg| RuntimeError::new(&msg, paren)),
+            Value::Class(class) => {
+                let instance = Value::Instance(Rc::new(RefCell::new(Instance::new(&class))));
+
+                if let Some(initializer) = class.find_method("init") {
+                    self.call_function(&initializer.bind(instance.clone()), arguments, paren)?;
+                }
+
+                Ok(instance)
+            }
+            _ => unreachable!("arity is only known for callable values"),
+        }
+    }
+
+    fn call_function(
+        &mut self,
+        function: &Function,
+        arguments: Vec<Value>,
+        paren: Span,
+    ) -> Result<Value, RuntimeError> {
+        let address = stack_address();
+        let stack_used = self.stack_base.map_or(0, |base| base.abs_diff(address));
+        if self.frames.len() == FRAMES_MAX || stack_used > self.stack_limit {
+            return Err(RuntimeError::new("Stack overflow.", paren));
+        }
+        let outermost = self.stack_base.is_none();
+        if outermost {
+            self.stack_base = Some(address);
+        }
+
+        let environment = Environment::with_enclosing(function.closure());
+
+        for (param, argument) in function.declaration().params().iter().zip(arguments) {
+            environment.borrow_mut().define(param.get_name(), argument);
+        }
+
+        let program = Rc::clone(function.program());
+        self.frames
+            .push(TraceFrame::new(Some(function.name()), program.path(), 0));
+        let globals = std::mem::replace(&mut self.globals, Environment::root(function.closure()));
+        let result = self.execute_block(&program, function.declaration().body(), environment);
+        self.globals = globals;
+        if outermost {
+            self.stack_base = None;
+        }
+
+        let result = match result {
+            Err(Unwind::Error(err)) => Err(Unwind::Error(self.traced(err))),
+            result => result,
+        };
+        self.frames.pop();
+
+        let value = match result {
+            Ok(()) => Value::Nil,
+            Err(Unwind::Return(value)) => value,
+            Err(Unwind::Error(err)) => return Err(err),
+            Err(Unwind::Break | Unwind::Continue) => {
+                unreachable!("the resolver rejects break and continue outside of loops")
+            }
+        };
+
+        match function.is_initializer() {
+            true => Ok(Environment::get_at(function.closure(), 0, "this").unwrap_or_default()),
+            false => Ok(value),
+        }
+    }
+}
+
+/// Where the stack currently ends, to measure how much the calls use.
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+fn binary_operation(
+    binary_type: &BinaryType,
+    left: Value,
+    right: Value,
+) -> Result<Value, &'static str> {
+    let value = match (binary_type, left, right) {
+        (BinaryType::Comma, _, right) => right,
+        (BinaryType::EqualEqual, left, right) => Value::Bool(left == right),
+        (BinaryType::BangEqual, left, right) => Value::Bool(left != right),
+        (BinaryType::Plus, Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+        (BinaryType::Plus, Value::String(a), Value::String(b)) => {
+            Value::String(Rc::from(format!("{a}{b}")))
+        }
+        (BinaryType::Plus, _, _) => return Err("Operands must be two numbers or two strings."),
+        (binary_type, Value::Number(a), Value::Number(b)) => match binary_type {
+            BinaryType::Minus => Value::Number(a - b),
+            BinaryType::Star => Value::Number(a * b),
+            BinaryType::Slash => Value::Number(a / b),
+            BinaryType::Greater => Value::Bool(a > b),
+            BinaryType::GreaterEqual => Value::Bool(a >= b),
+            BinaryType::Less => Value::Bool(a < b),
+            BinaryType::LessEqual => Value::Bool(a <= b),
+            BinaryType::Percent => Value::Number(modulo(a, b)),
+            BinaryType::StarStar => Value::Number(a.powf(b)),
+            BinaryType::Ampersand
+            | BinaryType::Pipe
+            | BinaryType::Caret
+            | BinaryType::LessLess
+            | BinaryType::GreaterGreater => Value::Number(bitwise(binary_type, a, b)?),
+            _ => unreachable!("equality and addition are handled above"),
+        },
+        _ => return Err("Operands must be numbers."),
+    };
+
+    Ok(value)
+}
+
+fn property_of(object: &Value, name: &Identifier) -> Result<Value, RuntimeError> {
+    let property = match object {
+        Value::Instance(instance) => get_property(instance, name.get_name()),
+        Value::Module(module) => module.get(name.get_name()),
+        Value::Exception(exception) => match name.get_name() {
+            "message" => Some(Value::String(Rc::clone(exception.message()))),
+            "line" => Some(Value::Number(exception.line() as f64)),
+            _ => None,
+        },
+        _ => {
+            return Err(RuntimeError::new(
+                "Only instances have properties.",
+                name.get_span(),
+            ))
+        }
+    };
+
+    property.ok_or_else(|| {
+        RuntimeError::new(
+            &format!("Undefined property '{}'.", name.get_name()),
+            name.get_span(),
+        )
+    })
+}
+
+fn get_property(instance: &Rc<RefCell<Instance>>, name: &str) -> Option<Value> {
+    if let Some(value) = instance.borrow().get_field(name) {
+        return Some(value);
+    }
+
+    let method = instance.borrow().class().find_method(name)?;
+
+    Some(Value::Function(Rc::new(
+        method.bind(Value::Instance(Rc::clone(instance))),
+    )))
+}
+
+/// Whether a loop goes on after one run of its body ended with `result`.
+fn loop_continues(result: Result<(), Unwind>) -> Result<bool, Unwind> {
+    match result {
+        Ok(()) | Err(Unwind::Continue) => Ok(true),
+        Err(Unwind::Break) => Ok(false),
+        Err(unwind) => Err(unwind),
+    }
+}
+
+fn get_index(object: &Value, index: &Value) -> Result<Value, String> {
+    match object {
+        Value::List(list) => {
+            let position = list_position(list, index)?;
+            let element = list.borrow()[position].clone();
+
+            Ok(element)
+        }
+        Value::Map(map) => map
+            .borrow()
+            .get(&index.to_key()?)
+            .cloned()
+            .ok_or_else(|| format!("Undefined key {}.", index.element())),
+        _ => Err("Can only index lists and maps.".to_string()),
+    }
+}
+
+fn set_index_of(object: &Value, index: &Value, value: Value) -> Result<(), String> {
+    match object {
+        Value::List(list) => {
+            let position = list_position(list, index)?;
+            list.borrow_mut()[position] = value;
+        }
+        Value::Map(map) => map.borrow_mut().insert(index.to_key()?, value),
+        _ => return Err("Can only index lists and maps.".to_string()),
+    }
+
+    Ok(())
+}
+
+fn list_position(list: &List, index: &Value) -> Result<usize, String> {
+    let Value::Number(index) = index else {
+        return Err("List index must be an integer.".to_string());
+    };
+
+    list_index(*index, list.borrow().len(), false)
+}
+
+fn undefined_variable(name: &str, span: Span) -> RuntimeError {
+    RuntimeError::new(&format!("Undefined variable '{name}'."), span)
+}
+
+fn native_value(value: &Value) -> NativeValue {
+    match value {
+        Value::Nil => NativeValue::Nil,
+        Value::Bool(value) => NativeValue::Bool(*value),
+        Value::Number(value) => NativeValue::Number(*value),
+        Value::String(value) => NativeValue::String(Rc::clone(value)),
+        value => NativeValue::Object {
+            type_name: value.type_name(),
+            display: value.to_string(),
+        },
+    }
+}
+
+fn from_native_value(value: NativeValue) -> Value {
+    match value {
+        NativeValue::Nil => Value::Nil,
+        NativeValue::Bool(value) => Value::Bool(value),
+        NativeValue::Number(value) => Value::Number(value),
+        NativeValue::String(value) => Value::String(value),
+        NativeValue::Object { display, .. } => Value::String(Rc::from(display)),
+    }
+}
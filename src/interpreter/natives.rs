@@ -0,0 +1,124 @@
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use super::{
+    value::{List, Map, Value},
+    Interpreter,
+};
+use crate::stdlib::{list_index, list_slice};
+
+/// Adds the list and map natives, wrapping the stdlib `len` so it also counts
+/// their elements.
+pub(crate) fn define_list_natives<W: Write>(interpreter: &mut Interpreter<W>) {
+    let string_len = interpreter.global("len");
+    interpreter.define_native("len", 1, move |arguments| {
+        match (&arguments[0], &string_len) {
+            (Value::List(list), _) => Ok(Value::Number(list.borrow().len() as f64)),
+            (Value::Map(map), _) => Ok(Value::Number(map.borrow().len() as f64)),
+            (_, Some(Value::Native(string_len))) => string_len.call(arguments),
+            _ => Err("len() expects a string, list or map.".to_string()),
+        }
+    });
+
+    interpreter.define_native("push", 2, |arguments| {
+        list("push", &arguments[0])?
+            .borrow_mut()
+            .push(arguments[1].clone());
+        Ok(Value::Nil)
+    });
+
+    interpreter.define_native("pop", 1, |arguments| {
+        list("pop", &arguments[0])?
+            .borrow_mut()
+            .pop()
+            .ok_or_else(|| "Can't pop from an empty list.".to_string())
+    });
+
+    interpreter.define_native("insert", 3, |arguments| {
+        let list = list("insert", &arguments[0])?;
+        let index = index("insert", &arguments[1])?;
+
+        let mut list = list.borrow_mut();
+        let index = list_index(index, list.len(), true)?;
+        list.insert(index, arguments[2].clone());
+
+        Ok(Value::Nil)
+    });
+
+    interpreter.define_native("remove", 2, |arguments| {
+        let list = list("remove", &arguments[0])?;
+        let index = index("remove", &arguments[1])?;
+
+        let mut list = list.borrow_mut();
+        let index = list_index(index, list.len(), false)?;
+
+        Ok(list.remove(index))
+    });
+
+    interpreter.define_native("slice", 3, |arguments| {
+        let list = list("slice", &arguments[0])?;
+        let start = index("slice", &arguments[1])?;
+        let end = index("slice", &arguments[2])?;
+
+        let list = list.borrow();
+        let range = list_slice(start, end, list.len())?;
+
+        Ok(Value::List(Rc::new(RefCell::new(list[range].to_vec()))))
+    });
+
+    interpreter.define_native("keys", 1, |arguments| {
+        let keys = map("keys", &arguments[0])?
+            .borrow()
+            .keys()
+            .map(Value::from_key)
+            .collect();
+
+        Ok(Value::List(Rc::new(RefCell::new(keys))))
+    });
+
+    interpreter.define_native("values", 1, |arguments| {
+        let values = map("values", &arguments[0])?
+            .borrow()
+            .values()
+            .cloned()
+            .collect();
+
+        Ok(Value::List(Rc::new(RefCell::new(values))))
+    });
+
+    interpreter.define_native("has", 2, |arguments| {
+        let map = map("has", &arguments[0])?;
+        let key = arguments[1].to_key()?;
+
+        let found = map.borrow().contains_key(&key);
+        Ok(Value::Bool(found))
+    });
+
+    interpreter.define_native("delete", 2, |arguments| {
+        let map = map("delete", &arguments[0])?;
+        let key = arguments[1].to_key()?;
+
+        let removed = map.borrow_mut().remove(&key);
+        Ok(removed.unwrap_or_default())
+    });
+}
+
+fn list(name: &str, value: &Value) -> Result<List, String> {
+    match value {
+        Value::List(list) => Ok(Rc::clone(list)),
+        _ => Err(format!("{name}() expects a list.")),
+    }
+}
+
+fn map(name: &str, value: &Value) -> Result<Map, String> {
+    match value {
+        Value::Map(map) => Ok(Rc::clone(map)),
+        _ => Err(format!("{name}() expects a map.")),
+    }
+}
+
+fn index(name: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(index) => Ok(*index),
+        _ => Err(format!("{name}() expects a number index.")),
+    }
+}
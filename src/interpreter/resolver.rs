@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+
+use crate::{
+    parser::{
+        ast::{Ast, ExprId, Identifier, StmtId},
+        error::ParseExprError,
+        expression::{assign::Assign, Expression},
+        statement::{
+            control_flow::{For, ForIn, Return, Try, While},
+            declaration::{Class, Function, Import, Var},
+            Statement,
+        },
+        visitor::{walk_statements, walk_var, Visitor},
+    },
+    tokenizer::Span,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+/// Static pass run before interpretation. Records for every local variable
+/// reference how many scopes away its binding lives, and reports misuse of
+/// `return`, `this` and `super` as well as redeclared locals.
+pub struct Resolver {
+    scopes: Vec<HashMap<String, bool>>,
+    locals: HashMap<ExprId, usize>,
+    errors: Vec<ParseExprError>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    /// Loops enclosing the current statement within the current function.
+    loop_depth: usize,
+}
+
+impl Resolver {
+    pub fn resolve(ast: &Ast) -> Result<HashMap<ExprId, usize>, Vec<ParseExprError>> {
+        let mut resolver = Resolver {
+            scopes: Vec::new(),
+            locals: HashMap::new(),
+            errors: Vec::new(),
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            loop_depth: 0,
+        };
+
+        resolver.visit_program(ast);
+        if let Some(root) = ast.root() {
+            resolver.visit_expression(ast, root);
+        }
+
+        match resolver.errors.is_empty() {
+            true => Ok(resolver.locals),
+            false => Err(resolver.errors),
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Identifier) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+
+        if scope.contains_key(name.get_name()) {
+            self.errors.push(ParseExprError::resolution(
+                name.get_span(),
+                name.get_name(),
+                "Already a variable with this name in this scope.",
+            ));
+        }
+
+        scope.insert(name.get_name().to_string(), false);
+    }
+
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn resolve_local(&mut self, id: ExprId, name: &str) {
+        let found = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name));
+
+        if let Some(distance) = found {
+            self.locals.insert(id, distance);
+        }
+    }
+
+    fn resolve_function(&mut self, ast: &Ast, function: &Function, function_type: FunctionType) {
+        let enclosing = self.current_function;
+        self.current_function = function_type;
+        let enclosing_loops = std::mem::take(&mut self.loop_depth);
+
+        self.begin_scope();
+        for param in function.params() {
+            self.declare(param);
+            self.define(param.get_name());
+        }
+        walk_statements(self, ast, function.body());
+        self.end_scope();
+
+        self.current_function = enclosing;
+        self.loop_depth = enclosing_loops;
+    }
+
+    fn loop_body(&mut self, ast: &Ast, body: StmtId) {
+        self.loop_depth += 1;
+        self.visit_statement(ast, body);
+        self.loop_depth -= 1;
+    }
+
+    fn error(&mut self, span: Span, lexeme: &str, msg: &str) {
+        self.errors
+            .push(ParseExprError::resolution(span, lexeme, msg));
+    }
+}
+
+impl<'ast> Visitor<'ast> for Resolver {
+    fn visit_var(&mut self, ast: &'ast Ast, _id: StmtId, var: &'ast Var) {
+        self.declare(var.get_name());
+        walk_var(self, ast, var);
+        self.define(var.get_name().get_name());
+    }
+
+    fn visit_import(&mut self, _ast: &'ast Ast, _id: StmtId, import: &'ast Import) {
+        if let Some(alias) = import.alias() {
+            self.declare(alias);
+            self.define(alias.get_name());
+        }
+    }
+
+    fn visit_block(&mut self, ast: &'ast Ast, _id: StmtId, statements: &'ast [StmtId]) {
+        self.begin_scope();
+        walk_statements(self, ast, statements);
+        self.end_scope();
+    }
+
+    fn visit_while(&mut self, ast: &'ast Ast, _id: StmtId, while_statement: &'ast While) {
+        self.visit_expression(ast, while_statement.condition());
+        self.loop_body(ast, while_statement.body());
+    }
+
+    fn visit_for(&mut self, ast: &'ast Ast, _id: StmtId, for_statement: &'ast For) {
+        self.begin_scope();
+        if let Some(initializer) = for_statement.initializer() {
+            self.visit_statement(ast, initializer);
+        }
+        if let Some(condition) = for_statement.condition() {
+            self.visit_expression(ast, condition);
+        }
+        if let Some(increment) = for_statement.increment() {
+            self.visit_expression(ast, increment);
+        }
+        self.loop_body(ast, for_statement.body());
+        self.end_scope();
+    }
+
+    fn visit_for_in(&mut self, ast: &'ast Ast, _id: StmtId, for_in: &'ast ForIn) {
+        self.visit_expression(ast, for_in.iterable());
+
+        self.begin_scope();
+        self.declare(for_in.get_name());
+        self.define(for_in.get_name().get_name());
+        self.loop_body(ast, for_in.body());
+        self.end_scope();
+    }
+
+    fn visit_try(&mut self, ast: &'ast Ast, _id: StmtId, try_statement: &'ast Try) {
+        self.visit_statement(ast, try_statement.body());
+
+        if let Some(catch) = try_statement.catch() {
+            self.begin_scope();
+            self.declare(catch.get_name());
+            self.define(catch.get_name().get_name());
+            self.visit_statement(ast, catch.body());
+            self.end_scope();
+        }
+
+        if let Some(finally) = try_statement.finally() {
+            self.visit_statement(ast, finally);
+        }
+    }
+
+    fn visit_break(&mut self, _ast: &'ast Ast, _id: StmtId, keyword: Span) {
+        if self.loop_depth == 0 {
+            self.error(keyword, "break", "Can't use 'break' outside of a loop.");
+        }
+    }
+
+    fn visit_continue(&mut self, _ast: &'ast Ast, _id: StmtId, keyword: Span) {
+        if self.loop_depth == 0 {
+            self.error(
+                keyword,
+                "continue",
+                "Can't use 'continue' outside of a loop.",
+            );
+        }
+    }
+
+    fn visit_function(&mut self, ast: &'ast Ast, _id: StmtId, function: &'ast Function) {
+        self.declare(function.get_name());
+        self.define(function.get_name().get_name());
+        self.resolve_function(ast, function, FunctionType::Function);
+    }
+
+    fn visit_lambda(&mut self, ast: &'ast Ast, _id: ExprId, function: StmtId) {
+        if let Statement::Function(function) = &ast[function] {
+            self.resolve_function(ast, function, FunctionType::Function);
+        }
+    }
+
+    fn visit_return(&mut self, ast: &'ast Ast, _id: StmtId, return_statement: &'ast Return) {
+        let keyword = return_statement.keyword_span();
+
+        if self.current_function == FunctionType::None {
+            self.error(keyword, "return", "Can't return from top-level code.");
+        }
+
+        if let Some(value) = return_statement.value() {
+            if self.current_function == FunctionType::Initializer {
+                self.error(
+                    keyword,
+                    "return",
+                    "Can't return a value from an initializer.",
+                );
+            }
+            self.visit_expression(ast, value);
+        }
+    }
+
+    fn visit_class(&mut self, ast: &'ast Ast, _id: StmtId, class: &'ast Class) {
+        let enclosing = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(class.get_name());
+        self.define(class.get_name().get_name());
+
+        if let Some(superclass) = class.superclass() {
+            if let Expression::Variable(name) = &ast[superclass] {
+                if name.get_name() == class.get_name().get_name() {
+                    self.error(
+                        name.get_span(),
+                        name.get_name(),
+                        "A class can't inherit from itself.",
+                    );
+                }
+            }
+
+            self.current_class = ClassType::Subclass;
+            self.visit_expression(ast, superclass);
+
+            self.begin_scope();
+            self.define("super");
+        }
+
+        self.begin_scope();
+        self.define("this");
+
+        for method in class.methods() {
+            if let Statement::Function(function) = &ast[*method] {
+                let function_type = match function.get_name().get_name() {
+                    "init" => FunctionType::Initializer,
+                    _ => FunctionType::Method,
+                };
+                self.resolve_function(ast, function, function_type);
+            }
+        }
+
+        self.end_scope();
+        if class.superclass().is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing;
+    }
+
+    fn visit_variable(&mut self, _ast: &'ast Ast, id: ExprId, name: &'ast Identifier) {
+        let in_initializer = self
+            .scopes
+            .last()
+            .and_then(|scope| scope.get(name.get_name()))
+            .is_some_and(|defined| !defined);
+
+        if in_initializer {
+            self.error(
+                name.get_span(),
+                name.get_name(),
+                "Can't read local variable in its own initializer.",
+            );
+        }
+
+        self.resolve_local(id, name.get_name());
+    }
+
+    fn visit_assign(&mut self, ast: &'ast Ast, id: ExprId, assign: &'ast Assign) {
+        self.visit_expression(ast, assign.value());
+        self.resolve_local(id, assign.get_name().get_name());
+    }
+
+    fn visit_this(&mut self, ast: &'ast Ast, id: ExprId) {
+        if self.current_class == ClassType::None {
+            self.error(ast.span(id), "this", "Can't use 'this' outside of a class.");
+            return;
+        }
+
+        self.resolve_local(id, "this");
+    }
+
+    fn visit_super(&mut self, ast: &'ast Ast, id: ExprId, _method: &'ast Identifier) {
+        match self.current_class {
+            ClassType::None => self.error(
+                ast.span(id),
+                "super",
+                "Can't use 'super' outside of a class.",
+            ),
+            ClassType::Class => self.error(
+                ast.span(id),
+                "super",
+                "Can't use 'super' in a class with no superclass.",
+            ),
+            ClassType::Subclass => self.resolve_local(id, "super"),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+#[allow(unused_imports)]
+use crate::{
+    interpreter::{Interpreter, Program},
+    parser::Parser,
+    repl::Repl,
+    tokenizer::Tokenizer,
+};
+
+fn _run(input: &str) -> Result<String, String> {
+    let output = Tokenizer::tokenize_with(input.to_string(), true).unwrap();
+    let program = Parser::parse_program_with(output.get_tokens(), true)
+        .and_then(Program::new)
+        .map_err(|errors| errors[0].to_string())?;
+    let mut interpreter = Interpreter::with_output(Vec::new());
+
+    interpreter
+        .interpret(&program)
+        .map_err(|err| err.to_string())?;
+
+    Ok(String::from_utf8(interpreter.output().clone()).unwrap())
+}
+
+fn _repl(lines: &[&str]) -> String {
+    _repl_with(lines, false)
+}
+
+fn _repl_with(lines: &[&str], extensions: bool) -> String {
+    let mut repl = Repl::with_output(Vec::new());
+    repl.set_extensions(extensions);
+
+    for line in lines {
+        if !repl.line(line).unwrap() {
+            break;
+        }
+    }
+
+    String::from_utf8(repl.output().clone()).unwrap()
+}
+
+#[test]
+fn arithmetic_and_strings() {
+    assert_eq!(
+        _run("print 1 + 2 * 3; print 10 / 4; print \"a\" + \"b\"; print -0;"),
+        Ok("7\n2.5\nab\n-0\n".to_string())
+    );
+}
+
+#[test]
+fn closures_capture_by_scope() {
+    let input = "
+        var a = \"global\";
+        {
+            fun show() { print a; }
+            show();
+            var a = \"block\";
+            show();
+        }
+        fun counter() { var i = 0; fun inc() { i = i + 1; return i; } return inc; }
+        var c = counter();
+        c();
+        print c();
+    ";
+
+    assert_eq!(_run(input), Ok("global\nglobal\n2\n".to_string()));
+}
+
+#[test]
+fn classes_and_inheritance() {
+    let input = "
+        class A { init(n) { this.n = n; } get() { return this.n; } }
+        class B < A { get() { return super.get() * 2; } }
+        var b = B(21);
+        print b.get();
+        print b;
+        print B;
+    ";
+
+    assert_eq!(_run(input), Ok("42\nB instance\nB\n".to_string()));
+}
+
+#[test]
+fn runtime_errors() {
+    assert_eq!(
+        _run("print 1;\nprint -\"x\";"),
+        Err("Operand must be a number.\n[line 2]".to_string())
+    );
+    assert_eq!(
+        _run("print nope;"),
+        Err("Undefined variable 'nope'.\n[line 1]".to_string())
+    );
+    assert_eq!(
+        _run("fun f(a) {}\nf();"),
+        Err("Expected 1 arguments but got 0.\n[line 2]".to_string())
+    );
+}
+
+#[test]
+fn bitwise_operands_must_be_integers() {
+    assert_eq!(
+        _run("print ~0.5;"),
+        Err("Operand must be an integer.\n[line 1]".to_string())
+    );
+    assert_eq!(
+        _run("print 1 << 64;"),
+        Err("Shift amount must be between 0 and 63.\n[line 1]".to_string())
+    );
+    assert_eq!(
+        _run("print \"a\" & 1;"),
+        Err("Operands must be numbers.\n[line 1]".to_string())
+    );
+    assert_eq!(_run("print 3 ^ 1000;"), Ok("1003\n".to_string()));
+}
+
+#[test]
+fn resolver_errors() {
+    assert_eq!(
+        _run("return 1;"),
+        Err("[line 1] Error at 'return': Can't return from top-level code.".to_string())
+    );
+    assert_eq!(
+        _run("{ var a = a; }"),
+        Err("[line 1] Error at 'a': Can't read local variable in its own initializer.".to_string())
+    );
+    assert_eq!(
+        _run("print this;"),
+        Err("[line 1] Error at 'this': Can't use 'this' outside of a class.".to_string())
+    );
+    assert_eq!(
+        _run("break;"),
+        Err("[line 1] Error at 'break': Can't use 'break' outside of a loop.".to_string())
+    );
+    assert_eq!(
+        _run("while (true) {\nfun f() { continue; }\n}"),
+        Err("[line 2] Error at 'continue': Can't use 'continue' outside of a loop.".to_string())
+    );
+}
+
+#[test]
+fn deep_recursion_fails_within_a_default_stack() {
+    let source = "fun f(n) { if (n == 0) return 0; return f(n - 1) + 1; }\n";
+    let (shallow, deep) = std::thread::spawn(move || {
+        (
+            _run(&format!("{source}print f(10);")),
+            _run(&format!("{source}print f(100000);")),
+        )
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(shallow, Ok("10\n".to_string()));
+    let err = deep.unwrap_err();
+    assert!(err.starts_with("Stack overflow.\n[line 1] in f()"), "{err}");
+}
+
+#[test]
+fn repl_keeps_globals_and_echoes_expressions() {
+    assert_eq!(_repl(&["var a = 1;", "a + 2", "a = 5;", "a"]), "3\n5\n");
+}
+
+#[test]
+fn repl_accumulates_incomplete_input() {
+    let lines = [
+        "fun twice(x) {",
+        "  return x * 2;",
+        "}",
+        "twice(",
+        "4)",
+        "\"two",
+        "lines\"",
+    ];
+
+    assert_eq!(_repl(&lines), "8\ntwo\nlines\n");
+}
+
+#[test]
+fn repl_continues_after_errors() {
+    assert_eq!(_repl(&["nope", "print 1 +;", "1"]), "1\n");
+}
+
+#[test]
+fn repl_meta_commands() {
+    let env = _repl(&["var a = 1;", ":env"]);
+    assert!(env.starts_with("a = 1\nabs = <native fn>\n"));
+    assert!(env.contains("\nclock = <native fn>\n"));
+    assert_eq!(_repl(&[":ast 1 + 2"]), "(+ 1.0 2.0)\n");
+    assert_eq!(_repl(&[":ast print 1;"]), "(print 1.0)\n");
+    assert_eq!(_repl(&[":tokens 1"]), "NUMBER 1 1.0\nEOF  null\n");
+    assert_eq!(_repl(&[":quit", "1"]), "");
+}
+
+#[test]
+fn repl_history_keeps_lines_apart() {
+    let lines = ["fun f() { // answer", "  return 1;", "}", ":history"];
+
+    assert_eq!(
+        _repl(&lines),
+        "   1  fun f() { // answer\n   2    return 1;\n   3  }\n   4  :history\n"
+    );
+}
+
+#[test]
+fn repl_accepts_extensions() {
+    let lines = [
+        "var l = [1,",
+        "2];",
+        "\"${l[1]}\"",
+        "{\"a\": l}[\"a\"]",
+        ":ast [1]",
+    ];
+
+    assert_eq!(_repl_with(&lines, true), "2\n[1, 2]\n(list 1.0)\n");
+    assert_eq!(_repl(&["[1]"]), "");
+}
@@ -0,0 +1,334 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::{
+    callable::{Class, Function, Instance, Module, NativeFunction},
+    error::Exception,
+};
+use crate::{
+    parser::expression::binary::BinaryType,
+    stdlib::map::{self, MapKey, KEY_ERROR},
+};
+
+/// Lists are shared: assigning one or passing it around aliases the same elements.
+pub type List = Rc<RefCell<Vec<Value>>>;
+/// Shared like lists.
+pub type Map = Rc<RefCell<map::Map<Key, Value>>>;
+pub type Key = MapKey<Rc<str>>;
+
+#[derive(Clone, Debug, Default)]
+pub enum Value {
+    #[default]
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(Rc<str>),
+    Function(Rc<Function>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(List),
+    Map(Map),
+    Module(Rc<Module>),
+    Exception(Rc<Exception>),
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Function(_) | Value::Native(_) => "function",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Module(_) => "module",
+            Value::Exception(_) => "error",
+        }
+    }
+
+    /// The key this value is stored under in a map.
+    pub fn to_key(&self) -> Result<Key, String> {
+        match self {
+            Value::Nil => Ok(MapKey::Nil),
+            Value::Bool(value) => Ok(MapKey::Bool(*value)),
+            Value::Number(value) => Ok(MapKey::number(*value)),
+            Value::String(value) => Ok(MapKey::String(Rc::clone(value))),
+            _ => Err(KEY_ERROR.to_string()),
+        }
+    }
+
+    pub fn from_key(key: &Key) -> Value {
+        match key {
+            MapKey::Nil => Value::Nil,
+            MapKey::Bool(value) => Value::Bool(*value),
+            MapKey::Number(bits) => Value::Number(f64::from_bits(*bits)),
+            MapKey::String(value) => Value::String(Rc::clone(value)),
+        }
+    }
+
+    /// How the value prints inside a list or map, where strings are quoted.
+    pub fn element(&self) -> String {
+        match self {
+            Value::String(string) => format!("\"{string}\""),
+            value => value.to_string(),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Native(a), Value::Native(b)) => Rc::ptr_eq(a, b),
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::List(a), Value::List(b)) => Rc::ptr_eq(a, b),
+            (Value::Map(a), Value::Map(b)) => Rc::ptr_eq(a, b),
+            (Value::Module(a), Value::Module(b)) => Rc::ptr_eq(a, b),
+            (Value::Exception(a), Value::Exception(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) => write!(f, "{}", format_number(*value)),
+            Value::String(value) => write!(f, "{value}"),
+            Value::Function(function) => write!(f, "<fn {}>", function.name()),
+            Value::Native(_) => write!(f, "<native fn>"),
+            Value::Class(class) => write!(f, "{}", class.name()),
+            Value::Instance(instance) => write!(f, "{} instance", instance.borrow().class().name()),
+            Value::List(list) => {
+                let elements: Vec<String> = list.borrow().iter().map(Value::element).collect();
+
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Value::Map(map) => {
+                let entries: Vec<String> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", Value::from_key(key).element(), value.element())
+                    })
+                    .collect();
+
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Value::Module(module) => write!(f, "<module {}>", module.name()),
+            Value::Exception(exception) => write!(f, "{}", exception.message()),
+        }
+    }
+}
+
+/// Integral numbers print without a fraction (`3`, not `3.0`), like the reference implementation.
+pub fn format_number(value: f64) -> String {
+    match value {
+        f64::INFINITY => "Infinity".to_string(),
+        f64::NEG_INFINITY => "-Infinity".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Floored modulo: the result has the sign of the divisor, so `-1 % 3` is `2`.
+pub fn modulo(a: f64, b: f64) -> f64 {
+    let remainder = a % b;
+
+    match remainder != 0.0 && (remainder < 0.0) != (b < 0.0) {
+        true => remainder + b,
+        false => remainder,
+    }
+}
+
+/// The bitwise and shift operators work on whole numbers as 64-bit integers.
+pub fn bitwise(binary_type: &BinaryType, a: f64, b: f64) -> Result<f64, &'static str> {
+    let (Some(a), Some(b)) = (integer(a), integer(b)) else {
+        return Err("Operands must be integers.");
+    };
+
+    let value = match binary_type {
+        BinaryType::Ampersand => a & b,
+        BinaryType::Pipe => a | b,
+        BinaryType::Caret => a ^ b,
+        BinaryType::LessLess | BinaryType::GreaterGreater => {
+            let shift = u32::try_from(b)
+                .ok()
+                .filter(|shift| *shift < i64::BITS)
+                .ok_or("Shift amount must be between 0 and 63.")?;
+
+            match binary_type {
+                BinaryType::LessLess => a << shift,
+                _ => a >> shift,
+            }
+        }
+        _ => unreachable!("{} is not a bitwise operator", binary_type.get_lexeme()),
+    };
+
+    Ok(value as f64)
+}
+
+pub fn bitwise_not(value: f64) -> Result<f64, &'static str> {
+    integer(value)
+        .map(|value| !value as f64)
+        .ok_or("Operand must be an integer.")
+}
+
+fn integer(value: f64) -> Option<i64> {
+    let in_range = value >= i64::MIN as f64 && value < i64::MAX as f64;
+    (value.fract() == 0.0 && in_range).then_some(value as i64)
+}
+
+/// Conversion of a Rust value into a Lox value, used for native return values.
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// Conversion of a Lox value into a Rust value, used for native arguments.
+/// The error message is suitable for a Lox runtime error.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+fn mismatch(expected: &str, value: &Value) -> String {
+    format!("Expected {expected} but got {}.", value.type_name())
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Nil
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+macro_rules! into_number {
+    ($($ty:ty),*) => {
+        $(impl IntoValue for $ty {
+            fn into_value(self) -> Value {
+                Value::Number(self as f64)
+            }
+        })*
+    };
+}
+
+into_number!(f64, f32, i32, i64, u32, u64, usize);
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(Rc::from(self))
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(Rc::from(self))
+    }
+}
+
+impl IntoValue for Rc<str> {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        self.map(IntoValue::into_value).unwrap_or_default()
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Bool(value) => Ok(*value),
+            value => Err(mismatch("boolean", value)),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Number(value) => Ok(*value),
+            value => Err(mismatch("number", value)),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Number(number) if number.fract() == 0.0 && number.abs() < 2f64.powi(63) => {
+                Ok(*number as i64)
+            }
+            Value::Number(number) => Err(format!(
+                "Expected integer but got {}.",
+                format_number(*number)
+            )),
+            value => Err(mismatch("integer", value)),
+        }
+    }
+}
+
+impl FromValue for usize {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match i64::from_value(value)? {
+            number if number >= 0 => Ok(number as usize),
+            number => Err(format!("Expected non-negative integer but got {number}.")),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Rc::<str>::from_value(value).map(|string| string.to_string())
+    }
+}
+
+impl FromValue for Rc<str> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(string) => Ok(Rc::clone(string)),
+            value => Err(mismatch("string", value)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Nil => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
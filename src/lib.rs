@@ -2,5 +2,14 @@
 #![allow(clippy::needless_return)]
 #![forbid(unsafe_code)]
 
+pub mod interpreter;
+pub mod lox;
+pub mod module;
 pub mod parser;
+pub mod repl;
+pub mod stdlib;
 pub mod tokenizer;
+pub mod vm;
+
+pub use interpreter::value::{FromValue, IntoValue, Value};
+pub use lox::{Lox, LoxError};
@@ -0,0 +1,171 @@
+use std::{
+    fmt::Display,
+    io::{Stdout, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    interpreter::{
+        error::RuntimeError,
+        value::{IntoValue, Value},
+        Interpreter, Program,
+    },
+    module::Loader,
+    parser::{error::ParseExprError, optimizer::OptLevel, Parser},
+    tokenizer::{Tokenizer, TokenizerError},
+};
+
+mod tests;
+
+#[derive(Debug)]
+pub enum LoxError {
+    Tokenize(Vec<TokenizerError>),
+    Compile(Vec<ParseExprError>),
+    Runtime(RuntimeError),
+    Io(std::io::Error),
+}
+
+impl Display for LoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn lines(f: &mut std::fmt::Formatter<'_>, errors: &[impl Display]) -> std::fmt::Result {
+            let lines: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            write!(f, "{}", lines.join("\n"))
+        }
+
+        match self {
+            LoxError::Tokenize(errors) => lines(f, errors),
+            LoxError::Compile(errors) => lines(f, errors),
+            LoxError::Runtime(err) => write!(f, "{err}"),
+            LoxError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for LoxError {}
+
+impl From<std::io::Error> for LoxError {
+    fn from(err: std::io::Error) -> Self {
+        LoxError::Io(err)
+    }
+}
+
+impl From<RuntimeError> for LoxError {
+    fn from(err: RuntimeError) -> Self {
+        LoxError::Runtime(err)
+    }
+}
+
+impl From<Vec<ParseExprError>> for LoxError {
+    fn from(errors: Vec<ParseExprError>) -> Self {
+        LoxError::Compile(errors)
+    }
+}
+
+/// Embedding entry point: one tree-walking interpreter whose globals persist
+/// across calls to [`Lox::eval`] and [`Lox::run_file`].
+pub struct Lox<W: Write = Stdout> {
+    interpreter: Interpreter<W>,
+    extensions: bool,
+}
+
+impl Lox<Stdout> {
+    pub fn new() -> Self {
+        Self::with_output(std::io::stdout())
+    }
+}
+
+impl Default for Lox<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Lox<W> {
+    /// An interpreter writing `print` output to `output` instead of stdout.
+    pub fn with_output(output: W) -> Self {
+        Self {
+            interpreter: Interpreter::with_output(output),
+            extensions: false,
+        }
+    }
+
+    /// Accepts the syntax extensions, e.g. list literals, in later sources.
+    pub fn set_extensions(&mut self, extensions: bool) {
+        self.extensions = extensions;
+        self.interpreter
+            .set_loader(Loader::new(extensions, OptLevel::default()));
+    }
+
+    /// Bytes of native stack Lox calls may use. The default suits any thread;
+    /// raise it for deeper recursion on a thread with a bigger stack.
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.interpreter.set_stack_limit(bytes);
+    }
+
+    pub fn output(&self) -> &W {
+        self.interpreter.output()
+    }
+
+    pub fn output_mut(&mut self) -> &mut W {
+        self.interpreter.output_mut()
+    }
+
+    pub fn into_output(self) -> W {
+        self.interpreter.into_output()
+    }
+
+    /// Registers a global function implemented in Rust. Arguments are checked
+    /// against `arity` before `function` runs, and an `Err` it returns becomes a
+    /// runtime error reported on the line of the call.
+    pub fn define_native<F, R>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Value]) -> Result<R, String> + 'static,
+        R: IntoValue,
+    {
+        self.interpreter
+            .define_native(name, arity, move |arguments| {
+                function(arguments).map(IntoValue::into_value)
+            });
+    }
+
+    pub fn global(&self, name: &str) -> Option<Value> {
+        self.interpreter.global(name)
+    }
+
+    /// Runs `source`. A lone expression without a trailing `;` evaluates to its
+    /// value, anything else runs as a program and evaluates to `nil`.
+    pub fn eval(&mut self, source: &str) -> Result<Value, LoxError> {
+        self.evaluate(source, None)
+    }
+
+    /// Imports in the file are resolved relative to it.
+    pub fn run_file(&mut self, path: impl AsRef<Path>) -> Result<(), LoxError> {
+        let source = std::fs::read_to_string(&path)?;
+
+        self.evaluate(&source, Some(Loader::resolve(None, path)))
+            .map(|_| ())
+    }
+
+    fn evaluate(&mut self, source: &str, path: Option<PathBuf>) -> Result<Value, LoxError> {
+        let program = |ast| match &path {
+            Some(path) => Program::with_path(ast, path.clone()),
+            None => Program::new(ast),
+        };
+
+        let tokenized = Tokenizer::tokenize_with(source.to_string(), self.extensions)?;
+        if !tokenized.get_errors().is_empty() {
+            return Err(LoxError::Tokenize(tokenized.get_errors().clone()));
+        }
+
+        let tokens = tokenized.get_tokens();
+        if let Ok(ast) = Parser::parse_tokens_with(tokens, self.extensions) {
+            let program = program(ast)?;
+            return Ok(self.interpreter.evaluate_root(&program)?);
+        }
+
+        let program = Parser::parse_program_with(tokens, self.extensions).and_then(program)?;
+        self.interpreter.interpret(&program)?;
+
+        Ok(Value::Nil)
+    }
+}
@@ -0,0 +1,101 @@
+#[allow(unused_imports)]
+use crate::{
+    interpreter::value::{FromValue, Value},
+    lox::{Lox, LoxError},
+};
+
+fn _lox() -> Lox<Vec<u8>> {
+    Lox::with_output(Vec::new())
+}
+
+fn _printed(lox: &Lox<Vec<u8>>) -> String {
+    String::from_utf8(lox.output().clone()).unwrap()
+}
+
+#[test]
+fn eval_returns_expression_values() {
+    let mut lox = _lox();
+
+    assert_eq!(lox.eval("1 + 2").unwrap(), Value::Number(3.0));
+    assert_eq!(lox.eval("var a = \"x\";").unwrap(), Value::Nil);
+    assert_eq!(lox.eval("a + a").unwrap(), Value::String("xx".into()));
+    assert_eq!(lox.global("a"), Some(Value::String("x".into())));
+}
+
+#[test]
+fn print_output_is_captured() {
+    let mut lox = _lox();
+    lox.eval("print \"hello\"; print 1 + 1;").unwrap();
+
+    assert_eq!(lox.into_output(), b"hello\n2\n");
+}
+
+#[test]
+fn natives_convert_arguments_and_results() {
+    let mut lox = _lox();
+    lox.define_native("repeat", 2, |arguments| {
+        let text = String::from_value(&arguments[0])?;
+        let count = usize::from_value(&arguments[1])?;
+        Ok(text.repeat(count))
+    });
+    lox.define_native("half", 1, |arguments| {
+        Ok(Option::<f64>::from_value(&arguments[0])?.map(|value| value / 2.0))
+    });
+
+    lox.eval("print repeat(\"ab\", 3); print half(5); print half(nil);")
+        .unwrap();
+
+    assert_eq!(_printed(&lox), "ababab\n2.5\nnil\n");
+}
+
+#[test]
+fn native_errors_report_the_calling_line() {
+    let mut lox = _lox();
+    lox.define_native("fail", 0, |_| Err::<(), _>("Native failure.".to_string()));
+    lox.define_native("square", 1, |arguments| {
+        f64::from_value(&arguments[0]).map(|value| value * value)
+    });
+
+    let err = lox.eval("var a = 1;\n\nfail();").unwrap_err();
+    assert_eq!(err.to_string(), "Native failure.\n[line 3]");
+
+    let err = lox.eval("square(\"x\");").unwrap_err();
+    assert_eq!(err.to_string(), "Expected number but got string.\n[line 1]");
+
+    let err = lox.eval("square(1, 2);").unwrap_err();
+    assert_eq!(err.to_string(), "Expected 1 arguments but got 2.\n[line 1]");
+}
+
+#[test]
+fn errors_are_typed() {
+    let mut lox = _lox();
+
+    assert!(matches!(lox.eval("@"), Err(LoxError::Tokenize(_))));
+    assert!(matches!(lox.eval("print ;"), Err(LoxError::Compile(_))));
+    assert!(matches!(lox.eval("-nil"), Err(LoxError::Runtime(_))));
+    assert!(matches!(
+        lox.run_file("/nonexistent/file.lox"),
+        Err(LoxError::Io(_))
+    ));
+}
+
+#[test]
+fn deep_recursion_is_an_error_on_a_default_thread() {
+    let (shallow, deep) = std::thread::spawn(|| {
+        let mut lox = _lox();
+        lox.eval("fun f(n) { if (n == 0) return 0; return f(n - 1) + 1; }")
+            .unwrap();
+        let mut eval = |source| match lox.eval(source) {
+            Ok(value) => Ok(value.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+
+        (eval("f(10)"), eval("f(1000)"))
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(shallow, Ok("10".to_string()));
+    let err = deep.unwrap_err();
+    assert!(err.starts_with("Stack overflow.\n[line 1] in f()"), "{err}");
+}
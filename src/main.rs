@@ -1,11 +1,44 @@
-use interpreter_starter_rust::{parser::Parser, tokenizer::Tokenizer};
+use std::path::{Path, PathBuf};
+
+use interpreter_starter_rust::{
+    interpreter::{Interpreter, Program, STACK_SIZE},
+    module::Loader,
+    parser::{
+        ast::Ast,
+        error::ParseExprError,
+        optimizer::{OptLevel, Optimizer},
+        printer::{LoxPrinter, PrintFormat},
+        Parser,
+    },
+    repl::Repl,
+    tokenizer::{Token, Tokenizer, TokenizerOutput},
+    vm::{bytecode::Bytecode, compiler::Compiler, disassembler::Disassembler, Vm},
+};
 
 const CODE_SUCCESS: i32 = 0;
+const CODE_UNFORMATTED: i32 = 1;
 const CODE_ERROR: i32 = 65;
+const CODE_RUNTIME_ERROR: i32 = 70;
 
 fn main() -> Result<(), std::io::Error> {
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)?
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+fn run() -> Result<(), std::io::Error> {
     let args: Vec<String> = std::env::args().collect();
 
+    if args[1..].iter().all(|arg| arg.starts_with("--")) {
+        let flags: Vec<&String> = args[1..].iter().collect();
+        let mut repl = Repl::new();
+        repl.set_extensions(has_flag(&flags, "extensions"));
+
+        return repl.run(std::io::stdin().lock());
+    }
+
     if args.len() < 3 {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidInput,
@@ -17,20 +50,197 @@ fn main() -> Result<(), std::io::Error> {
     }
 
     let command = &args[1];
-    let filename = &args[2];
+    let (flags, positional): (Vec<&String>, Vec<&String>) =
+        args[2..].iter().partition(|arg| arg.starts_with("--"));
+
+    let Some(filename) = positional.first() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Missing filename. Usage: {} {command} <filename>", args[0]),
+        ));
+    };
+
+    if command == "run" && filename.ends_with(".loxc") {
+        let function = match Bytecode::deserialize(&std::fs::read(filename)?) {
+            Ok(function) => function,
+            Err(err) => {
+                eprintln!("{filename}: {err}");
+                std::process::exit(CODE_ERROR);
+            }
+        };
+
+        if let Err(err) = vm(&flags, filename)?.interpret(function) {
+            eprintln!("{err}");
+            std::process::exit(CODE_RUNTIME_ERROR);
+        }
+
+        std::process::exit(CODE_SUCCESS);
+    }
+
     let file_contents = std::fs::read_to_string(filename)?;
 
     let result = match command.as_str() {
         "tokenize" => {
-            let output = Tokenizer::tokenize(file_contents)?;
+            let output = tokenize(&flags, file_contents)?;
             Tokenizer::serialize(output.get_tokens(), output.get_errors())
         }
         "parse" => {
-            let output = Tokenizer::tokenize(file_contents)?;
-            let expressions = Parser::parse_tokens(output.get_tokens())?;
+            let format = match option(&flags, "format") {
+                Some(format) => format.parse()?,
+                None => PrintFormat::default(),
+            };
+            let output = tokenize(&flags, file_contents)?;
+
+            match parse_tokens(&flags, output.get_tokens()) {
+                Ok(ast) => {
+                    println!("{}", format.print(&ast));
+                    Ok(())
+                }
+                Err(err) => {
+                    eprintln!("{err}");
+                    Err(err.into())
+                }
+            }
+        }
+        "evaluate" => {
+            let output = tokenize(&flags, file_contents)?;
+
+            match parse_tokens(&flags, output.get_tokens()) {
+                Ok(ast) => match Program::new(ast) {
+                    Ok(program) => match interpreter().evaluate_root(&program) {
+                        Ok(value) => {
+                            println!("{value}");
+                            Ok(())
+                        }
+                        Err(err) => {
+                            eprintln!("{err}");
+                            std::process::exit(CODE_RUNTIME_ERROR);
+                        }
+                    },
+                    Err(errors) => fail(&errors),
+                },
+                Err(err) => fail(&[err]),
+            }
+        }
+        "run" => {
+            let output = tokenize(&flags, file_contents)?;
+
+            if !output.get_errors().is_empty() {
+                Tokenizer::serialize(&[], output.get_errors())
+            } else {
+                let backend = option(&flags, "backend").unwrap_or("tree");
+                let trace = has_flag(&flags, "trace");
+                let path = Loader::resolve(None, filename);
+                let program = parse_program(&flags, output.get_tokens())
+                    .map(optimize(&flags)?)
+                    .and_then(|ast| Program::with_path(ast, path));
+
+                let result = match (backend, program) {
+                    (_, Err(errors)) => fail(&errors),
+                    ("tree", Ok(_)) if trace => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "--trace is only supported by --backend=vm",
+                        ))
+                    }
+                    ("tree", Ok(program)) => {
+                        let mut interpreter = interpreter();
+                        interpreter.set_loader(loader(&flags)?);
+                        if let Some(seed) = seed(&flags)? {
+                            interpreter.stdlib().seed(seed);
+                        }
+                        interpreter.interpret(&program)
+                    }
+                    ("vm", Ok(program)) => match Compiler::compile(program.ast()) {
+                        Ok(function) => vm(&flags, filename)?.interpret(function),
+                        Err(errors) => fail(&errors),
+                    },
+                    (backend, Ok(_)) => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("Unknown backend: {backend}. Expected one of tree, vm"),
+                        ))
+                    }
+                };
+
+                if let Err(err) = result {
+                    eprintln!("{err}");
+                    std::process::exit(CODE_RUNTIME_ERROR);
+                }
+
+                Ok(())
+            }
+        }
+        "compile" => {
+            let output = tokenize(&flags, file_contents)?;
+
+            if !output.get_errors().is_empty() {
+                Tokenizer::serialize(&[], output.get_errors())
+            } else {
+                let function = parse_program(&flags, output.get_tokens())
+                    .map(optimize(&flags)?)
+                    .and_then(Program::new)
+                    .and_then(|program| Compiler::compile(program.ast()));
+
+                match function {
+                    Ok(function) => {
+                        let path = match option(&flags, "output") {
+                            Some(path) => PathBuf::from(path),
+                            None => Path::new(filename).with_extension("loxc"),
+                        };
+                        std::fs::write(path, Bytecode::serialize(&function))
+                    }
+                    Err(errors) => fail(&errors),
+                }
+            }
+        }
+        "disassemble" => {
+            let output = tokenize(&flags, file_contents)?;
+
+            if !output.get_errors().is_empty() {
+                Tokenizer::serialize(&[], output.get_errors())
+            } else {
+                let function = parse_program(&flags, output.get_tokens())
+                    .map(optimize(&flags)?)
+                    .and_then(Program::new)
+                    .and_then(|program| Compiler::compile(program.ast()));
+
+                match function {
+                    Ok(function) => {
+                        print!("{}", Disassembler::disassemble(&function));
+                        Ok(())
+                    }
+                    Err(errors) => fail(&errors),
+                }
+            }
+        }
+        "fmt" => {
+            let output = tokenize(&flags, file_contents.clone())?;
+
+            if !output.get_errors().is_empty() {
+                Tokenizer::serialize(&[], output.get_errors())
+            } else {
+                match parse_program(&flags, output.get_tokens()) {
+                    Ok(ast) => {
+                        let formatted =
+                            LoxPrinter::print(&file_contents, output.get_tokens(), &ast);
 
-            println!("{expressions}");
-            Ok(())
+                        if has_flag(&flags, "check") {
+                            if formatted != file_contents {
+                                eprintln!("{filename} is not formatted");
+                                std::process::exit(CODE_UNFORMATTED);
+                            }
+                        } else if has_flag(&flags, "write") {
+                            std::fs::write(filename, formatted)?;
+                        } else {
+                            print!("{formatted}");
+                        }
+
+                        Ok(())
+                    }
+                    Err(errors) => fail(&errors),
+                }
+            }
         }
         _ => Err(std::io::Error::new(
             std::io::ErrorKind::NotFound,
@@ -44,3 +254,100 @@ fn main() -> Result<(), std::io::Error> {
         std::process::exit(CODE_ERROR)
     }
 }
+
+fn option<'a>(flags: &[&'a String], name: &str) -> Option<&'a str> {
+    flags.iter().find_map(|flag| {
+        flag.strip_prefix("--")
+            .and_then(|flag| flag.strip_prefix(name))
+            .and_then(|value| value.strip_prefix('='))
+    })
+}
+
+fn opt_level(flags: &[&String]) -> Result<OptLevel, std::io::Error> {
+    match option(flags, "opt-level") {
+        Some(level) => level.parse(),
+        None => Ok(OptLevel::default()),
+    }
+}
+
+/// The optimization pass selected by `--opt-level`, off by default.
+fn optimize(flags: &[&String]) -> Result<impl FnOnce(Ast) -> Ast, std::io::Error> {
+    let level = opt_level(flags)?;
+
+    Ok(move |mut ast: Ast| {
+        Optimizer::optimize(&mut ast, level);
+        ast
+    })
+}
+
+/// Tokenizes with the syntax extensions enabled by `--extensions`.
+fn tokenize(flags: &[&String], file_contents: String) -> Result<TokenizerOutput, std::io::Error> {
+    Tokenizer::tokenize_with(file_contents, has_flag(flags, "extensions"))
+}
+
+/// Parses an expression with the syntax extensions enabled by `--extensions`.
+fn parse_tokens(flags: &[&String], tokens: &[Token]) -> Result<Ast, ParseExprError> {
+    Parser::parse_tokens_with(tokens, has_flag(flags, "extensions"))
+}
+
+/// Parses a program with the syntax extensions enabled by `--extensions`.
+fn parse_program(flags: &[&String], tokens: &[Token]) -> Result<Ast, Vec<ParseExprError>> {
+    Parser::parse_program_with(tokens, has_flag(flags, "extensions"))
+}
+
+/// A tree-walker that may use most of the stack `main` runs it on.
+fn interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_stack_limit(STACK_SIZE / 2);
+    interpreter
+}
+
+/// Imported files get the same `--extensions` and `--opt-level` as the script.
+fn loader(flags: &[&String]) -> Result<Loader, std::io::Error> {
+    Ok(Loader::new(
+        has_flag(flags, "extensions"),
+        opt_level(flags)?,
+    ))
+}
+
+fn has_flag(flags: &[&String], name: &str) -> bool {
+    flags
+        .iter()
+        .any(|flag| flag.strip_prefix("--") == Some(name))
+}
+
+/// The `random()` seed given by `--seed`, if any.
+fn seed(flags: &[&String]) -> Result<Option<u64>, std::io::Error> {
+    option(flags, "seed")
+        .map(|seed| {
+            seed.parse().map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid seed: {seed}. Expected a non-negative integer"),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// A VM running `filename`, configured by the `--trace`, `--gc-stress`,
+/// `--gc-log` and `--seed` flags.
+fn vm(flags: &[&String], filename: &str) -> Result<Vm, std::io::Error> {
+    let mut vm = Vm::new();
+    vm.set_script_path(Path::new(filename));
+    vm.set_loader(loader(flags)?);
+    vm.set_trace(has_flag(flags, "trace"));
+    vm.set_gc_stress(has_flag(flags, "gc-stress"));
+    vm.set_gc_log(has_flag(flags, "gc-log"));
+    if let Some(seed) = seed(flags)? {
+        vm.stdlib().seed(seed);
+    }
+
+    Ok(vm)
+}
+
+/// Prints compile-time errors and exits with the data error code.
+fn fail(errors: &[ParseExprError]) -> ! {
+    errors.iter().for_each(|err| eprintln!("{err}"));
+    std::process::exit(CODE_ERROR)
+}
@@ -0,0 +1,101 @@
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    interpreter::Program,
+    parser::{
+        optimizer::{OptLevel, Optimizer},
+        Parser,
+    },
+    tokenizer::Tokenizer,
+};
+
+/// Reads the files named by `import` statements. Both backends keep their own
+/// module cache and only go through the loader the first time a path is seen.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Loader {
+    extensions: bool,
+    opt_level: OptLevel,
+}
+
+impl Loader {
+    pub fn new(extensions: bool, opt_level: OptLevel) -> Self {
+        Self {
+            extensions,
+            opt_level,
+        }
+    }
+
+    /// Import paths are relative to the importing file, or to the working
+    /// directory for code that wasn't read from a file.
+    pub fn resolve(importer: Option<&Path>, path: impl AsRef<Path>) -> PathBuf {
+        let path = match importer.and_then(Path::parent) {
+            Some(directory) => directory.join(path),
+            None => path.as_ref().to_path_buf(),
+        };
+
+        path.canonicalize().unwrap_or(path)
+    }
+
+    pub fn load(&self, path: &Path) -> Result<Rc<Program>, String> {
+        let name = file_name(path);
+        let source = std::fs::read_to_string(path)
+            .map_err(|err| format!("Can't import '{name}': {err}."))?;
+        let output = Tokenizer::tokenize_with(source, self.extensions)
+            .map_err(|err| format!("Can't import '{name}': {err}."))?;
+
+        let errors: Vec<String> = if output.get_errors().is_empty() {
+            let program = Parser::parse_program_with(output.get_tokens(), self.extensions)
+                .map(|mut ast| {
+                    Optimizer::optimize(&mut ast, self.opt_level);
+                    ast
+                })
+                .and_then(|ast| Program::with_path(ast, path.to_path_buf()));
+
+            match program {
+                Ok(program) => return Ok(program),
+                Err(errors) => errors.iter().map(ToString::to_string).collect(),
+            }
+        } else {
+            output
+                .get_errors()
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+
+        Err(format!("Can't import '{name}':\n{}", errors.join("\n")))
+    }
+}
+
+/// Reported when `path` is imported while it is still running; `loading` is
+/// the chain of files currently being executed, outermost first.
+pub fn cycle_error(loading: &[PathBuf], path: &Path) -> String {
+    let start = loading
+        .iter()
+        .position(|loading| loading == path)
+        .unwrap_or(0);
+    let chain: Vec<String> = loading[start..]
+        .iter()
+        .map(|path| file_name(path))
+        .chain(std::iter::once(file_name(path)))
+        .collect();
+
+    format!("Import cycle: {}.", chain.join(" -> "))
+}
+
+/// The name a module prints as, taken from its file name.
+pub fn module_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// How a file is named in error messages.
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
@@ -0,0 +1,170 @@
+use std::ops::{Index, IndexMut};
+
+use super::{expression::Expression, printer::AstPrinter, statement::Statement};
+use crate::tokenizer::{Span, Token};
+
+/// Stable handle to an expression stored in an [`Ast`]. Ids are never reused,
+/// so passes can key side tables (types, scopes, ...) by them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(u32);
+
+impl ExprId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Stable handle to a statement stored in an [`Ast`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StmtId(u32);
+
+impl StmtId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Identifier {
+    name: String,
+    span: Span,
+}
+
+impl Identifier {
+    pub fn new(name: &str, span: Span) -> Self {
+        Self {
+            name: name.to_string(),
+            span,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+impl From<&Token> for Identifier {
+    fn from(token: &Token) -> Self {
+        Self::new(token.get_lexeme(), *token.get_span())
+    }
+}
+
+/// Arena holding every node of a parsed program. Expressions and statements
+/// live in separate vectors, children refer to each other by id and spans are
+/// kept in side tables indexed the same way.
+#[derive(Clone, Debug, Default)]
+pub struct Ast {
+    expressions: Vec<Expression>,
+    expression_spans: Vec<Span>,
+    statements: Vec<Statement>,
+    statement_spans: Vec<Span>,
+    root: Option<ExprId>,
+    program: Vec<StmtId>,
+}
+
+impl Ast {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            expressions: Vec::with_capacity(capacity),
+            expression_spans: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, expression: Expression, span: Span) -> ExprId {
+        let id = ExprId(self.expressions.len() as u32);
+
+        self.expressions.push(expression);
+        self.expression_spans.push(span);
+
+        id
+    }
+
+    pub fn push_statement(&mut self, statement: Statement, span: Span) -> StmtId {
+        let id = StmtId(self.statements.len() as u32);
+
+        self.statements.push(statement);
+        self.statement_spans.push(span);
+
+        id
+    }
+
+    pub fn span(&self, id: ExprId) -> Span {
+        self.expression_spans[id.index()]
+    }
+
+    pub fn statement_span(&self, id: StmtId) -> Span {
+        self.statement_spans[id.index()]
+    }
+
+    /// The expression parsed by [`super::Parser::parse_tokens`].
+    pub fn root(&self) -> Option<ExprId> {
+        self.root
+    }
+
+    pub fn set_root(&mut self, root: ExprId) {
+        self.root = Some(root);
+    }
+
+    /// Top-level statements parsed by [`super::Parser::parse_program`].
+    pub fn program(&self) -> &[StmtId] {
+        &self.program
+    }
+
+    pub fn set_program(&mut self, program: Vec<StmtId>) {
+        self.program = program;
+    }
+
+    pub fn len(&self) -> usize {
+        self.expressions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+
+    pub fn statement_count(&self) -> usize {
+        self.statements.len()
+    }
+}
+
+impl Index<ExprId> for Ast {
+    type Output = Expression;
+
+    fn index(&self, id: ExprId) -> &Self::Output {
+        &self.expressions[id.index()]
+    }
+}
+
+impl IndexMut<ExprId> for Ast {
+    fn index_mut(&mut self, id: ExprId) -> &mut Self::Output {
+        &mut self.expressions[id.index()]
+    }
+}
+
+impl Index<StmtId> for Ast {
+    type Output = Statement;
+
+    fn index(&self, id: StmtId) -> &Self::Output {
+        &self.statements[id.index()]
+    }
+}
+
+impl IndexMut<StmtId> for Ast {
+    fn index_mut(&mut self, id: StmtId) -> &mut Self::Output {
+        &mut self.statements[id.index()]
+    }
+}
+
+impl std::fmt::Display for Ast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.root {
+            Some(root) => write!(f, "{}", AstPrinter::print(self, root)),
+            None => Ok(()),
+        }
+    }
+}
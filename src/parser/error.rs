@@ -1,29 +1,106 @@
-use crate::tokenizer::Token;
+use crate::tokenizer::{Span, Token, TokenType};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseExprErrorType {
     EmptyToken,
-    
+    UnexpectedToken,
+    UnclosedGroup,
+    Resolution,
+    Compile,
 }
 
+#[derive(Debug, Clone)]
 pub struct ParseExprError {
-    token: Token,
+    error_type: ParseExprErrorType,
+    span: Span,
+    lexeme: Option<String>,
     msg: String,
 }
 
 impl ParseExprError {
-    fn new(token: Token, msg: String) -> Self {
-        Self { token, msg }
+    fn new(error_type: ParseExprErrorType, span: Span, lexeme: Option<String>, msg: &str) -> Self {
+        Self {
+            error_type,
+            span,
+            lexeme,
+            msg: msg.to_string(),
+        }
+    }
+
+    pub(crate) fn empty() -> Self {
+        Self::new(
+            ParseExprErrorType::EmptyToken,
+            Span::default(),
+            None,
+            "Cannot parse empty token list",
+        )
+    }
+
+    pub(crate) fn unexpected(token: &Token, msg: &str) -> Self {
+        let lexeme = match token.get_type() {
+            TokenType::EOF => None,
+            _ => Some(token.get_lexeme().to_string()),
+        };
+
+        Self::new(
+            ParseExprErrorType::UnexpectedToken,
+            *token.get_span(),
+            lexeme,
+            msg,
+        )
+    }
+
+    pub(crate) fn unclosed_group(span: Span) -> Self {
+        Self::new(
+            ParseExprErrorType::UnclosedGroup,
+            span,
+            Some("(".to_string()),
+            "Expect ')' after expression.",
+        )
+    }
+
+    pub(crate) fn resolution(span: Span, lexeme: &str, msg: &str) -> Self {
+        Self::new(
+            ParseExprErrorType::Resolution,
+            span,
+            Some(lexeme.to_string()),
+            msg,
+        )
+    }
+
+    pub(crate) fn compile(span: Span, msg: &str) -> Self {
+        Self::new(ParseExprErrorType::Compile, span, None, msg)
+    }
+
+    pub fn get_type(&self) -> &ParseExprErrorType {
+        &self.error_type
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
     }
 }
 
 impl std::fmt::Display for ParseExprError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "[line {}]: Error at {}: {}",
-            self.token.get_line(),
-            self.token.get_literal(),
-            self.msg
-        )
+        match &self.lexeme {
+            None if self.error_type == ParseExprErrorType::Compile => {
+                write!(f, "[line {}] Error: {}", self.span.line, self.msg)
+            }
+            Some(lexeme) => write!(
+                f,
+                "[line {}] Error at '{}': {}",
+                self.span.line, lexeme, self.msg
+            ),
+            None => write!(f, "[line {}] Error at end: {}", self.span.line, self.msg),
+        }
+    }
+}
+
+impl std::error::Error for ParseExprError {}
+
+impl From<ParseExprError> for std::io::Error {
+    fn from(value: ParseExprError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, value.to_string())
     }
 }
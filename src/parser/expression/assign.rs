@@ -0,0 +1,21 @@
+use crate::parser::ast::{ExprId, Identifier};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Assign {
+    name: Identifier,
+    value: ExprId,
+}
+
+impl Assign {
+    pub fn new(name: Identifier, value: ExprId) -> Self {
+        Self { name, value }
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    pub fn value(&self) -> ExprId {
+        self.value
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{parser::ast::ExprId, tokenizer::Span};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call {
+    callee: ExprId,
+    arguments: Vec<ExprId>,
+    paren: Span,
+}
+
+impl Call {
+    pub fn new(callee: ExprId, arguments: Vec<ExprId>, paren: Span) -> Self {
+        Self {
+            callee,
+            arguments,
+            paren,
+        }
+    }
+
+    pub fn callee(&self) -> ExprId {
+        self.callee
+    }
+
+    pub fn arguments(&self) -> &[ExprId] {
+        &self.arguments
+    }
+
+    /// Span of the closing parenthesis, where call errors are reported.
+    pub fn paren_span(&self) -> Span {
+        self.paren
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{parser::ast::ExprId, tokenizer::Span};
+
+/// `condition ? then_branch : else_branch`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Conditional {
+    condition: ExprId,
+    then_branch: ExprId,
+    else_branch: ExprId,
+    question: Span,
+}
+
+impl Conditional {
+    pub fn new(
+        condition: ExprId,
+        then_branch: ExprId,
+        else_branch: ExprId,
+        question: Span,
+    ) -> Self {
+        Self {
+            condition,
+            then_branch,
+            else_branch,
+            question,
+        }
+    }
+
+    pub fn condition(&self) -> ExprId {
+        self.condition
+    }
+
+    pub fn then_branch(&self) -> ExprId {
+        self.then_branch
+    }
+
+    pub fn else_branch(&self) -> ExprId {
+        self.else_branch
+    }
+
+    pub fn question_span(&self) -> Span {
+        self.question
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{parser::ast::ExprId, tokenizer::Span};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Index {
+    object: ExprId,
+    index: ExprId,
+    bracket: Span,
+}
+
+impl Index {
+    pub fn new(object: ExprId, index: ExprId, bracket: Span) -> Self {
+        Self {
+            object,
+            index,
+            bracket,
+        }
+    }
+
+    pub fn object(&self) -> ExprId {
+        self.object
+    }
+
+    pub fn index(&self) -> ExprId {
+        self.index
+    }
+
+    /// Span of the closing bracket, where indexing errors are reported.
+    pub fn bracket_span(&self) -> Span {
+        self.bracket
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetIndex {
+    object: ExprId,
+    index: ExprId,
+    value: ExprId,
+    bracket: Span,
+}
+
+impl SetIndex {
+    pub fn new(object: ExprId, index: ExprId, value: ExprId, bracket: Span) -> Self {
+        Self {
+            object,
+            index,
+            value,
+            bracket,
+        }
+    }
+
+    pub fn object(&self) -> ExprId {
+        self.object
+    }
+
+    pub fn index(&self) -> ExprId {
+        self.index
+    }
+
+    pub fn value(&self) -> ExprId {
+        self.value
+    }
+
+    pub fn bracket_span(&self) -> Span {
+        self.bracket
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{parser::ast::ExprId, tokenizer::Span};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LogicalType {
+    And,
+    Or,
+}
+
+impl LogicalType {
+    pub fn get_lexeme(&self) -> &str {
+        match self {
+            LogicalType::And => "and",
+            LogicalType::Or => "or",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Logical {
+    logical_type: LogicalType,
+    left: ExprId,
+    right: ExprId,
+    operator: Span,
+}
+
+impl Logical {
+    pub fn new(logical_type: LogicalType, left: ExprId, right: ExprId, operator: Span) -> Self {
+        Self {
+            logical_type,
+            left,
+            right,
+            operator,
+        }
+    }
+
+    pub fn get_type(&self) -> &LogicalType {
+        &self.logical_type
+    }
+
+    pub fn left(&self) -> ExprId {
+        self.left
+    }
+
+    pub fn right(&self) -> ExprId {
+        self.right
+    }
+
+    pub fn operator_span(&self) -> Span {
+        self.operator
+    }
+}
@@ -0,0 +1,472 @@
+use super::{
+    assign::Assign,
+    binary::{Binary, BinaryType},
+    call::Call,
+    conditional::Conditional,
+    index::{Index, SetIndex},
+    logical::{Logical, LogicalType},
+    property::{Get, Set},
+    unary::{Unary, UnaryType},
+    update::{Update, UpdateKind},
+    Expression,
+};
+use crate::{
+    parser::{
+        ast::{ExprId, Identifier},
+        error::ParseExprError,
+        Parser,
+    },
+    tokenizer::{Span, Token, TokenType},
+};
+
+const MAX_ARGUMENTS: usize = 255;
+
+/// Name given to the `Function` of every anonymous function expression.
+const ANONYMOUS: &str = "anonymous";
+
+impl Parser<'_> {
+    pub(in crate::parser) fn expression(&mut self) -> Result<ExprId, ParseExprError> {
+        self.comma()
+    }
+
+    fn comma(&mut self) -> Result<ExprId, ParseExprError> {
+        self.binary(Parser::assignment, &[TokenType::Comma])
+    }
+
+    /// A single expression where a comma separates list items, as in arguments.
+    fn assignment(&mut self) -> Result<ExprId, ParseExprError> {
+        let target = self.conditional()?;
+
+        let Some(equals) = self.match_any(&[
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+        ]) else {
+            return Ok(target);
+        };
+
+        let value = self.assignment()?;
+        let span = self.ast.span(target).merge(self.ast.span(value));
+
+        if let Some(operator_type) = compound_type(equals.get_type()) {
+            let kind = UpdateKind::Compound(value);
+            return Ok(self.update(target, operator_type, kind, equals, span));
+        }
+
+        let expression = match &self.ast[target] {
+            Expression::Variable(name) => Expression::Assign(Assign::new(name.clone(), value)),
+            Expression::Get(get) => {
+                Expression::Set(Set::new(get.object(), get.get_name().clone(), value))
+            }
+            Expression::Index(index) => Expression::SetIndex(SetIndex::new(
+                index.object(),
+                index.index(),
+                value,
+                index.bracket_span(),
+            )),
+            _ => {
+                self.errors.push(ParseExprError::unexpected(
+                    equals,
+                    "Invalid assignment target.",
+                ));
+                return Ok(target);
+            }
+        };
+
+        Ok(self.ast.push(expression, span))
+    }
+
+    fn conditional(&mut self) -> Result<ExprId, ParseExprError> {
+        let condition = self.or()?;
+
+        let Some(question) = self.match_any(&[TokenType::Question]) else {
+            return Ok(condition);
+        };
+        let question = *question.get_span();
+
+        let then_branch = self.expression()?;
+        self.consume(
+            TokenType::Colon,
+            "Expect ':' after then branch of conditional expression.",
+        )?;
+        let else_branch = self.conditional()?;
+
+        let span = self.ast.span(condition).merge(self.ast.span(else_branch));
+        let conditional = Conditional::new(condition, then_branch, else_branch, question);
+
+        Ok(self.ast.push(Expression::Conditional(conditional), span))
+    }
+
+    fn or(&mut self) -> Result<ExprId, ParseExprError> {
+        self.logical(Parser::and, TokenType::Or)
+    }
+
+    fn and(&mut self) -> Result<ExprId, ParseExprError> {
+        self.logical(Parser::equality, TokenType::And)
+    }
+
+    fn logical(
+        &mut self,
+        operand: fn(&mut Self) -> Result<ExprId, ParseExprError>,
+        operator: TokenType,
+    ) -> Result<ExprId, ParseExprError> {
+        let mut left = operand(self)?;
+
+        while let Some(token) = self.match_any(std::slice::from_ref(&operator)) {
+            let logical_type = LogicalType::try_from(token.get_type())
+                .map_err(|err| ParseExprError::unexpected(token, &err.to_string()))?;
+            let right = operand(self)?;
+            let span = self.ast.span(left).merge(self.ast.span(right));
+            let logical = Logical::new(logical_type, left, right, *token.get_span());
+
+            left = self.ast.push(Expression::Logical(logical), span);
+        }
+
+        Ok(left)
+    }
+
+    fn equality(&mut self) -> Result<ExprId, ParseExprError> {
+        use TokenType::*;
+        self.binary(Parser::comparison, &[BangEqual, EqualEqual])
+    }
+
+    fn comparison(&mut self) -> Result<ExprId, ParseExprError> {
+        use TokenType::*;
+        self.binary(
+            Parser::bitwise_or,
+            &[Greater, GreaterEqual, Less, LessEqual],
+        )
+    }
+
+    fn bitwise_or(&mut self) -> Result<ExprId, ParseExprError> {
+        self.binary(Parser::bitwise_xor, &[TokenType::Pipe])
+    }
+
+    fn bitwise_xor(&mut self) -> Result<ExprId, ParseExprError> {
+        self.binary(Parser::bitwise_and, &[TokenType::Caret])
+    }
+
+    fn bitwise_and(&mut self) -> Result<ExprId, ParseExprError> {
+        self.binary(Parser::shift, &[TokenType::Ampersand])
+    }
+
+    fn shift(&mut self) -> Result<ExprId, ParseExprError> {
+        use TokenType::*;
+        self.binary(Parser::term, &[LessLess, GreaterGreater])
+    }
+
+    fn term(&mut self) -> Result<ExprId, ParseExprError> {
+        use TokenType::*;
+        self.binary(Parser::factor, &[Minus, Plus])
+    }
+
+    fn factor(&mut self) -> Result<ExprId, ParseExprError> {
+        use TokenType::*;
+        self.binary(Parser::unary, &[Slash, Star, Percent])
+    }
+
+    fn binary(
+        &mut self,
+        operand: fn(&mut Self) -> Result<ExprId, ParseExprError>,
+        operators: &[TokenType],
+    ) -> Result<ExprId, ParseExprError> {
+        let mut left = operand(self)?;
+
+        while let Some(token) = self.match_any(operators) {
+            let binary_type = BinaryType::try_from(token.get_type())
+                .map_err(|err| ParseExprError::unexpected(token, &err.to_string()))?;
+            let right = operand(self)?;
+            let span = self.ast.span(left).merge(self.ast.span(right));
+            let binary = Binary::new(binary_type, left, right, *token.get_span());
+
+            left = self.ast.push(Expression::Binary(binary), span);
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<ExprId, ParseExprError> {
+        if let Some(operator) = self.match_any(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let target = self.unary()?;
+            let span = operator.get_span().merge(self.ast.span(target));
+            let operator_type = increment_type(operator.get_type());
+
+            return Ok(self.update(target, operator_type, UpdateKind::Prefix, operator, span));
+        }
+
+        let unary_type = match self.peek().get_type() {
+            TokenType::Bang => UnaryType::Bang,
+            TokenType::Minus => UnaryType::Minus,
+            TokenType::Tilde => UnaryType::Tilde,
+            _ => return self.exponent(),
+        };
+
+        let operator = *self.advance().get_span();
+        let expression = self.unary()?;
+        let span = operator.merge(self.ast.span(expression));
+        let unary = Unary::new(unary_type, expression, operator);
+
+        Ok(self.ast.push(Expression::Unary(unary), span))
+    }
+
+    /// `**` binds tighter than a unary operator on its left, so `-2 ** 2` is
+    /// `-(2 ** 2)`, and is right-associative through the unary on its right.
+    fn exponent(&mut self) -> Result<ExprId, ParseExprError> {
+        let base = self.postfix()?;
+
+        let Some(token) = self.match_any(&[TokenType::StarStar]) else {
+            return Ok(base);
+        };
+        let exponent = self.unary()?;
+        let span = self.ast.span(base).merge(self.ast.span(exponent));
+        let binary = Binary::new(BinaryType::StarStar, base, exponent, *token.get_span());
+
+        Ok(self.ast.push(Expression::Binary(binary), span))
+    }
+
+    fn postfix(&mut self) -> Result<ExprId, ParseExprError> {
+        let target = self.call()?;
+
+        let Some(operator) = self.match_any(&[TokenType::PlusPlus, TokenType::MinusMinus]) else {
+            return Ok(target);
+        };
+        let span = self.ast.span(target).merge(*operator.get_span());
+        let operator_type = increment_type(operator.get_type());
+
+        Ok(self.update(target, operator_type, UpdateKind::Postfix, operator, span))
+    }
+
+    /// Only variables, properties and index expressions can be updated in
+    /// place; anything else is reported and parsing continues with `target`.
+    fn update(
+        &mut self,
+        target: ExprId,
+        operator_type: BinaryType,
+        kind: UpdateKind,
+        operator: &Token,
+        span: Span,
+    ) -> ExprId {
+        if !matches!(
+            self.ast[target],
+            Expression::Variable(_) | Expression::Get(_) | Expression::Index(_)
+        ) {
+            self.errors.push(ParseExprError::unexpected(
+                operator,
+                "Invalid assignment target.",
+            ));
+            return target;
+        }
+
+        let update = Update::new(target, operator_type, kind, *operator.get_span());
+        self.ast.push(Expression::Update(update), span)
+    }
+
+    fn call(&mut self) -> Result<ExprId, ParseExprError> {
+        let mut expression = self.primary()?;
+
+        loop {
+            if self.match_any(&[TokenType::LeftParenthesis]).is_some() {
+                expression = self.finish_call(expression)?;
+            } else if self.match_any(&[TokenType::Dot]).is_some() {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                let span = self.ast.span(expression).merge(*name.get_span());
+                let get = Get::new(expression, Identifier::from(name));
+
+                expression = self.ast.push(Expression::Get(get), span);
+            } else if self.match_any(&[TokenType::LeftBracket]).is_some() {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                let span = self.ast.span(expression).merge(*bracket.get_span());
+                let index = Index::new(expression, index, *bracket.get_span());
+
+                expression = self.ast.push(Expression::Index(index), span);
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    /// `"a ${b} c"` arrives as an `Interpolation` token for every part that
+    /// ends in `${`, each followed by the expression's tokens and a `}`, and
+    /// a `String` token for the rest of the string.
+    fn interpolation(&mut self) -> Result<ExprId, ParseExprError> {
+        let start = *self.peek().get_span();
+        let mut parts = Vec::new();
+
+        loop {
+            let token = self.advance();
+            let span = *token.get_span();
+
+            if !token.get_literal().is_empty() {
+                parts.push(self.ast.push(Expression::String(token.get_literal()), span));
+            }
+            if token.get_type() == &TokenType::String {
+                let span = start.merge(span);
+                return Ok(self.ast.push(Expression::Interpolation(parts), span));
+            }
+
+            parts.push(self.expression()?);
+            self.consume(
+                TokenType::RightCurly,
+                "Expect '}' after interpolated expression.",
+            )?;
+
+            if !matches!(
+                self.peek().get_type(),
+                TokenType::Interpolation | TokenType::String
+            ) {
+                return Err(ParseExprError::unexpected(
+                    self.peek(),
+                    "Unterminated string.",
+                ));
+            }
+        }
+    }
+
+    fn finish_call(&mut self, callee: ExprId) -> Result<ExprId, ParseExprError> {
+        let mut arguments = Vec::new();
+
+        if !self.check(&TokenType::RightParenthesis) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    self.errors.push(ParseExprError::unexpected(
+                        self.peek(),
+                        "Can't have more than 255 arguments.",
+                    ));
+                }
+                arguments.push(self.assignment()?);
+
+                if self.match_any(&[TokenType::Comma]).is_none() {
+                    break;
+                }
+            }
+        }
+
+        let paren = self.consume(TokenType::RightParenthesis, "Expect ')' after arguments.")?;
+        let span = self.ast.span(callee).merge(*paren.get_span());
+        let call = Call::new(callee, arguments, *paren.get_span());
+
+        Ok(self.ast.push(Expression::Call(call), span))
+    }
+
+    fn primary(&mut self) -> Result<ExprId, ParseExprError> {
+        let token = self.peek();
+        let span = *token.get_span();
+
+        let expression = match token.get_type() {
+            TokenType::Number => Expression::Number(token.get_literal()),
+            TokenType::String => Expression::String(token.get_literal()),
+            TokenType::True => Expression::True,
+            TokenType::False => Expression::False,
+            TokenType::Nil => Expression::Nil,
+            TokenType::This => Expression::This,
+            TokenType::Identifier => Expression::Variable(Identifier::from(token)),
+            TokenType::Interpolation => return self.interpolation(),
+            TokenType::Super => {
+                self.advance();
+                self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+                let method =
+                    self.consume(TokenType::Identifier, "Expect superclass method name.")?;
+                let span = span.merge(*method.get_span());
+
+                return Ok(self
+                    .ast
+                    .push(Expression::Super(Identifier::from(method)), span));
+            }
+            TokenType::LeftBracket => {
+                self.advance();
+                let mut elements = Vec::new();
+
+                if !self.check(&TokenType::RightBracket) {
+                    loop {
+                        elements.push(self.assignment()?);
+
+                        if self.match_any(&[TokenType::Comma]).is_none() {
+                            break;
+                        }
+                    }
+                }
+
+                let bracket =
+                    self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+                let span = span.merge(*bracket.get_span());
+
+                return Ok(self.ast.push(Expression::List(elements), span));
+            }
+            TokenType::LeftCurly if self.extensions => {
+                self.advance();
+                let mut entries = Vec::new();
+
+                if !self.check(&TokenType::RightCurly) {
+                    loop {
+                        let key = self.assignment()?;
+                        self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                        entries.push((key, self.assignment()?));
+
+                        if self.match_any(&[TokenType::Comma]).is_none() {
+                            break;
+                        }
+                    }
+                }
+
+                let brace = self.consume(TokenType::RightCurly, "Expect '}' after map entries.")?;
+                let span = span.merge(*brace.get_span());
+
+                return Ok(self.ast.push(Expression::Map(entries), span));
+            }
+            TokenType::Fun => {
+                self.advance();
+                if self.check(&TokenType::Identifier) {
+                    return Err(ParseExprError::unexpected(
+                        self.peek(),
+                        "Named functions can only be declared as statements.",
+                    ));
+                }
+                self.consume(TokenType::LeftParenthesis, "Expect '(' after 'fun'.")?;
+
+                let name = Identifier::new(ANONYMOUS, span);
+                let function = self.function_body(name, "function", span)?;
+                let span = self.ast.statement_span(function);
+
+                return Ok(self.ast.push(Expression::Lambda(function), span));
+            }
+            TokenType::LeftParenthesis => {
+                self.advance();
+                let inner = self.expression()?;
+
+                if !self.check(&TokenType::RightParenthesis) {
+                    return Err(ParseExprError::unclosed_group(span));
+                }
+
+                let span = span.merge(*self.advance().get_span());
+
+                return Ok(self.ast.push(Expression::Grouping(inner), span));
+            }
+            _ => return Err(ParseExprError::unexpected(token, "Expect expression.")),
+        };
+
+        self.advance();
+
+        Ok(self.ast.push(expression, span))
+    }
+}
+
+fn compound_type(token_type: &TokenType) -> Option<BinaryType> {
+    match token_type {
+        TokenType::PlusEqual => Some(BinaryType::Plus),
+        TokenType::MinusEqual => Some(BinaryType::Minus),
+        TokenType::StarEqual => Some(BinaryType::Star),
+        TokenType::SlashEqual => Some(BinaryType::Slash),
+        _ => None,
+    }
+}
+
+fn increment_type(token_type: &TokenType) -> BinaryType {
+    match token_type {
+        TokenType::PlusPlus => BinaryType::Plus,
+        _ => BinaryType::Minus,
+    }
+}
@@ -0,0 +1,50 @@
+use crate::parser::ast::{ExprId, Identifier};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Get {
+    object: ExprId,
+    name: Identifier,
+}
+
+impl Get {
+    pub fn new(object: ExprId, name: Identifier) -> Self {
+        Self { object, name }
+    }
+
+    pub fn object(&self) -> ExprId {
+        self.object
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Set {
+    object: ExprId,
+    name: Identifier,
+    value: ExprId,
+}
+
+impl Set {
+    pub fn new(object: ExprId, name: Identifier, value: ExprId) -> Self {
+        Self {
+            object,
+            name,
+            value,
+        }
+    }
+
+    pub fn object(&self) -> ExprId {
+        self.object
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    pub fn value(&self) -> ExprId {
+        self.value
+    }
+}
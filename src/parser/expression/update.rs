@@ -0,0 +1,74 @@
+use super::binary::BinaryType;
+use crate::{parser::ast::ExprId, tokenizer::Span};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpdateKind {
+    /// `target op= value`
+    Compound(ExprId),
+    /// `++target`, yielding the new value.
+    Prefix,
+    /// `target++`, yielding the old value.
+    Postfix,
+}
+
+/// Reads `target`, combines it with the value (or `1` for `++`/`--`) and
+/// writes the result back. The target is a variable, property or index
+/// expression and its object and key are only evaluated once.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Update {
+    target: ExprId,
+    operator_type: BinaryType,
+    kind: UpdateKind,
+    operator: Span,
+}
+
+impl Update {
+    pub fn new(
+        target: ExprId,
+        operator_type: BinaryType,
+        kind: UpdateKind,
+        operator: Span,
+    ) -> Self {
+        Self {
+            target,
+            operator_type,
+            kind,
+            operator,
+        }
+    }
+
+    pub fn target(&self) -> ExprId {
+        self.target
+    }
+
+    pub fn get_type(&self) -> &BinaryType {
+        &self.operator_type
+    }
+
+    pub fn kind(&self) -> UpdateKind {
+        self.kind
+    }
+
+    /// The right-hand side of a compound assignment.
+    pub fn value(&self) -> Option<ExprId> {
+        match self.kind {
+            UpdateKind::Compound(value) => Some(value),
+            UpdateKind::Prefix | UpdateKind::Postfix => None,
+        }
+    }
+
+    pub fn operator_span(&self) -> Span {
+        self.operator
+    }
+
+    pub fn get_lexeme(&self) -> &str {
+        match (self.kind, self.operator_type) {
+            (UpdateKind::Compound(_), BinaryType::Plus) => "+=",
+            (UpdateKind::Compound(_), BinaryType::Minus) => "-=",
+            (UpdateKind::Compound(_), BinaryType::Star) => "*=",
+            (UpdateKind::Compound(_), _) => "/=",
+            (_, BinaryType::Plus) => "++",
+            _ => "--",
+        }
+    }
+}
@@ -1,144 +1,188 @@
-use std::{fmt::format, io::{Error, ErrorKind}};
+use std::io::{Error, ErrorKind};
 
+use ast::Ast;
 use error::ParseExprError;
-use expression::{
-    binary::{Binary, BinaryType},
-    unary::Unary,
-    AddExpr, Expression, IsPartial,
-};
+use expression::{binary::BinaryType, logical::LogicalType};
 
-use crate::tokenizer::{token::Token, token_type::TokenType};
+use crate::tokenizer::{token::Token, token_type::TokenType, Span};
 
-pub mod expression;
+pub mod ast;
 pub mod error;
+pub mod expression;
+pub mod optimizer;
+pub mod printer;
+pub mod statement;
 mod tests;
+pub mod visitor;
+
+pub struct Parser<'a> {
+    tokens: Vec<&'a Token>,
+    current: usize,
+    ast: Ast,
+    errors: Vec<ParseExprError>,
+    /// Whether extension syntax the tokenizer can't rule out on its own, such
+    /// as map literals, is accepted.
+    extensions: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses a single expression, as used by the `parse` command.
+    pub fn parse_tokens(tokens: &'a [Token]) -> Result<Ast, ParseExprError> {
+        Parser::parse_tokens_with(tokens, false)
+    }
+
+    /// Parses a single expression with the syntax extensions enabled when
+    /// `extensions` is set, matching [`crate::tokenizer::Tokenizer::tokenize_with`].
+    pub fn parse_tokens_with(tokens: &'a [Token], extensions: bool) -> Result<Ast, ParseExprError> {
+        let mut parser = Parser::new(tokens, extensions);
+
+        if parser.tokens.is_empty() {
+            return Err(ParseExprError::empty());
+        }
 
-pub struct Parser;
+        let root = parser.expression()?;
 
-impl Parser {
-    pub fn parse_tokens(tokens: &Vec<Token>) -> Result<Expression, ParseExprError> {
-        if tokens.is_empty() {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "Cannot parse empty token list",
+        if !parser.is_at_end() {
+            return Err(ParseExprError::unexpected(
+                parser.peek(),
+                "Expect end of expression.",
             ));
         }
+        if let Some(err) = parser.errors.into_iter().next() {
+            return Err(err);
+        }
 
-        let mut expr = Expression::None;
-        let mut groups = Vec::new();
-        let mut groups_line = Vec::new();
+        parser.ast.set_root(root);
 
-        for token in tokens.iter().filter(Parser::token_filter()) {
-            let expr_base = groups.last().unwrap_or(&expr);
+        Ok(parser.ast)
+    }
 
-            let result = match token.get_type() {
-                TokenType::LeftParenthesis => {
-                    groups.push(Expression::None);
-                    groups_line.push(token.get_line());
-                    continue;
-                }
-                TokenType::RightParenthesis => match groups.pop() {
-                    Some(group_expr) => {
-                        if groups.is_empty() {
-                            groups_line.pop();
-                            expr.add_expr(Expression::Grouping(Box::new(group_expr)))
-                        } else {
-                            groups_line.pop();
-                            groups
-                                .last()
-                                .unwrap()
-                                .add_expr(Expression::Grouping(Box::new(group_expr)))
-                        }
-                    }
-                    Option::None => Err(format!("[line {}] No left paranthesis", token.get_line())),
-                },
-                TokenType::Minus if expr_base.is_binary() && expr_base.is_partial() => expr_base
-                    .get_binary()
-                    .and_then(|b| b.add_expr(Unary::new_minus_expr(Expression::None))),
-                TokenType::Minus if expr_base.is_none() => {
-                    Ok(Unary::new_minus_expr(Expression::None))
-                }
-                TokenType::Minus => expr_base.add_expr(Binary::new_empty_expr(BinaryType::Minus)),
-                TokenType::Bang => expr_base.add_expr(Unary::new_bang_expr(Expression::None)),
-                TokenType::Number => expr_base.add_expr(Expression::Number(token.get_literal())),
-                TokenType::String => expr_base.add_expr(Expression::String(token.get_literal())),
-                TokenType::True => expr_base.add_expr(Expression::True),
-                TokenType::False => expr_base.add_expr(Expression::False),
-                TokenType::Nil => expr_base.add_expr(Expression::Nil),
-                TokenType::Plus
-                | TokenType::Slash
-                | TokenType::Star
-                | TokenType::BangEqual
-                | TokenType::EqualEqual
-                | TokenType::Greater
-                | TokenType::GreaterEqual
-                | TokenType::Less
-                | TokenType::LessEqual => expr_base.add_expr(Parser::new_binary(token.get_type())?),
-                t => {
-                    eprintln!("Unknown token: {t}");
-                    continue;
-                }
-            };
-
-            match result {
-                Ok(expression) => {
-                    if groups.is_empty() {
-                        expr = expression
-                    } else {
-                        groups.pop();
-                        groups_line.pop();
-                        groups.push(expression);
-                    }
+    /// Parses a whole program. On failure every error found is returned; the
+    /// parser resynchronizes at statement boundaries to keep reporting.
+    pub fn parse_program(tokens: &'a [Token]) -> Result<Ast, Vec<ParseExprError>> {
+        Parser::parse_program_with(tokens, false)
+    }
+
+    /// Parses a whole program with the syntax extensions enabled when
+    /// `extensions` is set.
+    pub fn parse_program_with(
+        tokens: &'a [Token],
+        extensions: bool,
+    ) -> Result<Ast, Vec<ParseExprError>> {
+        let mut parser = Parser::new(tokens, extensions);
+
+        if parser.tokens.is_empty() {
+            return Err(vec![ParseExprError::empty()]);
+        }
+
+        let mut program = Vec::new();
+
+        while !parser.is_at_end() {
+            match parser.declaration() {
+                Ok(statement) => program.push(statement),
+                Err(err) => {
+                    parser.errors.push(err);
+                    parser.synchronize();
                 }
-                Err(err) => panic!("{err}"),
             }
         }
 
-        if !groups.is_empty() {
-            Err(Error::new(ErrorKind::InvalidInput, format!("")))
-        } else {
-            Ok(expr)
+        if !parser.errors.is_empty() {
+            return Err(parser.errors);
+        }
+
+        parser.ast.set_program(program);
+
+        Ok(parser.ast)
+    }
+
+    fn new(tokens: &'a [Token], extensions: bool) -> Self {
+        let tokens: Vec<&Token> = tokens.iter().filter(Parser::token_filter()).collect();
+
+        Self {
+            ast: Ast::with_capacity(tokens.len()),
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+            extensions,
         }
     }
 
-    fn new_binary(token: &TokenType) -> Result<Expression, Error> {
+    fn synchronize(&mut self) {
         use TokenType::*;
-        match token {
-            Plus | Slash | Star | BangEqual | EqualEqual | Greater | GreaterEqual | Less
-            | LessEqual => Ok(Binary::new_empty_expr(BinaryType::try_from(token)?)),
-            _ => Err(Error::new(
-                ErrorKind::Unsupported,
-                format!("Can't create binary from: {}", token),
-            )),
+
+        self.advance();
+
+        while !self.is_at_end() {
+            if matches!(self.previous().get_type(), Semicolon) {
+                return;
+            }
+            if matches!(
+                self.peek().get_type(),
+                Class | Fun | Var | For | If | While | Print | Return | Import | Throw | Try
+            ) {
+                return;
+            }
+
+            self.advance();
+        }
+    }
+
+    fn consume(&mut self, token_type: TokenType, msg: &str) -> Result<&'a Token, ParseExprError> {
+        if self.check(&token_type) {
+            Ok(self.advance())
+        } else {
+            Err(ParseExprError::unexpected(self.peek(), msg))
+        }
+    }
+
+    fn check(&self, token_type: &TokenType) -> bool {
+        self.peek().get_type() == token_type
+    }
+
+    fn match_any(&mut self, types: &[TokenType]) -> Option<&'a Token> {
+        if types.contains(self.peek().get_type()) {
+            Some(self.advance())
+        } else {
+            None
+        }
+    }
+
+    fn peek(&self) -> &'a Token {
+        self.tokens[self.current.min(self.tokens.len() - 1)]
+    }
+
+    /// The token `distance` tokens after the current one.
+    fn peek_at(&self, distance: usize) -> &'a Token {
+        self.tokens[(self.current + distance).min(self.tokens.len() - 1)]
+    }
+
+    fn previous(&self) -> &'a Token {
+        self.tokens[self.current.saturating_sub(1).min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> &'a Token {
+        let token = self.peek();
+
+        if !self.is_at_end() {
+            self.current += 1;
         }
+
+        token
+    }
+
+    /// Span from `start` up to the last consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.merge(*self.previous().get_span())
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current >= self.tokens.len() || matches!(self.peek().get_type(), TokenType::EOF)
     }
 
     fn token_filter() -> impl FnMut(&&Token) -> bool {
         use TokenType::*;
-        |t: &&Token| {
-            matches!(
-                t.get_type(),
-                LeftParenthesis
-                    | RightParenthesis
-                    | Bang
-                    | BangEqual
-                    | Equal
-                    | EqualEqual
-                    | False
-                    | Greater
-                    | GreaterEqual
-                    | Less
-                    | LessEqual
-                    | Minus
-                    | Nil
-                    | Number
-                    | Plus
-                    | Slash
-                    | Star
-                    | String
-                    | True
-            )
-        }
+        |t: &&Token| !matches!(t.get_type(), Whitespace | Tab | Unkonwn | Comment)
     }
 }
 
@@ -148,6 +192,7 @@ impl TryFrom<&TokenType> for BinaryType {
     fn try_from(value: &TokenType) -> Result<Self, Self::Error> {
         match value {
             TokenType::Plus => Ok(BinaryType::Plus),
+            TokenType::Minus => Ok(BinaryType::Minus),
             TokenType::Slash => Ok(BinaryType::Slash),
             TokenType::Star => Ok(BinaryType::Star),
             TokenType::BangEqual => Ok(BinaryType::BangEqual),
@@ -156,9 +201,32 @@ impl TryFrom<&TokenType> for BinaryType {
             TokenType::GreaterEqual => Ok(BinaryType::GreaterEqual),
             TokenType::Less => Ok(BinaryType::Less),
             TokenType::LessEqual => Ok(BinaryType::LessEqual),
+            TokenType::Percent => Ok(BinaryType::Percent),
+            TokenType::StarStar => Ok(BinaryType::StarStar),
+            TokenType::Ampersand => Ok(BinaryType::Ampersand),
+            TokenType::Pipe => Ok(BinaryType::Pipe),
+            TokenType::Caret => Ok(BinaryType::Caret),
+            TokenType::LessLess => Ok(BinaryType::LessLess),
+            TokenType::GreaterGreater => Ok(BinaryType::GreaterGreater),
+            TokenType::Comma => Ok(BinaryType::Comma),
+            _ => Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("Can't create binary from: {}", value),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&TokenType> for LogicalType {
+    type Error = Error;
+
+    fn try_from(value: &TokenType) -> Result<Self, Self::Error> {
+        match value {
+            TokenType::And => Ok(LogicalType::And),
+            TokenType::Or => Ok(LogicalType::Or),
             _ => Err(Error::new(
                 ErrorKind::Unsupported,
-                format!("Unexpected token for BinaryType"),
+                format!("Can't create logical from: {}", value),
             )),
         }
     }
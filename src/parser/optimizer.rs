@@ -0,0 +1,248 @@
+use std::{
+    io::{Error, ErrorKind},
+    str::FromStr,
+};
+
+use super::{
+    ast::{Ast, ExprId, StmtId},
+    expression::{
+        binary::{Binary, BinaryType},
+        unary::{Unary, UnaryType},
+        Expression,
+    },
+    statement::{
+        control_flow::{For, If, While},
+        Statement,
+    },
+    visitor::{self, fold_ast, Fold},
+};
+use crate::interpreter::value::{bitwise, bitwise_not, modulo};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    /// Leave the program as parsed.
+    #[default]
+    None,
+    /// Fold constant arithmetic, comparisons and string concatenation.
+    Fold,
+    /// Also simplify conditions and drop branches that can never run.
+    Full,
+}
+
+impl FromStr for OptLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptLevel::None),
+            "1" => Ok(OptLevel::Fold),
+            "2" => Ok(OptLevel::Full),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown optimization level: {s}. Expected one of 0, 1, 2"),
+            )),
+        }
+    }
+}
+
+/// AST optimization pass, run before resolution. Only operations that cannot
+/// fail are folded, so `1 + "a"` is left for the runtime to report on its line.
+pub struct Optimizer {
+    level: OptLevel,
+}
+
+impl Optimizer {
+    pub fn optimize(ast: &mut Ast, level: OptLevel) {
+        if level > OptLevel::None {
+            fold_ast(&mut Optimizer { level }, ast);
+        }
+    }
+
+    /// `!!x` has the truthiness of `x`, which is all a condition looks at.
+    fn simplify_condition(&self, ast: &Ast, mut condition: ExprId) -> ExprId {
+        while let Expression::Unary(outer) = &ast[condition] {
+            let Expression::Unary(inner) = &ast[outer.expression()] else {
+                break;
+            };
+            if *outer.get_type() != UnaryType::Bang || *inner.get_type() != UnaryType::Bang {
+                break;
+            }
+
+            condition = inner.expression();
+        }
+
+        condition
+    }
+}
+
+impl Fold for Optimizer {
+    fn fold_statement(&mut self, ast: &mut Ast, id: StmtId) -> StmtId {
+        let id = visitor::fold_statement(self, ast, id);
+        if self.level < OptLevel::Full {
+            return id;
+        }
+
+        let span = ast.statement_span(id);
+        let statement = match ast[id].clone() {
+            Statement::If(if_statement) => {
+                let condition = self.simplify_condition(ast, if_statement.condition());
+
+                match truthiness(&ast[condition]) {
+                    Some(true) => return if_statement.then_branch(),
+                    Some(false) => {
+                        return if_statement.else_branch().unwrap_or_else(|| {
+                            ast.push_statement(Statement::Block(Vec::new()), span)
+                        })
+                    }
+                    None => Statement::If(If::new(
+                        condition,
+                        if_statement.then_branch(),
+                        if_statement.else_branch(),
+                    )),
+                }
+            }
+            Statement::While(while_statement) => Statement::While(While::new(
+                self.simplify_condition(ast, while_statement.condition()),
+                while_statement.body(),
+            )),
+            Statement::For(for_statement) => Statement::For(For::new(
+                for_statement.initializer(),
+                for_statement
+                    .condition()
+                    .map(|condition| self.simplify_condition(ast, condition)),
+                for_statement.increment(),
+                for_statement.body(),
+            )),
+            _ => return id,
+        };
+
+        match ast[id] == statement {
+            true => id,
+            false => ast.push_statement(statement, span),
+        }
+    }
+
+    fn fold_grouping(&mut self, ast: &mut Ast, id: ExprId, inner: ExprId) -> ExprId {
+        let folded = self.fold_expression(ast, inner);
+
+        match ast[folded].is_literal() {
+            true => folded,
+            false if folded == inner => id,
+            false => ast.push(Expression::Grouping(folded), ast.span(id)),
+        }
+    }
+
+    fn fold_binary(&mut self, ast: &mut Ast, id: ExprId, binary: Binary) -> ExprId {
+        let id = visitor::fold_binary(self, ast, id, binary);
+        let Expression::Binary(binary) = &ast[id] else {
+            return id;
+        };
+
+        match fold_binary(binary.get_type(), &ast[binary.left()], &ast[binary.right()]) {
+            Some(folded) => ast.push(folded, ast.span(id)),
+            None => id,
+        }
+    }
+
+    fn fold_unary(&mut self, ast: &mut Ast, id: ExprId, unary: Unary) -> ExprId {
+        let id = visitor::fold_unary(self, ast, id, unary);
+        let Expression::Unary(unary) = &ast[id] else {
+            return id;
+        };
+
+        let folded = match (unary.get_type(), &ast[unary.expression()]) {
+            (UnaryType::Minus, Expression::Number(value)) => number(-parse(value)),
+            (UnaryType::Tilde, Expression::Number(value)) => {
+                bitwise_not(parse(value)).ok().and_then(number)
+            }
+            (UnaryType::Bang, operand) => truthiness(operand).map(|truthy| boolean(!truthy)),
+            _ => None,
+        };
+
+        match folded {
+            Some(folded) => ast.push(folded, ast.span(id)),
+            None => id,
+        }
+    }
+}
+
+fn fold_binary(
+    binary_type: &BinaryType,
+    left: &Expression,
+    right: &Expression,
+) -> Option<Expression> {
+    if !left.is_literal() || !right.is_literal() {
+        return None;
+    }
+
+    match (binary_type, left, right) {
+        (BinaryType::Comma, _, right) => Some(right.clone()),
+        (BinaryType::EqualEqual, left, right) => Some(boolean(equal(left, right)?)),
+        (BinaryType::BangEqual, left, right) => Some(boolean(!equal(left, right)?)),
+        (BinaryType::Plus, Expression::String(left), Expression::String(right)) => {
+            Some(Expression::String(format!("{left}{right}")))
+        }
+        (binary_type, Expression::Number(left), Expression::Number(right)) => {
+            let (left, right) = (parse(left), parse(right));
+
+            match binary_type {
+                BinaryType::Plus => number(left + right),
+                BinaryType::Minus => number(left - right),
+                BinaryType::Star => number(left * right),
+                BinaryType::Slash => number(left / right),
+                BinaryType::Greater => Some(boolean(left > right)),
+                BinaryType::GreaterEqual => Some(boolean(left >= right)),
+                BinaryType::Less => Some(boolean(left < right)),
+                BinaryType::LessEqual => Some(boolean(left <= right)),
+                BinaryType::Percent => number(modulo(left, right)),
+                BinaryType::StarStar => number(left.powf(right)),
+                BinaryType::Ampersand
+                | BinaryType::Pipe
+                | BinaryType::Caret
+                | BinaryType::LessLess
+                | BinaryType::GreaterGreater => number(bitwise(binary_type, left, right).ok()?),
+                BinaryType::EqualEqual | BinaryType::BangEqual | BinaryType::Comma => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn equal(left: &Expression, right: &Expression) -> Option<bool> {
+    match (left, right) {
+        (Expression::Number(left), Expression::Number(right)) => Some(parse(left) == parse(right)),
+        (left, right) if left.is_literal() && right.is_literal() => Some(left == right),
+        _ => None,
+    }
+}
+
+/// Truthiness of a literal, or `None` if it is only known at runtime.
+fn truthiness(expression: &Expression) -> Option<bool> {
+    match expression {
+        Expression::Nil | Expression::False => Some(false),
+        expression if expression.is_literal() => Some(true),
+        _ => None,
+    }
+}
+
+fn parse(literal: &str) -> f64 {
+    literal.parse().unwrap_or_default()
+}
+
+/// Only finite results are folded; there is no literal for `inf` or `NaN`.
+/// Whole numbers keep the `.0` the tokenizer gives number literals.
+fn number(value: f64) -> Option<Expression> {
+    let literal = match value.fract() == 0.0 {
+        true => format!("{value}.0"),
+        false => value.to_string(),
+    };
+
+    value.is_finite().then_some(Expression::Number(literal))
+}
+
+fn boolean(value: bool) -> Expression {
+    match value {
+        true => Expression::True,
+        false => Expression::False,
+    }
+}
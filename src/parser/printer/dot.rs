@@ -0,0 +1,63 @@
+use super::{labeled_children, node_label};
+use crate::parser::{
+    ast::{Ast, ExprId},
+    visitor::Visitor,
+};
+
+/// Emits the AST as a Graphviz digraph. Nodes are named after their `ExprId`.
+#[derive(Default)]
+pub struct DotPrinter {
+    nodes: Vec<String>,
+    edges: Vec<String>,
+}
+
+impl DotPrinter {
+    pub fn print(ast: &Ast, id: ExprId) -> String {
+        let mut printer = Self::default();
+        printer.visit_expression(ast, id);
+
+        let mut output = String::from("digraph ast {\n    node [shape=box];\n");
+        for line in printer.nodes.iter().chain(printer.edges.iter()) {
+            output.push_str("    ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        output.push('}');
+
+        output
+    }
+
+    fn node(&mut self, ast: &Ast, id: ExprId) {
+        let label = node_label(ast, id)
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+
+        self.nodes.push(format!(
+            "n{} [label=\"{label}\\n{}\"];",
+            id.index(),
+            ast.span(id)
+        ));
+    }
+
+    fn edge(&mut self, from: ExprId, to: ExprId, label: &str) {
+        self.edges.push(format!(
+            "n{} -> n{} [label=\"{label}\"];",
+            from.index(),
+            to.index()
+        ));
+    }
+}
+
+impl<'ast> Visitor<'ast> for DotPrinter {
+    fn visit_expression(&mut self, ast: &'ast Ast, id: ExprId) {
+        let children = labeled_children(ast, id);
+
+        self.node(ast, id);
+        for (label, child) in &children {
+            self.edge(id, *child, label);
+        }
+        for (_, child) in children {
+            self.visit_expression(ast, child);
+        }
+    }
+}
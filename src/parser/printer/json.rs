@@ -0,0 +1,426 @@
+use crate::{
+    parser::{
+        ast::{Ast, ExprId, Identifier, StmtId},
+        expression::{
+            assign::Assign,
+            binary::Binary,
+            call::Call,
+            conditional::Conditional,
+            index::{Index, SetIndex},
+            logical::Logical,
+            property::{Get, Set},
+            unary::Unary,
+            update::{Update, UpdateKind},
+            Expression,
+        },
+        statement::Statement,
+        visitor::Visitor,
+    },
+    tokenizer::Span,
+};
+
+/// Minimal JSON document model, enough to serialize the AST with a stable key order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    pub fn pretty(&self) -> String {
+        let mut output = String::new();
+        self.write(&mut output, 0);
+        output
+    }
+
+    fn write(&self, output: &mut String, indent: usize) {
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => output.push_str(value),
+            Json::String(value) => Json::write_string(output, value),
+            Json::Array(items) if items.is_empty() => output.push_str("[]"),
+            Json::Array(items) => {
+                output.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    output.push_str(&"  ".repeat(indent + 1));
+                    item.write(output, indent + 1);
+                    if index + 1 < items.len() {
+                        output.push(',');
+                    }
+                    output.push('\n');
+                }
+                output.push_str(&"  ".repeat(indent));
+                output.push(']');
+            }
+            Json::Object(fields) if fields.is_empty() => output.push_str("{}"),
+            Json::Object(fields) => {
+                output.push_str("{\n");
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    output.push_str(&"  ".repeat(indent + 1));
+                    Json::write_string(output, key);
+                    output.push_str(": ");
+                    value.write(output, indent + 1);
+                    if index + 1 < fields.len() {
+                        output.push(',');
+                    }
+                    output.push('\n');
+                }
+                output.push_str(&"  ".repeat(indent));
+                output.push('}');
+            }
+        }
+    }
+
+    fn write_string(output: &mut String, value: &str) {
+        output.push('"');
+        for ch in value.chars() {
+            match ch {
+                '"' => output.push_str("\\\""),
+                '\\' => output.push_str("\\\\"),
+                '\n' => output.push_str("\\n"),
+                '\r' => output.push_str("\\r"),
+                '\t' => output.push_str("\\t"),
+                ch if (ch as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", ch as u32)),
+                ch => output.push(ch),
+            }
+        }
+        output.push('"');
+    }
+}
+
+impl From<Span> for Json {
+    fn from(span: Span) -> Self {
+        Json::Object(vec![
+            ("line", Json::Number(span.line.to_string())),
+            ("column", Json::Number(span.column.to_string())),
+            ("end_line", Json::Number(span.end_line.to_string())),
+            ("end_column", Json::Number(span.end_column.to_string())),
+        ])
+    }
+}
+
+/// Serializes the AST as JSON. Every node is an object carrying its `id`, `kind`
+/// and `span` followed by kind-specific fields.
+#[derive(Default)]
+pub struct JsonPrinter {
+    stack: Vec<Json>,
+}
+
+impl JsonPrinter {
+    pub fn print(ast: &Ast, id: ExprId) -> String {
+        JsonPrinter::to_json(ast, id).pretty()
+    }
+
+    pub fn to_json(ast: &Ast, id: ExprId) -> Json {
+        let mut printer = Self::default();
+        printer.visit_expression(ast, id);
+        printer.stack.pop().unwrap_or(Json::Null)
+    }
+
+    fn pop(&mut self) -> Json {
+        self.stack.pop().unwrap_or(Json::Null)
+    }
+
+    fn child(&mut self, ast: &Ast, id: ExprId) -> Json {
+        self.visit_expression(ast, id);
+        self.pop()
+    }
+
+    fn name(name: &Identifier) -> Json {
+        Json::String(name.get_name().to_string())
+    }
+
+    fn node(ast: &Ast, id: ExprId, kind: &str, mut fields: Vec<(&'static str, Json)>) -> Json {
+        let mut node = vec![
+            ("id", Json::Number(id.index().to_string())),
+            ("kind", Json::String(kind.to_string())),
+            ("span", Json::from(ast.span(id))),
+        ];
+        node.append(&mut fields);
+
+        Json::Object(node)
+    }
+}
+
+impl<'ast> Visitor<'ast> for JsonPrinter {
+    fn visit_grouping(&mut self, ast: &'ast Ast, id: ExprId, inner: ExprId) {
+        self.visit_expression(ast, inner);
+        let expression = self.stack.pop().unwrap_or(Json::Null);
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Grouping",
+            vec![("expression", expression)],
+        ));
+    }
+
+    fn visit_binary(&mut self, ast: &'ast Ast, id: ExprId, binary: &'ast Binary) {
+        self.visit_expression(ast, binary.left());
+        self.visit_expression(ast, binary.right());
+        let right = self.stack.pop().unwrap_or(Json::Null);
+        let left = self.stack.pop().unwrap_or(Json::Null);
+        let operator = binary.get_type().get_lexeme().to_string();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Binary",
+            vec![
+                ("operator", Json::String(operator)),
+                ("left", left),
+                ("right", right),
+            ],
+        ));
+    }
+
+    fn visit_unary(&mut self, ast: &'ast Ast, id: ExprId, unary: &'ast Unary) {
+        self.visit_expression(ast, unary.expression());
+        let operand = self.stack.pop().unwrap_or(Json::Null);
+        let operator = unary.get_type().get_lexeme().to_string();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Unary",
+            vec![("operator", Json::String(operator)), ("operand", operand)],
+        ));
+    }
+
+    fn visit_logical(&mut self, ast: &'ast Ast, id: ExprId, logical: &'ast Logical) {
+        let left = self.child(ast, logical.left());
+        let right = self.child(ast, logical.right());
+        let operator = logical.get_type().get_lexeme().to_string();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Logical",
+            vec![
+                ("operator", Json::String(operator)),
+                ("left", left),
+                ("right", right),
+            ],
+        ));
+    }
+
+    fn visit_conditional(&mut self, ast: &'ast Ast, id: ExprId, conditional: &'ast Conditional) {
+        let condition = self.child(ast, conditional.condition());
+        let then_branch = self.child(ast, conditional.then_branch());
+        let else_branch = self.child(ast, conditional.else_branch());
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Conditional",
+            vec![
+                ("condition", condition),
+                ("then", then_branch),
+                ("else", else_branch),
+            ],
+        ));
+    }
+
+    /// Statements are not serialized, so only the parameters of the body are.
+    fn visit_lambda(&mut self, ast: &'ast Ast, id: ExprId, function: StmtId) {
+        let params = match &ast[function] {
+            Statement::Function(function) => function.params().iter().map(Self::name).collect(),
+            _ => Vec::new(),
+        };
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Lambda",
+            vec![("params", Json::Array(params))],
+        ));
+    }
+
+    fn visit_variable(&mut self, ast: &'ast Ast, id: ExprId, name: &'ast Identifier) {
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Variable",
+            vec![("name", Self::name(name))],
+        ));
+    }
+
+    fn visit_assign(&mut self, ast: &'ast Ast, id: ExprId, assign: &'ast Assign) {
+        let value = self.child(ast, assign.value());
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Assign",
+            vec![("name", Self::name(assign.get_name())), ("value", value)],
+        ));
+    }
+
+    fn visit_call(&mut self, ast: &'ast Ast, id: ExprId, call: &'ast Call) {
+        let callee = self.child(ast, call.callee());
+        let arguments = call
+            .arguments()
+            .iter()
+            .map(|argument| self.child(ast, *argument))
+            .collect();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Call",
+            vec![("callee", callee), ("arguments", Json::Array(arguments))],
+        ));
+    }
+
+    fn visit_get(&mut self, ast: &'ast Ast, id: ExprId, get: &'ast Get) {
+        let object = self.child(ast, get.object());
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Get",
+            vec![("object", object), ("name", Self::name(get.get_name()))],
+        ));
+    }
+
+    fn visit_set(&mut self, ast: &'ast Ast, id: ExprId, set: &'ast Set) {
+        let object = self.child(ast, set.object());
+        let value = self.child(ast, set.value());
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Set",
+            vec![
+                ("object", object),
+                ("name", Self::name(set.get_name())),
+                ("value", value),
+            ],
+        ));
+    }
+
+    fn visit_list(&mut self, ast: &'ast Ast, id: ExprId, elements: &'ast [ExprId]) {
+        let elements = elements
+            .iter()
+            .map(|element| self.child(ast, *element))
+            .collect();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "List",
+            vec![("elements", Json::Array(elements))],
+        ));
+    }
+
+    fn visit_interpolation(&mut self, ast: &'ast Ast, id: ExprId, parts: &'ast [ExprId]) {
+        let parts = parts.iter().map(|part| self.child(ast, *part)).collect();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Interpolation",
+            vec![("parts", Json::Array(parts))],
+        ));
+    }
+
+    fn visit_map(&mut self, ast: &'ast Ast, id: ExprId, entries: &'ast [(ExprId, ExprId)]) {
+        let entries = entries
+            .iter()
+            .map(|(key, value)| {
+                let key = self.child(ast, *key);
+                let value = self.child(ast, *value);
+
+                Json::Object(vec![("key", key), ("value", value)])
+            })
+            .collect();
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Map",
+            vec![("entries", Json::Array(entries))],
+        ));
+    }
+
+    fn visit_index(&mut self, ast: &'ast Ast, id: ExprId, index: &'ast Index) {
+        let object = self.child(ast, index.object());
+        let key = self.child(ast, index.index());
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Index",
+            vec![("object", object), ("index", key)],
+        ));
+    }
+
+    fn visit_set_index(&mut self, ast: &'ast Ast, id: ExprId, set_index: &'ast SetIndex) {
+        let object = self.child(ast, set_index.object());
+        let key = self.child(ast, set_index.index());
+        let value = self.child(ast, set_index.value());
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "SetIndex",
+            vec![("object", object), ("index", key), ("value", value)],
+        ));
+    }
+
+    fn visit_update(&mut self, ast: &'ast Ast, id: ExprId, update: &'ast Update) {
+        let target = self.child(ast, update.target());
+        let kind = match update.kind() {
+            UpdateKind::Compound(value) => self.child(ast, value),
+            UpdateKind::Prefix => Json::String("prefix".to_string()),
+            UpdateKind::Postfix => Json::String("postfix".to_string()),
+        };
+        let operator = update.get_lexeme().to_string();
+        let kind_field = match update.kind() {
+            UpdateKind::Compound(_) => "value",
+            UpdateKind::Prefix | UpdateKind::Postfix => "fixity",
+        };
+
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Update",
+            vec![
+                ("operator", Json::String(operator)),
+                ("target", target),
+                (kind_field, kind),
+            ],
+        ));
+    }
+
+    fn visit_this(&mut self, ast: &'ast Ast, id: ExprId) {
+        self.stack.push(Self::node(ast, id, "This", Vec::new()));
+    }
+
+    fn visit_super(&mut self, ast: &'ast Ast, id: ExprId, method: &'ast Identifier) {
+        self.stack.push(Self::node(
+            ast,
+            id,
+            "Super",
+            vec![("method", Self::name(method))],
+        ));
+    }
+
+    fn visit_literal(&mut self, ast: &'ast Ast, id: ExprId, literal: &'ast Expression) {
+        let value = match literal {
+            Expression::Number(value) => Json::Number(value.to_string()),
+            Expression::String(value) => Json::String(value.to_string()),
+            Expression::True => Json::Bool(true),
+            Expression::False => Json::Bool(false),
+            _ => Json::Null,
+        };
+
+        self.stack
+            .push(Self::node(ast, id, "Literal", vec![("value", value)]));
+    }
+}
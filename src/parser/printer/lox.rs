@@ -0,0 +1,630 @@
+use crate::{
+    parser::{
+        ast::{Ast, ExprId, StmtId},
+        expression::{
+            binary::BinaryType, logical::LogicalType, unary::UnaryType, update::UpdateKind,
+            Expression,
+        },
+        statement::{declaration::Function, Statement},
+        visitor::Visitor,
+    },
+    tokenizer::{Span, Token, TokenType},
+};
+
+const INDENT: &str = "    ";
+
+const COMMA: u8 = 1;
+const ASSIGNMENT: u8 = 2;
+const CONDITIONAL: u8 = 3;
+const OR: u8 = 4;
+const AND: u8 = 5;
+const EQUALITY: u8 = 6;
+const COMPARISON: u8 = 7;
+const BITWISE_OR: u8 = 8;
+const BITWISE_XOR: u8 = 9;
+const BITWISE_AND: u8 = 10;
+const SHIFT: u8 = 11;
+const TERM: u8 = 12;
+const FACTOR: u8 = 13;
+const UNARY: u8 = 14;
+const EXPONENT: u8 = 15;
+const POSTFIX: u8 = 16;
+const CALL: u8 = 17;
+const PRIMARY: u8 = 18;
+
+/// Prints a parsed program back as canonical Lox source.
+///
+/// Groupings are dropped and parentheses are re-inserted only where precedence
+/// requires them. Comments are taken from the token stream and re-attached by
+/// position: a comment right after the end of a statement or after a block's
+/// `{` trails it, every other comment is printed on its own line before the
+/// next statement or `}`. An expression with a comment inside is printed as
+/// it was written.
+pub struct LoxPrinter<'a> {
+    ast: &'a Ast,
+    lines: Vec<&'a str>,
+    comments: Vec<Comment<'a>>,
+    next_comment: usize,
+    output: String,
+    indent: usize,
+    last_line: usize,
+    block_start: bool,
+}
+
+impl<'a> LoxPrinter<'a> {
+    pub fn print(source: &'a str, tokens: &'a [Token], ast: &'a Ast) -> String {
+        let mut printer = LoxPrinter {
+            ast,
+            lines: source.lines().collect(),
+            comments: Comment::collect(tokens),
+            next_comment: 0,
+            output: String::new(),
+            indent: 0,
+            last_line: 0,
+            block_start: true,
+        };
+
+        printer.statements(ast.program(), false);
+        printer.comments_before(usize::MAX, usize::MAX);
+
+        printer.output
+    }
+
+    fn statements(&mut self, statements: &[StmtId], methods: bool) {
+        for &id in statements {
+            let span = self.ast.statement_span(id);
+
+            self.comments_before(span.line, span.column);
+            self.separate(span.line);
+            self.write_indent();
+
+            match (&self.ast[id], methods) {
+                (Statement::Function(_), true) => self.function(id, false),
+                _ => self.statement(id),
+            }
+
+            self.output.push('\n');
+            self.last_line = span.end_line;
+            self.trailing_comment((span.end_line, span.end_column));
+        }
+    }
+
+    fn statement(&mut self, id: StmtId) {
+        let ast = self.ast;
+
+        match &ast[id] {
+            Statement::Expression(expression) => {
+                let expression = self.expression(*expression, COMMA);
+                self.output.push_str(&expression);
+                self.output.push(';');
+            }
+            Statement::Print(expression) => {
+                let expression = self.expression(*expression, COMMA);
+                self.output.push_str(&format!("print {expression};"));
+            }
+            Statement::Var(var) => {
+                self.output.push_str("var ");
+                self.output.push_str(var.get_name().get_name());
+                if let Some(initializer) = var.initializer() {
+                    let initializer = self.expression(initializer, COMMA);
+                    self.output.push_str(&format!(" = {initializer}"));
+                }
+                self.output.push(';');
+            }
+            Statement::Block(statements) => self.block(statements, ast.statement_span(id), false),
+            Statement::If(if_statement) => {
+                let condition = self.expression(if_statement.condition(), COMMA);
+                self.output.push_str(&format!("if ({condition}) "));
+                self.statement(if_statement.then_branch());
+
+                if let Some(else_branch) = if_statement.else_branch() {
+                    self.output.push_str(" else ");
+                    self.statement(else_branch);
+                }
+            }
+            Statement::While(while_statement) => {
+                let condition = self.expression(while_statement.condition(), COMMA);
+                self.output.push_str(&format!("while ({condition}) "));
+                self.statement(while_statement.body());
+            }
+            Statement::For(for_statement) => {
+                self.output.push_str("for (");
+                match for_statement.initializer() {
+                    Some(initializer) => self.statement(initializer),
+                    None => self.output.push(';'),
+                }
+                if let Some(condition) = for_statement.condition() {
+                    let condition = self.expression(condition, COMMA);
+                    self.output.push(' ');
+                    self.output.push_str(&condition);
+                }
+                self.output.push(';');
+                if let Some(increment) = for_statement.increment() {
+                    let increment = self.expression(increment, COMMA);
+                    self.output.push(' ');
+                    self.output.push_str(&increment);
+                }
+                self.output.push_str(") ");
+                self.statement(for_statement.body());
+            }
+            Statement::ForIn(for_in) => {
+                let iterable = self.expression(for_in.iterable(), COMMA);
+                self.output.push_str(&format!(
+                    "for (var {} in {iterable}) ",
+                    for_in.get_name().get_name()
+                ));
+                self.statement(for_in.body());
+            }
+            Statement::Function(_) => self.function(id, true),
+            Statement::Return(return_statement) => match return_statement.value() {
+                Some(value) => {
+                    let value = self.expression(value, COMMA);
+                    self.output.push_str(&format!("return {value};"));
+                }
+                None => self.output.push_str("return;"),
+            },
+            Statement::Break(_) => self.output.push_str("break;"),
+            Statement::Continue(_) => self.output.push_str("continue;"),
+            Statement::Throw(value) => {
+                let value = self.expression(*value, COMMA);
+                self.output.push_str(&format!("throw {value};"));
+            }
+            Statement::Try(try_statement) => {
+                self.output.push_str("try ");
+                self.statement(try_statement.body());
+                if let Some(catch) = try_statement.catch() {
+                    self.output
+                        .push_str(&format!(" catch ({}) ", catch.get_name().get_name()));
+                    self.statement(catch.body());
+                }
+                if let Some(finally) = try_statement.finally() {
+                    self.output.push_str(" finally ");
+                    self.statement(finally);
+                }
+            }
+            Statement::Import(import) => {
+                self.output
+                    .push_str(&format!("import \"{}\"", import.path()));
+                if let Some(alias) = import.alias() {
+                    self.output.push_str(" as ");
+                    self.output.push_str(alias.get_name());
+                }
+                self.output.push(';');
+            }
+            Statement::Class(class) => {
+                self.output.push_str("class ");
+                self.output.push_str(class.get_name().get_name());
+                if let Some(superclass) = class.superclass() {
+                    let superclass = self.expression(superclass, PRIMARY);
+                    self.output.push_str(&format!(" < {superclass}"));
+                }
+                self.output.push(' ');
+                self.block(class.methods(), ast.statement_span(id), true);
+            }
+        }
+    }
+
+    fn function(&mut self, id: StmtId, keyword: bool) {
+        let ast = self.ast;
+        let Statement::Function(function) = &ast[id] else {
+            return self.statement(id);
+        };
+
+        if keyword {
+            self.output.push_str("fun ");
+        }
+        self.output.push_str(function.get_name().get_name());
+        self.parameters_and_body(id, function);
+    }
+
+    fn parameters_and_body(&mut self, id: StmtId, function: &Function) {
+        let params: Vec<&str> = function
+            .params()
+            .iter()
+            .map(|param| param.get_name())
+            .collect();
+
+        self.output.push_str(&format!("({}) ", params.join(", ")));
+        self.block(function.body(), self.ast.statement_span(id), false);
+    }
+
+    /// Writes `{`, the statements and the closing `}` of a block ending at `span`.
+    fn block(&mut self, statements: &[StmtId], span: Span, methods: bool) {
+        let close = span.end_column.saturating_sub(1);
+        let has_comments = self
+            .comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.position() < (span.end_line, close));
+
+        if statements.is_empty() && !has_comments {
+            self.output.push_str("{}");
+            return;
+        }
+
+        self.output.push_str("{\n");
+        if let Some(comment) = self.comments.get(self.next_comment).filter(|comment| {
+            comment.position() < (span.end_line, close)
+                && comment
+                    .trails()
+                    .is_some_and(|after| after.get_type() == &TokenType::LeftCurly)
+        }) {
+            self.output.pop();
+            self.output.push(' ');
+            self.output.push_str(comment.token.get_lexeme());
+            self.output.push('\n');
+            self.next_comment += 1;
+        }
+        self.indent += 1;
+        self.block_start = true;
+
+        self.statements(statements, methods);
+        self.comments_before(span.end_line, close);
+
+        self.indent -= 1;
+        self.write_indent();
+        self.output.push('}');
+        self.block_start = false;
+    }
+
+    fn expression(&mut self, id: ExprId, min: u8) -> String {
+        let ast = self.ast;
+        let span = ast.span(id);
+        if self.has_comment_inside(id, span) {
+            return self.verbatim(span);
+        }
+
+        let (precedence, output) = match &ast[id] {
+            Expression::Grouping(inner) => return self.expression(*inner, min),
+            Expression::Binary(binary) => {
+                let precedence = match binary.get_type() {
+                    BinaryType::EqualEqual | BinaryType::BangEqual => EQUALITY,
+                    BinaryType::Greater
+                    | BinaryType::GreaterEqual
+                    | BinaryType::Less
+                    | BinaryType::LessEqual => COMPARISON,
+                    BinaryType::Plus | BinaryType::Minus => TERM,
+                    BinaryType::Star | BinaryType::Slash | BinaryType::Percent => FACTOR,
+                    BinaryType::StarStar => EXPONENT,
+                    BinaryType::Pipe => BITWISE_OR,
+                    BinaryType::Caret => BITWISE_XOR,
+                    BinaryType::Ampersand => BITWISE_AND,
+                    BinaryType::LessLess | BinaryType::GreaterGreater => SHIFT,
+                    BinaryType::Comma => COMMA,
+                };
+                // `**` is right-associative and takes a unary operand on its right.
+                let (left, right) = match binary.get_type() {
+                    BinaryType::StarStar => (precedence + 1, UNARY),
+                    _ => (precedence, precedence + 1),
+                };
+                let left = self.expression(binary.left(), left);
+                let right = self.expression(binary.right(), right);
+                let output = match binary.get_type() {
+                    BinaryType::Comma => format!("{left}, {right}"),
+                    binary_type => format!("{left} {} {right}", binary_type.get_lexeme()),
+                };
+
+                (precedence, output)
+            }
+            Expression::Logical(logical) => {
+                let precedence = match logical.get_type() {
+                    LogicalType::Or => OR,
+                    LogicalType::And => AND,
+                };
+                let left = self.expression(logical.left(), precedence);
+                let right = self.expression(logical.right(), precedence + 1);
+
+                (
+                    precedence,
+                    format!("{left} {} {right}", logical.get_type().get_lexeme()),
+                )
+            }
+            Expression::Conditional(conditional) => {
+                let condition = self.expression(conditional.condition(), OR);
+                let then_branch = self.expression(conditional.then_branch(), COMMA);
+                let else_branch = self.expression(conditional.else_branch(), CONDITIONAL);
+
+                (
+                    CONDITIONAL,
+                    format!("{condition} ? {then_branch} : {else_branch}"),
+                )
+            }
+            Expression::Unary(unary) => {
+                let operand = self.expression(unary.expression(), UNARY);
+                let operator = unary.get_type().get_lexeme();
+                let separator = match unary.get_type() {
+                    UnaryType::Minus if operand.starts_with('-') => " ",
+                    _ => "",
+                };
+
+                (UNARY, format!("{operator}{separator}{operand}"))
+            }
+            Expression::Assign(assign) => {
+                let value = self.expression(assign.value(), ASSIGNMENT);
+
+                (
+                    ASSIGNMENT,
+                    format!("{} = {value}", assign.get_name().get_name()),
+                )
+            }
+            Expression::Set(set) => {
+                let object = self.expression(set.object(), CALL);
+                let value = self.expression(set.value(), ASSIGNMENT);
+
+                (
+                    ASSIGNMENT,
+                    format!("{object}.{} = {value}", set.get_name().get_name()),
+                )
+            }
+            Expression::Call(call) => {
+                let callee = self.expression(call.callee(), CALL);
+                let arguments: Vec<String> = call
+                    .arguments()
+                    .iter()
+                    .map(|argument| self.expression(*argument, ASSIGNMENT))
+                    .collect();
+
+                (CALL, format!("{callee}({})", arguments.join(", ")))
+            }
+            Expression::Get(get) => {
+                let object = self.expression(get.object(), CALL);
+
+                (CALL, format!("{object}.{}", get.get_name().get_name()))
+            }
+            Expression::List(elements) => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|element| self.expression(*element, ASSIGNMENT))
+                    .collect();
+
+                (PRIMARY, format!("[{}]", elements.join(", ")))
+            }
+            Expression::Interpolation(parts) => {
+                let mut output = String::from("\"");
+                for part in parts {
+                    match &ast[*part] {
+                        Expression::String(text) => output.push_str(text),
+                        _ => {
+                            let expression = self.expression(*part, COMMA);
+                            output.push_str(&format!("${{{expression}}}"));
+                        }
+                    }
+                }
+                output.push('"');
+
+                (PRIMARY, output)
+            }
+            Expression::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = self.expression(*key, ASSIGNMENT);
+                        let value = self.expression(*value, ASSIGNMENT);
+
+                        format!("{key}: {value}")
+                    })
+                    .collect();
+
+                (PRIMARY, format!("{{{}}}", entries.join(", ")))
+            }
+            Expression::Index(index) => {
+                let object = self.expression(index.object(), CALL);
+                let key = self.expression(index.index(), COMMA);
+
+                (CALL, format!("{object}[{key}]"))
+            }
+            Expression::SetIndex(set_index) => {
+                let object = self.expression(set_index.object(), CALL);
+                let key = self.expression(set_index.index(), COMMA);
+                let value = self.expression(set_index.value(), ASSIGNMENT);
+
+                (ASSIGNMENT, format!("{object}[{key}] = {value}"))
+            }
+            Expression::Update(update) => {
+                let operator = update.get_lexeme();
+
+                match update.kind() {
+                    UpdateKind::Compound(value) => {
+                        let target = self.expression(update.target(), CALL);
+                        let value = self.expression(value, ASSIGNMENT);
+
+                        (ASSIGNMENT, format!("{target} {operator} {value}"))
+                    }
+                    UpdateKind::Prefix => {
+                        let target = self.expression(update.target(), UNARY);
+
+                        (UNARY, format!("{operator}{target}"))
+                    }
+                    UpdateKind::Postfix => {
+                        let target = self.expression(update.target(), CALL);
+
+                        (POSTFIX, format!("{target}{operator}"))
+                    }
+                }
+            }
+            Expression::Lambda(function) => {
+                let Statement::Function(declaration) = &ast[*function] else {
+                    unreachable!("lambdas wrap a function declaration");
+                };
+
+                // The body is written like any block, so it is rendered into
+                // `output` and moved out again.
+                let enclosing = std::mem::replace(&mut self.output, "fun ".to_string());
+                self.parameters_and_body(*function, declaration);
+
+                (PRIMARY, std::mem::replace(&mut self.output, enclosing))
+            }
+            Expression::Super(method) => (PRIMARY, format!("super.{}", method.get_name())),
+            Expression::This => (PRIMARY, "this".to_string()),
+            Expression::Variable(name) => (PRIMARY, name.get_name().to_string()),
+            Expression::Number(literal) => (PRIMARY, self.source(self.ast.span(id), literal)),
+            Expression::String(value) => (PRIMARY, format!("\"{value}\"")),
+            Expression::True => (PRIMARY, "true".to_string()),
+            Expression::False => (PRIMARY, "false".to_string()),
+            Expression::Nil => (PRIMARY, "nil".to_string()),
+        };
+
+        match precedence < min {
+            true => format!("({output})"),
+            false => output,
+        }
+    }
+
+    /// Source text of a single-line span, so numbers keep the spelling they were written with.
+    fn source(&self, span: Span, fallback: &str) -> String {
+        match self.lines.get(span.line.wrapping_sub(1)) {
+            Some(line) if span.line == span.end_line => line
+                .chars()
+                .skip(span.column - 1)
+                .take(span.end_column - span.column)
+                .collect(),
+            _ => fallback.to_string(),
+        }
+    }
+
+    /// Whether a comment falls inside the expression, leaving out the bodies
+    /// of lambdas, whose statements carry their own comments.
+    fn has_comment_inside(&self, id: ExprId, span: Span) -> bool {
+        let inside: Vec<(usize, usize)> = self.comments[self.next_comment..]
+            .iter()
+            .map(Comment::position)
+            .take_while(|&position| position < (span.end_line, span.end_column))
+            .filter(|&position| position >= (span.line, span.column))
+            .collect();
+        if inside.is_empty() {
+            return false;
+        }
+
+        let mut lambdas = LambdaSpans(Vec::new());
+        lambdas.visit_expression(self.ast, id);
+
+        inside.iter().any(|&position| {
+            !lambdas.0.iter().any(|lambda| {
+                (lambda.line, lambda.column) <= position
+                    && position < (lambda.end_line, lambda.end_column)
+            })
+        })
+    }
+
+    /// The source of `span` as written, consuming the comments inside it.
+    fn verbatim(&mut self, span: Span) -> String {
+        let start = (span.line, span.column);
+        let end = (span.end_line, span.end_column);
+        self.comments
+            .retain(|comment| !(start <= comment.position() && comment.position() < end));
+
+        (span.line..=span.end_line)
+            .filter_map(|line| {
+                let text = self.lines.get(line - 1)?;
+                let skip = if line == span.line {
+                    span.column - 1
+                } else {
+                    0
+                };
+                let take = if line == span.end_line {
+                    span.end_column - 1 - skip
+                } else {
+                    usize::MAX
+                };
+
+                Some(text.chars().skip(skip).take(take).collect::<String>())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn comments_before(&mut self, line: usize, column: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.position() >= (line, column) {
+                break;
+            }
+
+            let token = comment.token;
+            let comment_line = token.get_span().line;
+
+            self.separate(comment_line);
+            self.write_indent();
+            self.output.push_str(token.get_lexeme());
+            self.output.push('\n');
+            self.last_line = comment_line;
+            self.next_comment += 1;
+        }
+    }
+
+    /// Only the outermost statement ending where the comment's line has its
+    /// last code takes the comment.
+    fn trailing_comment(&mut self, end: (usize, usize)) {
+        let Some(comment) = self.comments.get(self.next_comment) else {
+            return;
+        };
+
+        let trails = comment
+            .trails()
+            .is_some_and(|after| (after.get_span().end_line, after.get_span().end_column) == end);
+        if trails {
+            let token = comment.token;
+
+            self.output.pop();
+            self.output.push(' ');
+            self.output.push_str(token.get_lexeme());
+            self.output.push('\n');
+            self.next_comment += 1;
+        }
+    }
+
+    /// Keeps at most one blank line where the source had any.
+    fn separate(&mut self, line: usize) {
+        if !self.block_start && line > self.last_line + 1 {
+            self.output.push('\n');
+        }
+        self.block_start = false;
+    }
+
+    fn write_indent(&mut self) {
+        self.output.push_str(&INDENT.repeat(self.indent));
+    }
+}
+
+/// A comment token with the code token before it.
+struct Comment<'a> {
+    token: &'a Token,
+    after: Option<&'a Token>,
+}
+
+impl<'a> Comment<'a> {
+    fn collect(tokens: &'a [Token]) -> Vec<Self> {
+        let mut previous: Option<&Token> = None;
+        let mut comments = Vec::new();
+
+        for token in tokens {
+            match token.get_type() {
+                TokenType::Comment => comments.push(Comment {
+                    token,
+                    after: previous,
+                }),
+                TokenType::Whitespace | TokenType::Tab | TokenType::Unkonwn => {}
+                _ => previous = Some(token),
+            }
+        }
+
+        comments
+    }
+
+    fn position(&self) -> (usize, usize) {
+        (self.token.get_span().line, self.token.get_span().column)
+    }
+
+    /// The code token the comment shares its line with, if it comes last.
+    fn trails(&self) -> Option<&'a Token> {
+        self.after
+            .filter(|after| after.get_span().end_line == self.token.get_span().line)
+    }
+}
+
+/// Spans of the lambdas inside an expression.
+struct LambdaSpans(Vec<Span>);
+
+impl<'ast> Visitor<'ast> for LambdaSpans {
+    fn visit_lambda(&mut self, ast: &'ast Ast, _id: ExprId, function: StmtId) {
+        self.0.push(ast.statement_span(function));
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    io::{Error, ErrorKind},
+    str::FromStr,
+};
+
+pub use dot::DotPrinter;
+pub use json::JsonPrinter;
+pub use lox::LoxPrinter;
+pub use sexpr::AstPrinter;
+pub use tree::TreePrinter;
+
+use super::{
+    ast::{Ast, ExprId},
+    expression::Expression,
+};
+
+pub mod dot;
+pub mod json;
+pub mod lox;
+pub mod sexpr;
+pub mod tree;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrintFormat {
+    #[default]
+    SExpr,
+    Json,
+    Tree,
+    Dot,
+}
+
+impl PrintFormat {
+    pub fn print(&self, ast: &Ast) -> String {
+        let Some(root) = ast.root() else {
+            return String::new();
+        };
+
+        match self {
+            PrintFormat::SExpr => AstPrinter::print(ast, root),
+            PrintFormat::Json => JsonPrinter::print(ast, root),
+            PrintFormat::Tree => TreePrinter::print(ast, root),
+            PrintFormat::Dot => DotPrinter::print(ast, root),
+        }
+    }
+}
+
+impl FromStr for PrintFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sexpr" => Ok(PrintFormat::SExpr),
+            "json" => Ok(PrintFormat::Json),
+            "tree" => Ok(PrintFormat::Tree),
+            "dot" => Ok(PrintFormat::Dot),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown format: {s}. Expected one of sexpr, json, tree, dot"),
+            )),
+        }
+    }
+}
+
+/// Short human-readable description of a node, shared by the tree and dot printers.
+pub(crate) fn node_label(ast: &Ast, id: ExprId) -> String {
+    match &ast[id] {
+        Expression::Grouping(_) => "Grouping".to_string(),
+        Expression::Binary(binary) => format!("Binary {}", binary.get_type().get_lexeme()),
+        Expression::Unary(unary) => format!("Unary {}", unary.get_type().get_lexeme()),
+        Expression::Logical(logical) => format!("Logical {}", logical.get_type().get_lexeme()),
+        Expression::Conditional(_) => "Conditional".to_string(),
+        Expression::Variable(name) => format!("Variable {}", name.get_name()),
+        Expression::Assign(assign) => format!("Assign {}", assign.get_name().get_name()),
+        Expression::Call(_) => "Call".to_string(),
+        Expression::Get(get) => format!("Get {}", get.get_name().get_name()),
+        Expression::Set(set) => format!("Set {}", set.get_name().get_name()),
+        Expression::List(_) => "List".to_string(),
+        Expression::Map(_) => "Map".to_string(),
+        Expression::Index(_) => "Index".to_string(),
+        Expression::SetIndex(_) => "SetIndex".to_string(),
+        Expression::Update(update) => format!("Update {}", update.get_lexeme()),
+        Expression::Interpolation(_) => "Interpolation".to_string(),
+        Expression::Lambda(_) => "Lambda".to_string(),
+        Expression::This => "This".to_string(),
+        Expression::Super(method) => format!("Super {}", method.get_name()),
+        Expression::Number(value) => format!("Number {value}"),
+        Expression::String(value) => format!("String {value:?}"),
+        Expression::True => "Boolean true".to_string(),
+        Expression::False => "Boolean false".to_string(),
+        Expression::Nil => "Nil".to_string(),
+    }
+}
+
+/// Child expressions of a node paired with the role they play in it.
+pub(crate) fn labeled_children(ast: &Ast, id: ExprId) -> Vec<(String, ExprId)> {
+    match &ast[id] {
+        Expression::Grouping(inner) => vec![("expression".to_string(), *inner)],
+        Expression::Binary(binary) => vec![
+            ("left".to_string(), binary.left()),
+            ("right".to_string(), binary.right()),
+        ],
+        Expression::Logical(logical) => vec![
+            ("left".to_string(), logical.left()),
+            ("right".to_string(), logical.right()),
+        ],
+        Expression::Conditional(conditional) => vec![
+            ("condition".to_string(), conditional.condition()),
+            ("then".to_string(), conditional.then_branch()),
+            ("else".to_string(), conditional.else_branch()),
+        ],
+        Expression::Unary(unary) => vec![("operand".to_string(), unary.expression())],
+        Expression::Assign(assign) => vec![("value".to_string(), assign.value())],
+        Expression::Call(call) => std::iter::once(("callee".to_string(), call.callee()))
+            .chain(
+                call.arguments()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, argument)| (format!("argument {index}"), *argument)),
+            )
+            .collect(),
+        Expression::Get(get) => vec![("object".to_string(), get.object())],
+        Expression::Set(set) => vec![
+            ("object".to_string(), set.object()),
+            ("value".to_string(), set.value()),
+        ],
+        Expression::List(elements) => elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (format!("element {index}"), *element))
+            .collect(),
+        Expression::Interpolation(parts) => parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| (format!("part {index}"), *part))
+            .collect(),
+        Expression::Map(entries) => entries
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (key, value))| {
+                [
+                    (format!("key {index}"), *key),
+                    (format!("value {index}"), *value),
+                ]
+            })
+            .collect(),
+        Expression::Index(index) => vec![
+            ("object".to_string(), index.object()),
+            ("index".to_string(), index.index()),
+        ],
+        Expression::SetIndex(set_index) => vec![
+            ("object".to_string(), set_index.object()),
+            ("index".to_string(), set_index.index()),
+            ("value".to_string(), set_index.value()),
+        ],
+        Expression::Update(update) => std::iter::once(("target".to_string(), update.target()))
+            .chain(update.value().map(|value| ("value".to_string(), value)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
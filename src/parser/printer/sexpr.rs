@@ -0,0 +1,402 @@
+use crate::parser::{
+    ast::{Ast, ExprId, Identifier, StmtId},
+    expression::{
+        assign::Assign,
+        binary::Binary,
+        call::Call,
+        conditional::Conditional,
+        index::{Index, SetIndex},
+        logical::Logical,
+        property::{Get, Set},
+        unary::Unary,
+        update::{Update, UpdateKind},
+        Expression,
+    },
+    statement::{
+        control_flow::{For, ForIn, If, Return, Try, While},
+        declaration::{Class, Function, Import, Var},
+        Statement,
+    },
+    visitor::Visitor,
+};
+use crate::tokenizer::Span;
+
+/// Prints expressions in the parenthesized prefix form used by the `parse` command.
+#[derive(Default)]
+pub struct AstPrinter {
+    output: String,
+}
+
+impl AstPrinter {
+    pub fn print(ast: &Ast, id: ExprId) -> String {
+        let mut printer = Self::default();
+        printer.visit_expression(ast, id);
+        printer.finish()
+    }
+
+    /// Prints every top-level statement of a program on its own line.
+    pub fn print_program(ast: &Ast) -> String {
+        let lines: Vec<String> = ast
+            .program()
+            .iter()
+            .map(|statement| {
+                let mut printer = Self::default();
+                printer.visit_statement(ast, *statement);
+                printer.finish()
+            })
+            .collect();
+
+        lines.join("\n")
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+impl AstPrinter {
+    fn optional_expression(&mut self, ast: &Ast, id: Option<ExprId>) {
+        match id {
+            Some(id) => self.visit_expression(ast, id),
+            None => self.output.push_str("nil"),
+        }
+    }
+
+    fn parameters_and_body(&mut self, ast: &Ast, function: &Function) {
+        let params: Vec<&str> = function
+            .params()
+            .iter()
+            .map(|param| param.get_name())
+            .collect();
+
+        self.output.push_str(&format!("({})", params.join(" ")));
+        self.statements(ast, function.body());
+        self.output.push(')');
+    }
+
+    fn statements(&mut self, ast: &Ast, statements: &[StmtId]) {
+        for statement in statements {
+            self.output.push(' ');
+            self.visit_statement(ast, *statement);
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for AstPrinter {
+    fn visit_expression_statement(&mut self, ast: &'ast Ast, _id: StmtId, expression: ExprId) {
+        self.output.push_str("(; ");
+        self.visit_expression(ast, expression);
+        self.output.push(')');
+    }
+
+    fn visit_print(&mut self, ast: &'ast Ast, _id: StmtId, expression: ExprId) {
+        self.output.push_str("(print ");
+        self.visit_expression(ast, expression);
+        self.output.push(')');
+    }
+
+    fn visit_var(&mut self, ast: &'ast Ast, _id: StmtId, var: &'ast Var) {
+        self.output.push_str("(var ");
+        self.output.push_str(var.get_name().get_name());
+        if let Some(initializer) = var.initializer() {
+            self.output.push(' ');
+            self.visit_expression(ast, initializer);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_block(&mut self, ast: &'ast Ast, _id: StmtId, statements: &'ast [StmtId]) {
+        self.output.push_str("(block");
+        self.statements(ast, statements);
+        self.output.push(')');
+    }
+
+    fn visit_if(&mut self, ast: &'ast Ast, _id: StmtId, if_statement: &'ast If) {
+        self.output.push_str("(if ");
+        self.visit_expression(ast, if_statement.condition());
+        self.output.push(' ');
+        self.visit_statement(ast, if_statement.then_branch());
+        if let Some(else_branch) = if_statement.else_branch() {
+            self.output.push(' ');
+            self.visit_statement(ast, else_branch);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_while(&mut self, ast: &'ast Ast, _id: StmtId, while_statement: &'ast While) {
+        self.output.push_str("(while ");
+        self.visit_expression(ast, while_statement.condition());
+        self.output.push(' ');
+        self.visit_statement(ast, while_statement.body());
+        self.output.push(')');
+    }
+
+    fn visit_for(&mut self, ast: &'ast Ast, _id: StmtId, for_statement: &'ast For) {
+        self.output.push_str("(for ");
+        match for_statement.initializer() {
+            Some(initializer) => self.visit_statement(ast, initializer),
+            None => self.output.push_str("nil"),
+        }
+        self.output.push(' ');
+        self.optional_expression(ast, for_statement.condition());
+        self.output.push(' ');
+        self.optional_expression(ast, for_statement.increment());
+        self.output.push(' ');
+        self.visit_statement(ast, for_statement.body());
+        self.output.push(')');
+    }
+
+    fn visit_for_in(&mut self, ast: &'ast Ast, _id: StmtId, for_in: &'ast ForIn) {
+        self.output.push_str("(for-in ");
+        self.output.push_str(for_in.get_name().get_name());
+        self.output.push(' ');
+        self.visit_expression(ast, for_in.iterable());
+        self.output.push(' ');
+        self.visit_statement(ast, for_in.body());
+        self.output.push(')');
+    }
+
+    fn visit_function(&mut self, ast: &'ast Ast, _id: StmtId, function: &'ast Function) {
+        self.output.push_str("(fun ");
+        self.output.push_str(function.get_name().get_name());
+        self.output.push(' ');
+        self.parameters_and_body(ast, function);
+    }
+
+    fn visit_return(&mut self, ast: &'ast Ast, _id: StmtId, return_statement: &'ast Return) {
+        self.output.push_str("(return");
+        if let Some(value) = return_statement.value() {
+            self.output.push(' ');
+            self.visit_expression(ast, value);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_throw(&mut self, ast: &'ast Ast, _id: StmtId, value: ExprId) {
+        self.output.push_str("(throw ");
+        self.visit_expression(ast, value);
+        self.output.push(')');
+    }
+
+    fn visit_try(&mut self, ast: &'ast Ast, _id: StmtId, try_statement: &'ast Try) {
+        self.output.push_str("(try ");
+        self.visit_statement(ast, try_statement.body());
+        if let Some(catch) = try_statement.catch() {
+            self.output.push_str(" (catch ");
+            self.output.push_str(catch.get_name().get_name());
+            self.output.push(' ');
+            self.visit_statement(ast, catch.body());
+            self.output.push(')');
+        }
+        if let Some(finally) = try_statement.finally() {
+            self.output.push_str(" (finally ");
+            self.visit_statement(ast, finally);
+            self.output.push(')');
+        }
+        self.output.push(')');
+    }
+
+    fn visit_break(&mut self, _ast: &'ast Ast, _id: StmtId, _keyword: Span) {
+        self.output.push_str("(break)");
+    }
+
+    fn visit_continue(&mut self, _ast: &'ast Ast, _id: StmtId, _keyword: Span) {
+        self.output.push_str("(continue)");
+    }
+
+    fn visit_import(&mut self, _ast: &'ast Ast, _id: StmtId, import: &'ast Import) {
+        self.output
+            .push_str(&format!("(import \"{}\"", import.path()));
+        if let Some(alias) = import.alias() {
+            self.output.push_str(" as ");
+            self.output.push_str(alias.get_name());
+        }
+        self.output.push(')');
+    }
+
+    fn visit_class(&mut self, ast: &'ast Ast, _id: StmtId, class: &'ast Class) {
+        self.output.push_str("(class ");
+        self.output.push_str(class.get_name().get_name());
+        if let Some(superclass) = class.superclass() {
+            self.output.push_str(" < ");
+            self.visit_expression(ast, superclass);
+        }
+        self.statements(ast, class.methods());
+        self.output.push(')');
+    }
+
+    fn visit_grouping(&mut self, ast: &'ast Ast, _id: ExprId, inner: ExprId) {
+        self.output.push_str("(group ");
+        self.visit_expression(ast, inner);
+        self.output.push(')');
+    }
+
+    fn visit_binary(&mut self, ast: &'ast Ast, _id: ExprId, binary: &'ast Binary) {
+        self.output.push('(');
+        self.output.push_str(binary.get_type().get_lexeme());
+        self.output.push(' ');
+        self.visit_expression(ast, binary.left());
+        self.output.push(' ');
+        self.visit_expression(ast, binary.right());
+        self.output.push(')');
+    }
+
+    fn visit_unary(&mut self, ast: &'ast Ast, _id: ExprId, unary: &'ast Unary) {
+        self.output.push('(');
+        self.output.push_str(unary.get_type().get_lexeme());
+        self.output.push(' ');
+        self.visit_expression(ast, unary.expression());
+        self.output.push(')');
+    }
+
+    fn visit_logical(&mut self, ast: &'ast Ast, _id: ExprId, logical: &'ast Logical) {
+        self.output.push('(');
+        self.output.push_str(logical.get_type().get_lexeme());
+        self.output.push(' ');
+        self.visit_expression(ast, logical.left());
+        self.output.push(' ');
+        self.visit_expression(ast, logical.right());
+        self.output.push(')');
+    }
+
+    fn visit_conditional(&mut self, ast: &'ast Ast, _id: ExprId, conditional: &'ast Conditional) {
+        self.output.push_str("(?: ");
+        self.visit_expression(ast, conditional.condition());
+        self.output.push(' ');
+        self.visit_expression(ast, conditional.then_branch());
+        self.output.push(' ');
+        self.visit_expression(ast, conditional.else_branch());
+        self.output.push(')');
+    }
+
+    fn visit_variable(&mut self, _ast: &'ast Ast, _id: ExprId, name: &'ast Identifier) {
+        self.output.push_str(name.get_name());
+    }
+
+    fn visit_assign(&mut self, ast: &'ast Ast, _id: ExprId, assign: &'ast Assign) {
+        self.output.push_str("(= ");
+        self.output.push_str(assign.get_name().get_name());
+        self.output.push(' ');
+        self.visit_expression(ast, assign.value());
+        self.output.push(')');
+    }
+
+    fn visit_call(&mut self, ast: &'ast Ast, _id: ExprId, call: &'ast Call) {
+        self.output.push_str("(call ");
+        self.visit_expression(ast, call.callee());
+        for argument in call.arguments() {
+            self.output.push(' ');
+            self.visit_expression(ast, *argument);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_get(&mut self, ast: &'ast Ast, _id: ExprId, get: &'ast Get) {
+        self.output.push_str("(. ");
+        self.visit_expression(ast, get.object());
+        self.output.push(' ');
+        self.output.push_str(get.get_name().get_name());
+        self.output.push(')');
+    }
+
+    fn visit_set(&mut self, ast: &'ast Ast, _id: ExprId, set: &'ast Set) {
+        self.output.push_str("(= (. ");
+        self.visit_expression(ast, set.object());
+        self.output.push(' ');
+        self.output.push_str(set.get_name().get_name());
+        self.output.push_str(") ");
+        self.visit_expression(ast, set.value());
+        self.output.push(')');
+    }
+
+    fn visit_list(&mut self, ast: &'ast Ast, _id: ExprId, elements: &'ast [ExprId]) {
+        self.output.push_str("(list");
+        for element in elements {
+            self.output.push(' ');
+            self.visit_expression(ast, *element);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_interpolation(&mut self, ast: &'ast Ast, _id: ExprId, parts: &'ast [ExprId]) {
+        self.output.push_str("(interpolate");
+        for part in parts {
+            self.output.push(' ');
+            self.visit_expression(ast, *part);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_map(&mut self, ast: &'ast Ast, _id: ExprId, entries: &'ast [(ExprId, ExprId)]) {
+        self.output.push_str("(map");
+        for (key, value) in entries {
+            self.output.push(' ');
+            self.visit_expression(ast, *key);
+            self.output.push(' ');
+            self.visit_expression(ast, *value);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_index(&mut self, ast: &'ast Ast, _id: ExprId, index: &'ast Index) {
+        self.output.push_str("([] ");
+        self.visit_expression(ast, index.object());
+        self.output.push(' ');
+        self.visit_expression(ast, index.index());
+        self.output.push(')');
+    }
+
+    fn visit_set_index(&mut self, ast: &'ast Ast, _id: ExprId, set_index: &'ast SetIndex) {
+        self.output.push_str("(= ([] ");
+        self.visit_expression(ast, set_index.object());
+        self.output.push(' ');
+        self.visit_expression(ast, set_index.index());
+        self.output.push_str(") ");
+        self.visit_expression(ast, set_index.value());
+        self.output.push(')');
+    }
+
+    fn visit_update(&mut self, ast: &'ast Ast, _id: ExprId, update: &'ast Update) {
+        self.output.push('(');
+        match update.kind() {
+            UpdateKind::Prefix => self.output.push_str("pre"),
+            UpdateKind::Postfix => self.output.push_str("post"),
+            UpdateKind::Compound(_) => {}
+        }
+        self.output.push_str(update.get_lexeme());
+        self.output.push(' ');
+        self.visit_expression(ast, update.target());
+        if let Some(value) = update.value() {
+            self.output.push(' ');
+            self.visit_expression(ast, value);
+        }
+        self.output.push(')');
+    }
+
+    fn visit_lambda(&mut self, ast: &'ast Ast, _id: ExprId, function: StmtId) {
+        if let Statement::Function(function) = &ast[function] {
+            self.output.push_str("(fun ");
+            self.parameters_and_body(ast, function);
+        }
+    }
+
+    fn visit_this(&mut self, _ast: &'ast Ast, _id: ExprId) {
+        self.output.push_str("this");
+    }
+
+    fn visit_super(&mut self, _ast: &'ast Ast, _id: ExprId, method: &'ast Identifier) {
+        self.output.push_str("(. super ");
+        self.output.push_str(method.get_name());
+        self.output.push(')');
+    }
+
+    fn visit_literal(&mut self, _ast: &'ast Ast, _id: ExprId, literal: &'ast Expression) {
+        match literal {
+            Expression::Number(value) | Expression::String(value) => self.output.push_str(value),
+            Expression::True => self.output.push_str("true"),
+            Expression::False => self.output.push_str("false"),
+            Expression::Nil => self.output.push_str("nil"),
+            _ => {}
+        }
+    }
+}
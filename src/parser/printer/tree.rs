@@ -0,0 +1,68 @@
+use super::{labeled_children, node_label};
+use crate::parser::{
+    ast::{Ast, ExprId},
+    visitor::Visitor,
+};
+
+/// Indented dump of the AST, one node per line with its source span.
+#[derive(Default)]
+pub struct TreePrinter {
+    output: String,
+    prefix: String,
+    last: Vec<bool>,
+}
+
+impl TreePrinter {
+    pub fn print(ast: &Ast, id: ExprId) -> String {
+        let mut printer = Self::default();
+        printer.last.push(true);
+        printer.visit_expression(ast, id);
+        printer.output.pop();
+        printer.output
+    }
+
+    fn line(&mut self, ast: &Ast, id: ExprId) {
+        let is_root = self.last.len() == 1;
+        let connector = match self.last.last() {
+            _ if is_root => "",
+            Some(true) => "└── ",
+            _ => "├── ",
+        };
+
+        self.output.push_str(&self.prefix);
+        self.output.push_str(connector);
+        self.output.push_str(&node_label(ast, id));
+        self.output.push_str(&format!(" @ {}\n", ast.span(id)));
+    }
+
+    fn children(&mut self, ast: &Ast, children: &[ExprId]) {
+        let is_root = self.last.len() == 1;
+        let indent = match self.last.last() {
+            _ if is_root => "",
+            Some(true) => "    ",
+            _ => "│   ",
+        };
+        let prefix_len = self.prefix.len();
+        self.prefix.push_str(indent);
+
+        for (index, child) in children.iter().enumerate() {
+            self.last.push(index + 1 == children.len());
+            self.visit_expression(ast, *child);
+            self.last.pop();
+        }
+
+        self.prefix.truncate(prefix_len);
+    }
+}
+
+impl<'ast> Visitor<'ast> for TreePrinter {
+    fn visit_expression(&mut self, ast: &'ast Ast, id: ExprId) {
+        let children: Vec<ExprId> = labeled_children(ast, id)
+            .into_iter()
+            .map(|(_, child)| child)
+            .collect();
+
+        self.line(ast, id);
+        self.children(ast, &children);
+    }
+}
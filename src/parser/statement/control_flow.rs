@@ -0,0 +1,196 @@
+use crate::{
+    parser::ast::{ExprId, Identifier, StmtId},
+    tokenizer::Span,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct If {
+    condition: ExprId,
+    then_branch: StmtId,
+    else_branch: Option<StmtId>,
+}
+
+impl If {
+    pub fn new(condition: ExprId, then_branch: StmtId, else_branch: Option<StmtId>) -> Self {
+        Self {
+            condition,
+            then_branch,
+            else_branch,
+        }
+    }
+
+    pub fn condition(&self) -> ExprId {
+        self.condition
+    }
+
+    pub fn then_branch(&self) -> StmtId {
+        self.then_branch
+    }
+
+    pub fn else_branch(&self) -> Option<StmtId> {
+        self.else_branch
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct While {
+    condition: ExprId,
+    body: StmtId,
+}
+
+impl While {
+    pub fn new(condition: ExprId, body: StmtId) -> Self {
+        Self { condition, body }
+    }
+
+    pub fn condition(&self) -> ExprId {
+        self.condition
+    }
+
+    pub fn body(&self) -> StmtId {
+        self.body
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct For {
+    initializer: Option<StmtId>,
+    condition: Option<ExprId>,
+    increment: Option<ExprId>,
+    body: StmtId,
+}
+
+impl For {
+    pub fn new(
+        initializer: Option<StmtId>,
+        condition: Option<ExprId>,
+        increment: Option<ExprId>,
+        body: StmtId,
+    ) -> Self {
+        Self {
+            initializer,
+            condition,
+            increment,
+            body,
+        }
+    }
+
+    /// A `Var` or `Expression` statement.
+    pub fn initializer(&self) -> Option<StmtId> {
+        self.initializer
+    }
+
+    pub fn condition(&self) -> Option<ExprId> {
+        self.condition
+    }
+
+    pub fn increment(&self) -> Option<ExprId> {
+        self.increment
+    }
+
+    pub fn body(&self) -> StmtId {
+        self.body
+    }
+}
+
+/// `for (var name in iterable) body`, running `body` once per list element.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForIn {
+    name: Identifier,
+    iterable: ExprId,
+    body: StmtId,
+}
+
+impl ForIn {
+    pub fn new(name: Identifier, iterable: ExprId, body: StmtId) -> Self {
+        Self {
+            name,
+            iterable,
+            body,
+        }
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    pub fn iterable(&self) -> ExprId {
+        self.iterable
+    }
+
+    pub fn body(&self) -> StmtId {
+        self.body
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Return {
+    keyword: Span,
+    value: Option<ExprId>,
+}
+
+impl Return {
+    pub fn new(keyword: Span, value: Option<ExprId>) -> Self {
+        Self { keyword, value }
+    }
+
+    pub fn keyword_span(&self) -> Span {
+        self.keyword
+    }
+
+    pub fn value(&self) -> Option<ExprId> {
+        self.value
+    }
+}
+
+/// `catch (name) body`; the thrown value is bound to `name` in a scope of its own.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Catch {
+    name: Identifier,
+    body: StmtId,
+}
+
+impl Catch {
+    pub fn new(name: Identifier, body: StmtId) -> Self {
+        Self { name, body }
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    pub fn body(&self) -> StmtId {
+        self.body
+    }
+}
+
+/// `try body catch (e) { } finally { }` with at least one of the two clauses.
+/// The bodies are `Block` statements.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Try {
+    body: StmtId,
+    catch: Option<Catch>,
+    finally: Option<StmtId>,
+}
+
+impl Try {
+    pub fn new(body: StmtId, catch: Option<Catch>, finally: Option<StmtId>) -> Self {
+        Self {
+            body,
+            catch,
+            finally,
+        }
+    }
+
+    pub fn body(&self) -> StmtId {
+        self.body
+    }
+
+    pub fn catch(&self) -> Option<&Catch> {
+        self.catch.as_ref()
+    }
+
+    pub fn finally(&self) -> Option<StmtId> {
+        self.finally
+    }
+}
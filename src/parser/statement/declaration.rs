@@ -0,0 +1,112 @@
+use crate::{
+    parser::ast::{ExprId, Identifier, StmtId},
+    tokenizer::Span,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Var {
+    name: Identifier,
+    initializer: Option<ExprId>,
+}
+
+impl Var {
+    pub fn new(name: Identifier, initializer: Option<ExprId>) -> Self {
+        Self { name, initializer }
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    pub fn initializer(&self) -> Option<ExprId> {
+        self.initializer
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Function {
+    name: Identifier,
+    params: Vec<Identifier>,
+    body: Vec<StmtId>,
+}
+
+impl Function {
+    pub fn new(name: Identifier, params: Vec<Identifier>, body: Vec<StmtId>) -> Self {
+        Self { name, params, body }
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    pub fn params(&self) -> &[Identifier] {
+        &self.params
+    }
+
+    pub fn body(&self) -> &[StmtId] {
+        &self.body
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Class {
+    name: Identifier,
+    superclass: Option<ExprId>,
+    methods: Vec<StmtId>,
+}
+
+impl Class {
+    pub fn new(name: Identifier, superclass: Option<ExprId>, methods: Vec<StmtId>) -> Self {
+        Self {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    /// A `Variable` expression naming the superclass.
+    pub fn superclass(&self) -> Option<ExprId> {
+        self.superclass
+    }
+
+    /// `Function` statements, one per method.
+    pub fn methods(&self) -> &[StmtId] {
+        &self.methods
+    }
+}
+
+/// `import "path";` runs the file at `path` once and copies its top-level
+/// names into the importer's globals; `import "path" as name;` binds them to
+/// `name` as a module instead.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Import {
+    path: String,
+    path_span: Span,
+    alias: Option<Identifier>,
+}
+
+impl Import {
+    pub fn new(path: String, path_span: Span, alias: Option<Identifier>) -> Self {
+        Self {
+            path,
+            path_span,
+            alias,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn path_span(&self) -> Span {
+        self.path_span
+    }
+
+    pub fn alias(&self) -> Option<&Identifier> {
+        self.alias.as_ref()
+    }
+}
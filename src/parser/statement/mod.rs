@@ -0,0 +1,30 @@
+use control_flow::{For, ForIn, If, Return, Try, While};
+use declaration::{Class, Function, Import, Var};
+
+use super::ast::{ExprId, StmtId};
+use crate::tokenizer::Span;
+
+pub mod control_flow;
+pub mod declaration;
+mod parse;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Statement {
+    Expression(ExprId),
+    Print(ExprId),
+    Var(Var),
+    Block(Vec<StmtId>),
+    If(If),
+    While(While),
+    For(For),
+    ForIn(ForIn),
+    Function(Function),
+    Return(Return),
+    /// Holds the span of the keyword.
+    Break(Span),
+    Continue(Span),
+    Class(Class),
+    Import(Import),
+    Throw(ExprId),
+    Try(Try),
+}
@@ -0,0 +1,390 @@
+use super::{
+    control_flow::{Catch, For, ForIn, If, Return, Try, While},
+    declaration::{Class, Function, Import, Var},
+    Statement,
+};
+use crate::{
+    parser::{
+        ast::{Identifier, StmtId},
+        error::ParseExprError,
+        expression::Expression,
+        Parser,
+    },
+    tokenizer::{Span, TokenType},
+};
+
+const MAX_PARAMETERS: usize = 255;
+
+impl Parser<'_> {
+    pub(in crate::parser) fn declaration(&mut self) -> Result<StmtId, ParseExprError> {
+        match self.peek().get_type() {
+            TokenType::Class => self.class_declaration(),
+            // `fun (` starts an anonymous function used as an expression statement.
+            TokenType::Fun if self.peek_at(1).get_type() == &TokenType::LeftParenthesis => {
+                self.statement()
+            }
+            TokenType::Fun => {
+                let start = *self.advance().get_span();
+                if !self.check(&TokenType::Identifier) {
+                    return Err(ParseExprError::unexpected(
+                        self.peek(),
+                        "Expect function name or '(' after 'fun'.",
+                    ));
+                }
+                self.function("function", start)
+            }
+            TokenType::Var => self.var_declaration(),
+            TokenType::Import => self.import_declaration(),
+            _ => self.statement(),
+        }
+    }
+
+    fn class_declaration(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        let name = Identifier::from(self.consume(TokenType::Identifier, "Expect class name.")?);
+
+        let superclass = match self.match_any(&[TokenType::Less]) {
+            Some(_) => {
+                let token = self.consume(TokenType::Identifier, "Expect superclass name.")?;
+                let variable = Expression::Variable(Identifier::from(token));
+
+                Some(self.ast.push(variable, *token.get_span()))
+            }
+            None => None,
+        };
+
+        self.consume(TokenType::LeftCurly, "Expect '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(&TokenType::RightCurly) && !self.is_at_end() {
+            let start = *self.peek().get_span();
+            methods.push(self.function("method", start)?);
+        }
+
+        self.consume(TokenType::RightCurly, "Expect '}' after class body.")?;
+
+        let class = Class::new(name, superclass, methods);
+        let span = self.span_from(start);
+
+        Ok(self.ast.push_statement(Statement::Class(class), span))
+    }
+
+    fn function(&mut self, kind: &str, start: Span) -> Result<StmtId, ParseExprError> {
+        let name = self.consume(TokenType::Identifier, &format!("Expect {kind} name."))?;
+        let name = Identifier::from(name);
+
+        self.consume(
+            TokenType::LeftParenthesis,
+            &format!("Expect '(' after {kind} name."),
+        )?;
+
+        self.function_body(name, kind, start)
+    }
+
+    /// Parses the parameters and body of a function whose `(` was just consumed.
+    pub(in crate::parser) fn function_body(
+        &mut self,
+        name: Identifier,
+        kind: &str,
+        start: Span,
+    ) -> Result<StmtId, ParseExprError> {
+        let mut params = Vec::new();
+        if !self.check(&TokenType::RightParenthesis) {
+            loop {
+                if params.len() >= MAX_PARAMETERS {
+                    self.errors.push(ParseExprError::unexpected(
+                        self.peek(),
+                        "Can't have more than 255 parameters.",
+                    ));
+                }
+
+                let param = self.consume(TokenType::Identifier, "Expect parameter name.")?;
+                params.push(Identifier::from(param));
+
+                if self.match_any(&[TokenType::Comma]).is_none() {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightParenthesis, "Expect ')' after parameters.")?;
+        self.consume(
+            TokenType::LeftCurly,
+            &format!("Expect '{{' before {kind} body."),
+        )?;
+
+        let body = self.block()?;
+        let function = Function::new(name, params, body);
+        let span = self.span_from(start);
+
+        Ok(self.ast.push_statement(Statement::Function(function), span))
+    }
+
+    fn var_declaration(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        let name = Identifier::from(self.consume(TokenType::Identifier, "Expect variable name.")?);
+
+        let initializer = match self.match_any(&[TokenType::Equal]) {
+            Some(_) => Some(self.expression()?),
+            None => None,
+        };
+
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+
+        let span = self.span_from(start);
+
+        Ok(self
+            .ast
+            .push_statement(Statement::Var(Var::new(name, initializer)), span))
+    }
+
+    /// `as` is only special after the path, so it stays a valid identifier.
+    fn import_declaration(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        let path = self.consume(TokenType::String, "Expect module path after 'import'.")?;
+
+        let alias = match self.peek() {
+            token if token.get_type() == &TokenType::Identifier && token.get_lexeme() == "as" => {
+                self.advance();
+                let name = self.consume(TokenType::Identifier, "Expect module name after 'as'.")?;
+                Some(Identifier::from(name))
+            }
+            _ => None,
+        };
+
+        self.consume(TokenType::Semicolon, "Expect ';' after import.")?;
+
+        let span = self.span_from(start);
+        let import = Import::new(path.get_literal(), *path.get_span(), alias);
+
+        Ok(self.ast.push_statement(Statement::Import(import), span))
+    }
+
+    fn statement(&mut self) -> Result<StmtId, ParseExprError> {
+        match self.peek().get_type() {
+            TokenType::For => self.for_statement(),
+            TokenType::If => self.if_statement(),
+            TokenType::Print => self.print_statement(),
+            TokenType::Return => self.return_statement(),
+            TokenType::While => self.while_statement(),
+            TokenType::Throw => {
+                let start = *self.advance().get_span();
+                let value = self.expression()?;
+                self.consume(TokenType::Semicolon, "Expect ';' after thrown value.")?;
+                let span = self.span_from(start);
+
+                Ok(self.ast.push_statement(Statement::Throw(value), span))
+            }
+            TokenType::Try => self.try_statement(),
+            TokenType::Break => {
+                let keyword = *self.advance().get_span();
+                self.consume(TokenType::Semicolon, "Expect ';' after 'break'.")?;
+                let span = self.span_from(keyword);
+
+                Ok(self.ast.push_statement(Statement::Break(keyword), span))
+            }
+            TokenType::Continue => {
+                let keyword = *self.advance().get_span();
+                self.consume(TokenType::Semicolon, "Expect ';' after 'continue'.")?;
+                let span = self.span_from(keyword);
+
+                Ok(self.ast.push_statement(Statement::Continue(keyword), span))
+            }
+            TokenType::LeftCurly => {
+                let start = *self.advance().get_span();
+                let statements = self.block()?;
+                let span = self.span_from(start);
+
+                Ok(self.ast.push_statement(Statement::Block(statements), span))
+            }
+            _ => self.expression_statement(),
+        }
+    }
+
+    fn try_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        let body = self.block_statement("Expect '{' after 'try'.")?;
+
+        let catch = match self.match_any(&[TokenType::Catch]) {
+            Some(_) => {
+                self.consume(TokenType::LeftParenthesis, "Expect '(' after 'catch'.")?;
+                let name = self.consume(TokenType::Identifier, "Expect error variable name.")?;
+                self.consume(
+                    TokenType::RightParenthesis,
+                    "Expect ')' after error variable name.",
+                )?;
+                let body = self.block_statement("Expect '{' before catch body.")?;
+
+                Some(Catch::new(Identifier::from(name), body))
+            }
+            None => None,
+        };
+
+        let finally = match self.match_any(&[TokenType::Finally]) {
+            Some(_) => Some(self.block_statement("Expect '{' after 'finally'.")?),
+            None => None,
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParseExprError::unexpected(
+                self.peek(),
+                "Expect 'catch' or 'finally' after try block.",
+            ));
+        }
+
+        let span = self.span_from(start);
+
+        Ok(self
+            .ast
+            .push_statement(Statement::Try(Try::new(body, catch, finally)), span))
+    }
+
+    /// A `{ ... }` block that the grammar requires at this point.
+    fn block_statement(&mut self, msg: &str) -> Result<StmtId, ParseExprError> {
+        let start = *self.consume(TokenType::LeftCurly, msg)?.get_span();
+        let statements = self.block()?;
+        let span = self.span_from(start);
+
+        Ok(self.ast.push_statement(Statement::Block(statements), span))
+    }
+
+    /// Parses the statements of a block whose `{` was already consumed.
+    fn block(&mut self) -> Result<Vec<StmtId>, ParseExprError> {
+        let mut statements = Vec::new();
+
+        while !self.check(&TokenType::RightCurly) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(TokenType::RightCurly, "Expect '}' after block.")?;
+
+        Ok(statements)
+    }
+
+    fn for_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        self.consume(TokenType::LeftParenthesis, "Expect '(' after 'for'.")?;
+
+        if self.check(&TokenType::Var) && self.peek_at(2).get_type() == &TokenType::In {
+            return self.for_in_statement(start);
+        }
+
+        let initializer = match self.peek().get_type() {
+            TokenType::Semicolon => {
+                self.advance();
+                None
+            }
+            TokenType::Var => Some(self.var_declaration()?),
+            _ => Some(self.expression_statement()?),
+        };
+
+        let condition = match self.check(&TokenType::Semicolon) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = match self.check(&TokenType::RightParenthesis) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::RightParenthesis, "Expect ')' after for clauses.")?;
+
+        let body = self.statement()?;
+        let span = self.span_from(start);
+        let for_statement = For::new(initializer, condition, increment, body);
+
+        Ok(self.ast.push_statement(Statement::For(for_statement), span))
+    }
+
+    /// The rest of `for (var name in iterable) body` after the `(`.
+    fn for_in_statement(&mut self, start: Span) -> Result<StmtId, ParseExprError> {
+        self.advance();
+        let name = Identifier::from(self.consume(TokenType::Identifier, "Expect variable name.")?);
+        self.consume(TokenType::In, "Expect 'in' after loop variable.")?;
+
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParenthesis, "Expect ')' after for clauses.")?;
+
+        let body = self.statement()?;
+        let span = self.span_from(start);
+        let for_in = ForIn::new(name, iterable, body);
+
+        Ok(self.ast.push_statement(Statement::ForIn(for_in), span))
+    }
+
+    fn if_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        self.consume(TokenType::LeftParenthesis, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(
+            TokenType::RightParenthesis,
+            "Expect ')' after if condition.",
+        )?;
+
+        let then_branch = self.statement()?;
+        let else_branch = match self.match_any(&[TokenType::Else]) {
+            Some(_) => Some(self.statement()?),
+            None => None,
+        };
+
+        let span = self.span_from(start);
+        let if_statement = If::new(condition, then_branch, else_branch);
+
+        Ok(self.ast.push_statement(Statement::If(if_statement), span))
+    }
+
+    fn print_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+
+        let span = self.span_from(start);
+
+        Ok(self.ast.push_statement(Statement::Print(value), span))
+    }
+
+    fn return_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let keyword = *self.advance().get_span();
+
+        let value = match self.check(&TokenType::Semicolon) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+
+        let span = self.span_from(keyword);
+
+        Ok(self
+            .ast
+            .push_statement(Statement::Return(Return::new(keyword, value)), span))
+    }
+
+    fn while_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let start = *self.advance().get_span();
+        self.consume(TokenType::LeftParenthesis, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParenthesis, "Expect ')' after condition.")?;
+
+        let body = self.statement()?;
+        let span = self.span_from(start);
+
+        Ok(self
+            .ast
+            .push_statement(Statement::While(While::new(condition, body)), span))
+    }
+
+    fn expression_statement(&mut self) -> Result<StmtId, ParseExprError> {
+        let expression = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+
+        let span = self.ast.span(expression).merge(*self.previous().get_span());
+
+        Ok(self
+            .ast
+            .push_statement(Statement::Expression(expression), span))
+    }
+}
@@ -1,18 +1,44 @@
 #[allow(unused_imports)]
 use crate::{
     parser::{
-        expression::{binary::Binary, AddExpr, Expression, IsPartial},
+        ast::{Ast, ExprId},
+        expression::{binary::Binary, Expression},
+        optimizer::{OptLevel, Optimizer},
+        printer::{AstPrinter, LoxPrinter, PrintFormat},
+        visitor::{fold_ast, Fold, Visitor, VisitorMut},
         Parser,
     },
-    tokenizer::Tokenizer,
+    tokenizer::{Span, TokenType, Tokenizer, TokenizerOutput},
 };
 
+/// Tests exercise the whole language; standard Lox is checked separately.
+fn _tokenize(input: &str) -> TokenizerOutput {
+    Tokenizer::tokenize_with(input.to_string(), true).unwrap()
+}
+
 fn _parse(input: &str) -> String {
-    format!(
-        "{}",
-        Parser::parse_tokens(&Tokenizer::tokenize(input.to_string()).unwrap().get_tokens())
-            .unwrap_or(Expression::None)
-    )
+    Parser::parse_tokens_with(_tokenize(input).get_tokens(), true)
+        .map(|ast| ast.to_string())
+        .unwrap_or_default()
+}
+
+fn _parse_ast(input: &str) -> Ast {
+    Parser::parse_tokens_with(_tokenize(input).get_tokens(), true).unwrap()
+}
+
+fn _format(input: &str) -> String {
+    let output = _tokenize(input);
+    let ast = Parser::parse_program_with(output.get_tokens(), true).unwrap();
+
+    LoxPrinter::print(input, output.get_tokens(), &ast)
+}
+
+fn _optimize(input: &str, level: OptLevel) -> String {
+    let output = _tokenize(input);
+    let mut ast = Parser::parse_program_with(output.get_tokens(), true).unwrap();
+    Optimizer::optimize(&mut ast, level);
+
+    AstPrinter::print_program(&ast)
 }
 
 #[test]
@@ -176,3 +202,741 @@ fn complex3() {
 
     assert_eq!(_parse(input), expected.to_string());
 }
+
+#[test]
+fn spans() {
+    let ast = _parse_ast("(1 + 23) * 4");
+    let root = ast.root().unwrap();
+
+    assert_eq!(ast.span(root), Span::new(1, 1, 12));
+
+    let Expression::Binary(binary) = &ast[root] else {
+        panic!("Expected binary expression");
+    };
+    assert_eq!(binary.operator_span(), Span::new(1, 10, 1));
+    assert_eq!(ast.span(binary.left()), Span::new(1, 1, 8));
+
+    let ast = _parse_ast("1 +\n-2");
+    let span = ast.span(ast.root().unwrap());
+
+    assert_eq!(
+        (span.line, span.column, span.end_line, span.end_column),
+        (1, 1, 2, 3)
+    );
+}
+
+#[test]
+fn unclosed_group() {
+    let tokens = Tokenizer::tokenize("(1 + 2".to_string()).unwrap();
+    let err = Parser::parse_tokens(tokens.get_tokens()).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "[line 1] Error at '(': Expect ')' after expression."
+    );
+}
+
+#[test]
+fn missing_operand() {
+    let tokens = Tokenizer::tokenize("(72 +)".to_string()).unwrap();
+    let err = Parser::parse_tokens(tokens.get_tokens()).unwrap_err();
+
+    assert_eq!(err.to_string(), "[line 1] Error at ')': Expect expression.");
+}
+
+#[test]
+fn arena_is_linear() {
+    let terms = 10_000;
+    let input = vec!["1"; terms].join(" + ");
+    let ast = _parse_ast(&input);
+
+    assert_eq!(ast.len(), 2 * terms - 1);
+}
+
+#[test]
+fn visitor_walks_every_literal() {
+    #[derive(Default)]
+    struct LiteralCounter(usize);
+
+    impl Visitor<'_> for LiteralCounter {
+        fn visit_literal(&mut self, _ast: &Ast, _id: ExprId, _literal: &Expression) {
+            self.0 += 1;
+        }
+    }
+
+    let ast = _parse_ast("(1 + -2) * !(3 / 4)");
+    let mut counter = LiteralCounter::default();
+    counter.visit_expression(&ast, ast.root().unwrap());
+
+    assert_eq!(counter.0, 4);
+}
+
+#[test]
+fn visitor_mut_rewrites_in_place() {
+    struct Zero;
+
+    impl VisitorMut for Zero {
+        fn visit_literal_mut(&mut self, ast: &mut Ast, id: ExprId) {
+            if let Expression::Number(value) = &mut ast[id] {
+                *value = "0.0".to_string();
+            }
+        }
+    }
+
+    let mut ast = _parse_ast("1 + (2 * 3)");
+    let root = ast.root().unwrap();
+    Zero.visit_expression_mut(&mut ast, root);
+
+    assert_eq!(ast.to_string(), "(+ 0.0 (group (* 0.0 0.0)))");
+}
+
+#[test]
+fn fold_rebuilds_tree() {
+    struct Ungroup;
+
+    impl Fold for Ungroup {
+        fn fold_grouping(&mut self, ast: &mut Ast, _id: ExprId, inner: ExprId) -> ExprId {
+            self.fold_expression(ast, inner)
+        }
+    }
+
+    let mut ast = _parse_ast("((1 + 3)) * -(2)");
+    let root = ast.root().unwrap();
+    let span = ast.span(root);
+    fold_ast(&mut Ungroup, &mut ast);
+
+    assert_eq!(ast.to_string(), "(* (+ 1.0 3.0) (- 2.0))");
+    assert_ne!(ast.root().unwrap(), root);
+    assert_eq!(ast.span(ast.root().unwrap()), span);
+
+    let mut ast = _parse_ast("1 + 2");
+    let root = ast.root().unwrap();
+    fold_ast(&mut Ungroup, &mut ast);
+
+    assert_eq!(ast.root().unwrap(), root);
+}
+
+#[test]
+fn json_format() {
+    let ast = _parse_ast("-nil");
+    let expected = r#"{
+  "id": 1,
+  "kind": "Unary",
+  "span": {
+    "line": 1,
+    "column": 1,
+    "end_line": 1,
+    "end_column": 5
+  },
+  "operator": "-",
+  "operand": {
+    "id": 0,
+    "kind": "Literal",
+    "span": {
+      "line": 1,
+      "column": 2,
+      "end_line": 1,
+      "end_column": 5
+    },
+    "value": null
+  }
+}"#;
+
+    assert_eq!(PrintFormat::Json.print(&ast), expected);
+}
+
+#[test]
+fn tree_format() {
+    let ast = _parse_ast("(1 + 2) * -3");
+    let expected = "\
+Binary * @ 1:1
+├── Grouping @ 1:1
+│   └── Binary + @ 1:2
+│       ├── Number 1.0 @ 1:2
+│       └── Number 2.0 @ 1:6
+└── Unary - @ 1:11
+    └── Number 3.0 @ 1:12";
+
+    assert_eq!(PrintFormat::Tree.print(&ast), expected);
+}
+
+#[test]
+fn dot_format() {
+    let ast = _parse_ast("!\"a\"");
+    let expected = r#"digraph ast {
+    node [shape=box];
+    n1 [label="Unary !\n1:1"];
+    n0 [label="String \"a\"\n1:2"];
+    n1 -> n0 [label="operand"];
+}"#;
+
+    assert_eq!(PrintFormat::Dot.print(&ast), expected);
+}
+
+#[test]
+fn program_statements() {
+    let input = "var a = 1; { print a; } if (a) print a; else a = 2; while (false) {} fun f(x) { return x; } class A < B { m() {} }";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let ast = Parser::parse_program(output.get_tokens()).unwrap();
+
+    assert_eq!(ast.program().len(), 6);
+}
+
+#[test]
+fn break_and_continue() {
+    let input = "while (true) { continue; break; }";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let ast = Parser::parse_program(output.get_tokens()).unwrap();
+
+    assert_eq!(
+        AstPrinter::print_program(&ast),
+        "(while true (block (continue) (break)))"
+    );
+    assert_eq!(
+        _format(input),
+        "while (true) {\n    continue;\n    break;\n}\n"
+    );
+}
+
+#[test]
+fn conditional_and_comma() {
+    assert_eq!(_parse("a ? b : c ? d : e"), "(?: a b (?: c d e))");
+    assert_eq!(_parse("a or b ? c, d : e"), "(?: (or a b) (, c d) e)");
+    assert_eq!(_parse("a = b ? c : d"), "(= a (?: b c d))");
+    assert_eq!(_parse("a, b = c, d"), "(, (, a (= b c)) d)");
+    assert_eq!(_parse("f((a, b), c)"), "(call f (group (, a b)) c)");
+    assert_eq!(
+        _format("print (a ? b : c) ? (d, e) : (f = g);"),
+        "print (a ? b : c) ? d, e : (f = g);\n"
+    );
+    assert_eq!(_format("print (a, b);"), "print a, b;\n");
+}
+
+#[test]
+fn conditional_errors() {
+    let output = Tokenizer::tokenize("print a ? b;\na ? b : c = d;".to_string()).unwrap();
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at ';': Expect ':' after then branch of conditional expression.",
+            "[line 2] Error at '=': Invalid assignment target."
+        ]
+    );
+}
+
+#[test]
+fn lambdas() {
+    assert_eq!(_parse("fun (a, b) { return a; }"), "(fun (a b) (return a))");
+    assert_eq!(_parse("f(fun () {})"), "(call f (fun ()))");
+
+    let input = "var f = fun (a) {\n    return a;\n};\nfun () {}();\n";
+    assert_eq!(_format(input), input);
+}
+
+#[test]
+fn lambda_errors() {
+    let input = "fun 1() {}\nvar f = fun g() {};\nvar h = fun {};";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at '1': Expect function name or '(' after 'fun'.",
+            "[line 2] Error at 'g': Named functions can only be declared as statements.",
+            "[line 3] Error at '{': Expect '(' after 'fun'."
+        ]
+    );
+}
+
+#[test]
+fn arithmetic_and_bitwise_precedence() {
+    assert_eq!(_parse("-2 ** 3 ** 2"), "(- (** 2.0 (** 3.0 2.0)))");
+    assert_eq!(_parse("2 ** -1"), "(** 2.0 (- 1.0))");
+    assert_eq!(_parse("a % b * c"), "(* (% a b) c)");
+    assert_eq!(
+        _parse("a | b ^ c & d << e + f"),
+        "(| a (^ b (& c (<< d (+ e f)))))"
+    );
+    assert_eq!(_parse("~a >> b < c"), "(< (>> (~ a) b) c)");
+    assert_eq!(
+        _format("print (-2) ** (3 ** 2);"),
+        "print (-2) ** 3 ** 2;\n"
+    );
+    assert_eq!(_format("print (2 ** 3) ** 2;"), "print (2 ** 3) ** 2;\n");
+    assert_eq!(_format("print (a | b) & c;"), "print (a | b) & c;\n");
+    assert_eq!(
+        _optimize("print 7 % -3 + 2 ** 3 + (6 ^ 3);", OptLevel::Fold),
+        "(print 11.0)"
+    );
+    assert_eq!(
+        _optimize("print 1.5 | 1;", OptLevel::Fold),
+        "(print (| 1.5 1.0))"
+    );
+}
+
+#[test]
+fn operators_need_extensions() {
+    let output = Tokenizer::tokenize("% & | ^ ~\n** << >>".to_string()).unwrap();
+    let errors: Vec<String> = output.get_errors().iter().map(|e| e.to_string()).collect();
+    let tokens: Vec<&TokenType> = output
+        .get_tokens()
+        .iter()
+        .map(|token| token.get_type())
+        .filter(|token_type| !matches!(token_type, TokenType::Whitespace | TokenType::Unkonwn))
+        .collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error: Unexpected character: %",
+            "[line 1] Error: Unexpected character: &",
+            "[line 1] Error: Unexpected character: |",
+            "[line 1] Error: Unexpected character: ^",
+            "[line 1] Error: Unexpected character: ~",
+        ]
+    );
+    assert_eq!(
+        tokens,
+        vec![
+            &TokenType::Star,
+            &TokenType::Star,
+            &TokenType::Less,
+            &TokenType::Less,
+            &TokenType::Greater,
+            &TokenType::Greater,
+            &TokenType::EOF,
+        ]
+    );
+}
+
+#[test]
+fn compound_assignment_and_increment() {
+    assert_eq!(_parse("a += b = c"), "(+= a (= b c))");
+    assert_eq!(_parse("a.b *= c ? d : e"), "(*= (. a b) (?: c d e))");
+    assert_eq!(_parse("-a++ ** 2"), "(- (** (post++ a) 2.0))");
+    assert_eq!(_parse("--a.b - c--"), "(- (pre-- (. a b)) (post-- c))");
+    assert_eq!(_parse("a - -b"), "(- a (- b))");
+    assert_eq!(
+        _format(
+            "print -(--a) + b++ ** 2;
+x.y /= 2;
+"
+        ),
+        "print - --a + b++ ** 2;
+x.y /= 2;
+"
+    );
+}
+
+#[test]
+fn increment_needs_extensions() {
+    let parse = |input: &str| {
+        let output = Tokenizer::tokenize(input.to_string()).unwrap();
+        Parser::parse_tokens(output.get_tokens())
+            .map_err(|err| err.to_string())
+            .map(|ast| ast.to_string())
+    };
+
+    assert_eq!(parse("--(3)"), Ok("(- (- (group 3.0)))".to_string()));
+    assert_eq!(parse("1--1"), Ok("(- 1.0 (- 1.0))".to_string()));
+    assert!(parse("a += 1").is_err());
+}
+
+#[test]
+fn compound_assignment_errors() {
+    let input = "1 += 2;
+++1;
+(a)++;
+a + b -= c;";
+    let output = _tokenize(input);
+    let errors = Parser::parse_program_with(output.get_tokens(), true).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at '+=': Invalid assignment target.",
+            "[line 2] Error at '++': Invalid assignment target.",
+            "[line 3] Error at '++': Invalid assignment target.",
+            "[line 4] Error at '-=': Invalid assignment target.",
+        ]
+    );
+}
+
+#[test]
+fn string_interpolation() {
+    assert_eq!(
+        _parse("\"a ${b + 1} c ${\"${d}\"}\""),
+        "(interpolate a  (+ b 1.0)  c  (interpolate d))"
+    );
+    assert_eq!(
+        _parse("\"${fun () { return \"}\"; }()}\""),
+        "(interpolate (call (fun () (return }))))"
+    );
+
+    let input = "print \"Hello ${name}, ${count + 1} items\" + \"${x ? \"y\" : z}\";\n";
+    assert_eq!(_format(input), input);
+
+    let ast = _parse_ast("\"ab ${cd + 1}\nx\"");
+    let root = ast.root().unwrap();
+    let Expression::Interpolation(parts) = &ast[root] else {
+        panic!("Expected interpolation");
+    };
+    assert_eq!(ast.span(parts[1]), Span::new(1, 7, 6));
+    assert_eq!((ast.span(root).end_line, ast.span(root).end_column), (2, 3));
+}
+
+#[test]
+fn interpolation_errors() {
+    let input = "print \"x ${a +} y\";\nprint \"${a b}\";\nprint \"${\n  a +\n}\";";
+    let output = Tokenizer::tokenize_with(input.to_string(), true).unwrap();
+    let errors = Parser::parse_program_with(output.get_tokens(), true).unwrap_err();
+    let positions: Vec<(usize, usize)> = errors
+        .iter()
+        .map(|err| (err.get_span().line, err.get_span().column))
+        .collect();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at '}': Expect expression.",
+            "[line 2] Error at 'b': Expect '}' after interpolated expression.",
+            "[line 5] Error at '}': Expect expression.",
+        ]
+    );
+    assert_eq!(positions, vec![(1, 15), (2, 12), (5, 1)]);
+
+    let output = Tokenizer::tokenize_with("\"a ${b".to_string(), true).unwrap();
+    let errors: Vec<String> = output.get_errors().iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors, vec!["[line 1] Error: Unterminated string."]);
+}
+
+#[test]
+fn interpolation_needs_extensions() {
+    let output = Tokenizer::tokenize("\"a ${b} {\" \"${\"".to_string()).unwrap();
+    let tokens: Vec<(TokenType, String)> = output
+        .get_tokens()
+        .iter()
+        .filter(|token| !matches!(token.get_type(), TokenType::Whitespace | TokenType::EOF))
+        .map(|token| (token.get_type().clone(), token.get_literal()))
+        .collect();
+
+    assert!(output.get_errors().is_empty());
+    assert_eq!(
+        tokens,
+        vec![
+            (TokenType::String, "a ${b} {".to_string()),
+            (TokenType::String, "${".to_string()),
+        ]
+    );
+
+    let output = Tokenizer::tokenize_with("\"a ${b}\"".to_string(), true).unwrap();
+    assert_eq!(output.get_tokens()[0].get_type(), &TokenType::Interpolation);
+}
+
+#[test]
+fn program_errors() {
+    let input = "var = 1;\nprint 2\n";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at '=': Expect variable name.",
+            "[line 2] Error at end: Expect ';' after value."
+        ]
+    );
+}
+
+#[test]
+fn format_minimal_parentheses() {
+    assert_eq!(_format("print ((1 + 2)) + 3;"), "print 1 + 2 + 3;\n");
+    assert_eq!(_format("print 1 - (2 - 3);"), "print 1 - (2 - 3);\n");
+    assert_eq!(_format("print (1 + 2) * 3;"), "print (1 + 2) * 3;\n");
+    assert_eq!(_format("print -(-1);"), "print - -1;\n");
+    assert_eq!(_format("print (a.b)(c);"), "print a.b(c);\n");
+    assert_eq!(_format("a = (b = c);"), "a = b = c;\n");
+}
+
+#[test]
+fn format_preserves_comments() {
+    let input = "// head\nvar a = 1; // tail\n\n\nfun f() {\n// inside\n}\n// end\n";
+    let expected = "// head\nvar a = 1; // tail\n\nfun f() {\n    // inside\n}\n// end\n";
+
+    assert_eq!(_format(input), expected);
+}
+
+#[test]
+fn format_keeps_comments_in_place() {
+    assert_eq!(
+        _format("if (x) { return -(-x); } else print (1+2)*3; // after\n"),
+        "if (x) {\n    return - -x;\n} else print (1 + 2) * 3; // after\n"
+    );
+    assert_eq!(
+        _format("var a = 1 +   // mid\n  2;\nprint a;\n"),
+        "var a = 1 +   // mid\n  2;\nprint a;\n"
+    );
+    assert_eq!(
+        _format("fun f(x) { // trailing\nprint x;\n}\n"),
+        "fun f(x) { // trailing\n    print x;\n}\n"
+    );
+    assert_eq!(
+        _format("{ { print 1; } // closed\n}\n"),
+        "{\n    {\n        print 1;\n    } // closed\n}\n"
+    );
+}
+
+#[test]
+fn format_is_idempotent() {
+    let input = "class A<B{init(x){this.x=x;}get(){return super.get()*(1+2);}}\nfor(var i=0;i<10;i=i+1){if(i>5)print i;else{print -i;}}\nfor(;;){}";
+    let once = _format(input);
+
+    assert_eq!(_format(&once), once);
+    assert_eq!(
+        once,
+        "class A < B {\n    init(x) {\n        this.x = x;\n    }\n    get() {\n        return super.get() * (1 + 2);\n    }\n}\nfor (var i = 0; i < 10; i = i + 1) {\n    if (i > 5) print i; else {\n        print -i;\n    }\n}\nfor (;;) {}\n"
+    );
+}
+
+#[test]
+fn optimizer_folds_constants() {
+    let mut ast = _parse_ast("(1 + 3) * 5 / 2");
+    Optimizer::optimize(&mut ast, OptLevel::Fold);
+    assert_eq!(ast.to_string(), "10.0");
+
+    assert_eq!(
+        _optimize(
+            "print -(2 - 3) >= 1; print \"a\" + \"b\"; print !nil != (1 != 1);",
+            OptLevel::Fold
+        ),
+        "(print true)\n(print ab)\n(print true)"
+    );
+    assert_eq!(
+        _optimize("print 1 / 0;", OptLevel::Fold),
+        "(print (/ 1.0 0.0))"
+    );
+    assert_eq!(
+        _optimize("print (1 + 2);", OptLevel::None),
+        "(print (group (+ 1.0 2.0)))"
+    );
+}
+
+#[test]
+fn optimizer_keeps_runtime_errors() {
+    assert_eq!(
+        _optimize("print (1 + 2) + \"a\"; print -\"b\";", OptLevel::Full),
+        "(print (+ 3.0 a))\n(print (- b))"
+    );
+}
+
+#[test]
+fn optimizer_simplifies_control_flow() {
+    assert_eq!(
+        _optimize("if (!!x) print 1; while (!!!!x) print 2;", OptLevel::Full),
+        "(if x (print 1.0))\n(while x (print 2.0))"
+    );
+    assert_eq!(
+        _optimize("if (!!x) print 1;", OptLevel::Fold),
+        "(if (! (! x)) (print 1.0))"
+    );
+    assert_eq!(
+        _optimize(
+            "if (1 > 2) print 1; else print 2; if (false) print 3;",
+            OptLevel::Full
+        ),
+        "(print 2.0)\n(block)"
+    );
+    assert_eq!(
+        _optimize("if (\"\") print 1;", OptLevel::Full),
+        "(print 1.0)"
+    );
+}
+
+#[test]
+fn lists_need_extensions() {
+    let output = Tokenizer::tokenize("[in]".to_string()).unwrap();
+    assert_eq!(output.get_errors().len(), 2);
+    assert!(output
+        .get_tokens()
+        .iter()
+        .any(|token| token.get_type() == &TokenType::Identifier));
+
+    let output = Tokenizer::tokenize_with("[in]".to_string(), true).unwrap();
+    assert!(output.get_errors().is_empty());
+    let types: Vec<_> = output.get_tokens().iter().map(|t| t.get_type()).collect();
+    assert_eq!(
+        types[..3],
+        [
+            &TokenType::LeftBracket,
+            &TokenType::In,
+            &TokenType::RightBracket
+        ]
+    );
+}
+
+#[test]
+fn maps_need_extensions() {
+    let input = "var a = {}; print a;";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    assert!(output.get_errors().is_empty());
+
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 1] Error at '{': Expect expression."
+    );
+    assert!(Parser::parse_program_with(output.get_tokens(), true).is_ok());
+    assert!(
+        Parser::parse_tokens(Tokenizer::tokenize("{}".to_string()).unwrap().get_tokens()).is_err()
+    );
+}
+
+#[test]
+fn colon_is_standard_only_for_conditionals() {
+    let output = Tokenizer::tokenize("print {\"a\": 1};\nprint a ? b : c;".to_string()).unwrap();
+    assert!(output.get_errors().is_empty());
+
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 1] Error at '{': Expect expression."
+    );
+
+    let output = Tokenizer::tokenize("print a ? b : c;".to_string()).unwrap();
+    let ast = Parser::parse_program(output.get_tokens()).unwrap();
+    assert_eq!(AstPrinter::print_program(&ast), "(print (?: a b c))");
+}
+
+#[test]
+fn list_syntax() {
+    let input = "var a = [1, [2]];\na[0] = a[1][0];\nfor (var x in a) print x;\n";
+    let output = Tokenizer::tokenize_with(input.to_string(), true).unwrap();
+    let ast = Parser::parse_program_with(output.get_tokens(), true).unwrap();
+
+    assert_eq!(
+        AstPrinter::print_program(&ast),
+        "(var a (list 1.0 (list 2.0)))\n\
+         (; (= ([] a 0.0) ([] ([] a 1.0) 0.0)))\n\
+         (for-in x a (print x))"
+    );
+    assert_eq!(LoxPrinter::print(input, output.get_tokens(), &ast), input);
+
+    let output = Tokenizer::tokenize_with("a[1;".to_string(), true).unwrap();
+    let errors = Parser::parse_program_with(output.get_tokens(), true).unwrap_err();
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 1] Error at ';': Expect ']' after index."
+    );
+}
+
+#[test]
+fn map_syntax() {
+    let input = "var m = {\"a\": 1, 2: {}};\n{\n    m[\"a\"] = m[2];\n}\n";
+    let output = Tokenizer::tokenize_with(input.to_string(), true).unwrap();
+    let ast = Parser::parse_program_with(output.get_tokens(), true).unwrap();
+
+    assert_eq!(
+        AstPrinter::print_program(&ast),
+        "(var m (map a 1.0 2.0 (map)))\n(block (; (= ([] m a) ([] m 2.0))))"
+    );
+    assert_eq!(LoxPrinter::print(input, output.get_tokens(), &ast), input);
+
+    let output = Tokenizer::tokenize_with("print {\"a\" 1};".to_string(), true).unwrap();
+    let errors = Parser::parse_program_with(output.get_tokens(), true).unwrap_err();
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 1] Error at '1': Expect ':' after map key."
+    );
+}
+
+#[test]
+fn import_statements() {
+    let input = "import \"lib/math.lox\";\nimport \"util.lox\" as util;\nvar as = 1;\n";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let ast = Parser::parse_program(output.get_tokens()).unwrap();
+
+    assert_eq!(
+        AstPrinter::print_program(&ast),
+        "(import \"lib/math.lox\")\n(import \"util.lox\" as util)\n(var as 1.0)"
+    );
+    assert_eq!(_format(input), input);
+}
+
+#[test]
+fn import_errors() {
+    let input = "import math;
+import \"a.lox\" as;
+import \"a.lox\"
+print 1;";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at 'math': Expect module path after 'import'.",
+            "[line 2] Error at ';': Expect module name after 'as'.",
+            "[line 4] Error at 'print': Expect ';' after import.",
+        ]
+    );
+}
+
+#[test]
+fn try_statements() {
+    let input = "try {
+    throw \"a\" + b;
+} catch (e) {
+    print e;
+} finally {
+    print 1;
+}
+try {
+    f();
+} finally {}
+";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let ast = Parser::parse_program(output.get_tokens()).unwrap();
+
+    assert_eq!(
+        AstPrinter::print_program(&ast),
+        "(try (block (throw (+ a b))) (catch e (block (print e))) (finally (block (print 1.0))))
+(try (block (; (call f))) (finally (block)))"
+    );
+    assert_eq!(_format(input), input);
+}
+
+#[test]
+fn try_errors() {
+    let input = "try print 1;
+try {} print 1;
+try {} catch e {}
+try {} catch (1) {}
+try {} catch (e) print e;
+throw 1
+print 2;";
+    let output = Tokenizer::tokenize(input.to_string()).unwrap();
+    let errors = Parser::parse_program(output.get_tokens()).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "[line 1] Error at 'print': Expect '{' after 'try'.",
+            "[line 2] Error at 'print': Expect 'catch' or 'finally' after try block.",
+            "[line 3] Error at 'e': Expect '(' after 'catch'.",
+            "[line 4] Error at '1': Expect error variable name.",
+            "[line 5] Error at 'print': Expect '{' before catch body.",
+            "[line 7] Error at 'print': Expect ';' after thrown value.",
+        ]
+    );
+}
@@ -0,0 +1,784 @@
+use super::{
+    ast::{Ast, ExprId, Identifier, StmtId},
+    expression::{
+        assign::Assign,
+        binary::Binary,
+        call::Call,
+        conditional::Conditional,
+        index::{Index, SetIndex},
+        logical::Logical,
+        property::{Get, Set},
+        unary::Unary,
+        update::{Update, UpdateKind},
+        Expression,
+    },
+    statement::{
+        control_flow::{Catch, For, ForIn, If, Return, Try, While},
+        declaration::{Class, Function, Import, Var},
+        Statement,
+    },
+};
+use crate::tokenizer::Span;
+
+/// Read-only traversal over the AST. Every method defaults to walking into the
+/// node's children, so a pass only overrides the nodes it cares about.
+pub trait Visitor<'ast>: Sized {
+    fn visit_program(&mut self, ast: &'ast Ast) {
+        walk_program(self, ast)
+    }
+
+    fn visit_statement(&mut self, ast: &'ast Ast, id: StmtId) {
+        walk_statement(self, ast, id)
+    }
+
+    fn visit_expression_statement(&mut self, ast: &'ast Ast, _id: StmtId, expression: ExprId) {
+        self.visit_expression(ast, expression)
+    }
+
+    fn visit_print(&mut self, ast: &'ast Ast, _id: StmtId, expression: ExprId) {
+        self.visit_expression(ast, expression)
+    }
+
+    fn visit_var(&mut self, ast: &'ast Ast, _id: StmtId, var: &'ast Var) {
+        walk_var(self, ast, var)
+    }
+
+    fn visit_block(&mut self, ast: &'ast Ast, _id: StmtId, statements: &'ast [StmtId]) {
+        walk_statements(self, ast, statements)
+    }
+
+    fn visit_if(&mut self, ast: &'ast Ast, _id: StmtId, if_statement: &'ast If) {
+        walk_if(self, ast, if_statement)
+    }
+
+    fn visit_while(&mut self, ast: &'ast Ast, _id: StmtId, while_statement: &'ast While) {
+        walk_while(self, ast, while_statement)
+    }
+
+    fn visit_for(&mut self, ast: &'ast Ast, _id: StmtId, for_statement: &'ast For) {
+        walk_for(self, ast, for_statement)
+    }
+
+    fn visit_for_in(&mut self, ast: &'ast Ast, _id: StmtId, for_in: &'ast ForIn) {
+        walk_for_in(self, ast, for_in)
+    }
+
+    fn visit_function(&mut self, ast: &'ast Ast, _id: StmtId, function: &'ast Function) {
+        walk_statements(self, ast, function.body())
+    }
+
+    fn visit_return(&mut self, ast: &'ast Ast, _id: StmtId, return_statement: &'ast Return) {
+        if let Some(value) = return_statement.value() {
+            self.visit_expression(ast, value)
+        }
+    }
+
+    fn visit_break(&mut self, _ast: &'ast Ast, _id: StmtId, _keyword: Span) {}
+
+    fn visit_continue(&mut self, _ast: &'ast Ast, _id: StmtId, _keyword: Span) {}
+
+    fn visit_class(&mut self, ast: &'ast Ast, _id: StmtId, class: &'ast Class) {
+        walk_class(self, ast, class)
+    }
+
+    fn visit_import(&mut self, _ast: &'ast Ast, _id: StmtId, _import: &'ast Import) {}
+
+    fn visit_throw(&mut self, ast: &'ast Ast, _id: StmtId, value: ExprId) {
+        self.visit_expression(ast, value)
+    }
+
+    fn visit_try(&mut self, ast: &'ast Ast, _id: StmtId, try_statement: &'ast Try) {
+        walk_try(self, ast, try_statement)
+    }
+
+    fn visit_expression(&mut self, ast: &'ast Ast, id: ExprId) {
+        walk_expression(self, ast, id)
+    }
+
+    fn visit_grouping(&mut self, ast: &'ast Ast, _id: ExprId, inner: ExprId) {
+        self.visit_expression(ast, inner)
+    }
+
+    fn visit_binary(&mut self, ast: &'ast Ast, _id: ExprId, binary: &'ast Binary) {
+        walk_binary(self, ast, binary)
+    }
+
+    fn visit_unary(&mut self, ast: &'ast Ast, _id: ExprId, unary: &'ast Unary) {
+        walk_unary(self, ast, unary)
+    }
+
+    fn visit_logical(&mut self, ast: &'ast Ast, _id: ExprId, logical: &'ast Logical) {
+        self.visit_expression(ast, logical.left());
+        self.visit_expression(ast, logical.right());
+    }
+
+    fn visit_conditional(&mut self, ast: &'ast Ast, _id: ExprId, conditional: &'ast Conditional) {
+        self.visit_expression(ast, conditional.condition());
+        self.visit_expression(ast, conditional.then_branch());
+        self.visit_expression(ast, conditional.else_branch());
+    }
+
+    fn visit_variable(&mut self, _ast: &'ast Ast, _id: ExprId, _name: &'ast Identifier) {}
+
+    fn visit_assign(&mut self, ast: &'ast Ast, _id: ExprId, assign: &'ast Assign) {
+        self.visit_expression(ast, assign.value())
+    }
+
+    fn visit_call(&mut self, ast: &'ast Ast, _id: ExprId, call: &'ast Call) {
+        walk_call(self, ast, call)
+    }
+
+    fn visit_get(&mut self, ast: &'ast Ast, _id: ExprId, get: &'ast Get) {
+        self.visit_expression(ast, get.object())
+    }
+
+    fn visit_set(&mut self, ast: &'ast Ast, _id: ExprId, set: &'ast Set) {
+        self.visit_expression(ast, set.object());
+        self.visit_expression(ast, set.value());
+    }
+
+    fn visit_list(&mut self, ast: &'ast Ast, _id: ExprId, elements: &'ast [ExprId]) {
+        for element in elements {
+            self.visit_expression(ast, *element);
+        }
+    }
+
+    fn visit_map(&mut self, ast: &'ast Ast, _id: ExprId, entries: &'ast [(ExprId, ExprId)]) {
+        for (key, value) in entries {
+            self.visit_expression(ast, *key);
+            self.visit_expression(ast, *value);
+        }
+    }
+
+    fn visit_index(&mut self, ast: &'ast Ast, _id: ExprId, index: &'ast Index) {
+        self.visit_expression(ast, index.object());
+        self.visit_expression(ast, index.index());
+    }
+
+    fn visit_set_index(&mut self, ast: &'ast Ast, _id: ExprId, set_index: &'ast SetIndex) {
+        self.visit_expression(ast, set_index.object());
+        self.visit_expression(ast, set_index.index());
+        self.visit_expression(ast, set_index.value());
+    }
+
+    fn visit_update(&mut self, ast: &'ast Ast, _id: ExprId, update: &'ast Update) {
+        self.visit_expression(ast, update.target());
+        if let Some(value) = update.value() {
+            self.visit_expression(ast, value);
+        }
+    }
+
+    fn visit_interpolation(&mut self, ast: &'ast Ast, _id: ExprId, parts: &'ast [ExprId]) {
+        for part in parts {
+            self.visit_expression(ast, *part);
+        }
+    }
+
+    /// `function` is the `Function` statement of the anonymous function.
+    fn visit_lambda(&mut self, ast: &'ast Ast, _id: ExprId, function: StmtId) {
+        self.visit_statement(ast, function)
+    }
+
+    fn visit_this(&mut self, _ast: &'ast Ast, _id: ExprId) {}
+
+    fn visit_super(&mut self, _ast: &'ast Ast, _id: ExprId, _method: &'ast Identifier) {}
+
+    fn visit_literal(&mut self, _ast: &'ast Ast, _id: ExprId, _literal: &'ast Expression) {}
+}
+
+pub fn walk_program<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast) {
+    walk_statements(visitor, ast, ast.program())
+}
+
+pub fn walk_statements<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    ast: &'ast Ast,
+    statements: &[StmtId],
+) {
+    for statement in statements {
+        visitor.visit_statement(ast, *statement);
+    }
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, id: StmtId) {
+    match &ast[id] {
+        Statement::Expression(expression) => {
+            visitor.visit_expression_statement(ast, id, *expression)
+        }
+        Statement::Print(expression) => visitor.visit_print(ast, id, *expression),
+        Statement::Var(var) => visitor.visit_var(ast, id, var),
+        Statement::Block(statements) => visitor.visit_block(ast, id, statements),
+        Statement::If(if_statement) => visitor.visit_if(ast, id, if_statement),
+        Statement::While(while_statement) => visitor.visit_while(ast, id, while_statement),
+        Statement::For(for_statement) => visitor.visit_for(ast, id, for_statement),
+        Statement::ForIn(for_in) => visitor.visit_for_in(ast, id, for_in),
+        Statement::Function(function) => visitor.visit_function(ast, id, function),
+        Statement::Return(return_statement) => visitor.visit_return(ast, id, return_statement),
+        Statement::Break(keyword) => visitor.visit_break(ast, id, *keyword),
+        Statement::Continue(keyword) => visitor.visit_continue(ast, id, *keyword),
+        Statement::Class(class) => visitor.visit_class(ast, id, class),
+        Statement::Import(import) => visitor.visit_import(ast, id, import),
+        Statement::Throw(value) => visitor.visit_throw(ast, id, *value),
+        Statement::Try(try_statement) => visitor.visit_try(ast, id, try_statement),
+    }
+}
+
+pub fn walk_try<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, try_statement: &Try) {
+    visitor.visit_statement(ast, try_statement.body());
+    if let Some(catch) = try_statement.catch() {
+        visitor.visit_statement(ast, catch.body());
+    }
+    if let Some(finally) = try_statement.finally() {
+        visitor.visit_statement(ast, finally);
+    }
+}
+
+pub fn walk_var<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, var: &Var) {
+    if let Some(initializer) = var.initializer() {
+        visitor.visit_expression(ast, initializer);
+    }
+}
+
+pub fn walk_if<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, if_statement: &If) {
+    visitor.visit_expression(ast, if_statement.condition());
+    visitor.visit_statement(ast, if_statement.then_branch());
+    if let Some(else_branch) = if_statement.else_branch() {
+        visitor.visit_statement(ast, else_branch);
+    }
+}
+
+pub fn walk_while<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    ast: &'ast Ast,
+    while_statement: &While,
+) {
+    visitor.visit_expression(ast, while_statement.condition());
+    visitor.visit_statement(ast, while_statement.body());
+}
+
+pub fn walk_for<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, for_statement: &For) {
+    if let Some(initializer) = for_statement.initializer() {
+        visitor.visit_statement(ast, initializer);
+    }
+    if let Some(condition) = for_statement.condition() {
+        visitor.visit_expression(ast, condition);
+    }
+    if let Some(increment) = for_statement.increment() {
+        visitor.visit_expression(ast, increment);
+    }
+    visitor.visit_statement(ast, for_statement.body());
+}
+
+pub fn walk_for_in<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, for_in: &ForIn) {
+    visitor.visit_expression(ast, for_in.iterable());
+    visitor.visit_statement(ast, for_in.body());
+}
+
+pub fn walk_class<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, class: &Class) {
+    if let Some(superclass) = class.superclass() {
+        visitor.visit_expression(ast, superclass);
+    }
+    walk_statements(visitor, ast, class.methods())
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, id: ExprId) {
+    match &ast[id] {
+        Expression::Grouping(inner) => visitor.visit_grouping(ast, id, *inner),
+        Expression::Binary(binary) => visitor.visit_binary(ast, id, binary),
+        Expression::Unary(unary) => visitor.visit_unary(ast, id, unary),
+        Expression::Logical(logical) => visitor.visit_logical(ast, id, logical),
+        Expression::Conditional(conditional) => visitor.visit_conditional(ast, id, conditional),
+        Expression::Variable(name) => visitor.visit_variable(ast, id, name),
+        Expression::Assign(assign) => visitor.visit_assign(ast, id, assign),
+        Expression::Call(call) => visitor.visit_call(ast, id, call),
+        Expression::Get(get) => visitor.visit_get(ast, id, get),
+        Expression::Set(set) => visitor.visit_set(ast, id, set),
+        Expression::List(elements) => visitor.visit_list(ast, id, elements),
+        Expression::Map(entries) => visitor.visit_map(ast, id, entries),
+        Expression::Index(index) => visitor.visit_index(ast, id, index),
+        Expression::SetIndex(set_index) => visitor.visit_set_index(ast, id, set_index),
+        Expression::Update(update) => visitor.visit_update(ast, id, update),
+        Expression::Interpolation(parts) => visitor.visit_interpolation(ast, id, parts),
+        Expression::Lambda(function) => visitor.visit_lambda(ast, id, *function),
+        Expression::This => visitor.visit_this(ast, id),
+        Expression::Super(method) => visitor.visit_super(ast, id, method),
+        literal => visitor.visit_literal(ast, id, literal),
+    }
+}
+
+pub fn walk_binary<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, binary: &Binary) {
+    visitor.visit_expression(ast, binary.left());
+    visitor.visit_expression(ast, binary.right());
+}
+
+pub fn walk_unary<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, unary: &Unary) {
+    visitor.visit_expression(ast, unary.expression());
+}
+
+pub fn walk_call<'ast, V: Visitor<'ast>>(visitor: &mut V, ast: &'ast Ast, call: &Call) {
+    visitor.visit_expression(ast, call.callee());
+    for argument in call.arguments() {
+        visitor.visit_expression(ast, *argument);
+    }
+}
+
+/// In-place traversal over the AST, for passes that rewrite nodes without
+/// changing the shape of the tree. Nodes are handed out by value and can be
+/// written back through `ast[id]`.
+pub trait VisitorMut: Sized {
+    fn visit_program_mut(&mut self, ast: &mut Ast) {
+        for statement in ast.program().to_vec() {
+            self.visit_statement_mut(ast, statement);
+        }
+    }
+
+    fn visit_statement_mut(&mut self, ast: &mut Ast, id: StmtId) {
+        walk_statement_mut(self, ast, id)
+    }
+
+    fn visit_expression_mut(&mut self, ast: &mut Ast, id: ExprId) {
+        walk_expression_mut(self, ast, id)
+    }
+
+    fn visit_grouping_mut(&mut self, ast: &mut Ast, _id: ExprId, inner: ExprId) {
+        self.visit_expression_mut(ast, inner)
+    }
+
+    fn visit_binary_mut(&mut self, ast: &mut Ast, _id: ExprId, binary: Binary) {
+        walk_binary_mut(self, ast, binary)
+    }
+
+    fn visit_unary_mut(&mut self, ast: &mut Ast, _id: ExprId, unary: Unary) {
+        walk_unary_mut(self, ast, unary)
+    }
+
+    fn visit_literal_mut(&mut self, _ast: &mut Ast, _id: ExprId) {}
+}
+
+pub fn walk_statement_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, id: StmtId) {
+    let (statements, expressions) = statement_children(&ast[id]);
+
+    for expression in expressions {
+        visitor.visit_expression_mut(ast, expression);
+    }
+    for statement in statements {
+        visitor.visit_statement_mut(ast, statement);
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, id: ExprId) {
+    match ast[id].clone() {
+        Expression::Grouping(inner) => visitor.visit_grouping_mut(ast, id, inner),
+        Expression::Binary(binary) => visitor.visit_binary_mut(ast, id, binary),
+        Expression::Unary(unary) => visitor.visit_unary_mut(ast, id, unary),
+        Expression::Lambda(function) => visitor.visit_statement_mut(ast, function),
+        expression if expression.is_literal() => visitor.visit_literal_mut(ast, id),
+        expression => {
+            for child in expression_children(&expression) {
+                visitor.visit_expression_mut(ast, child);
+            }
+        }
+    }
+}
+
+pub fn walk_binary_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, binary: Binary) {
+    visitor.visit_expression_mut(ast, binary.left());
+    visitor.visit_expression_mut(ast, binary.right());
+}
+
+pub fn walk_unary_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, unary: Unary) {
+    visitor.visit_expression_mut(ast, unary.expression());
+}
+
+/// Direct child expressions of an expression, in evaluation order.
+pub fn expression_children(expression: &Expression) -> Vec<ExprId> {
+    match expression {
+        Expression::Grouping(inner) => vec![*inner],
+        Expression::Binary(binary) => vec![binary.left(), binary.right()],
+        Expression::Unary(unary) => vec![unary.expression()],
+        Expression::Logical(logical) => vec![logical.left(), logical.right()],
+        Expression::Conditional(conditional) => vec![
+            conditional.condition(),
+            conditional.then_branch(),
+            conditional.else_branch(),
+        ],
+        Expression::Assign(assign) => vec![assign.value()],
+        Expression::Call(call) => std::iter::once(call.callee())
+            .chain(call.arguments().iter().copied())
+            .collect(),
+        Expression::Get(get) => vec![get.object()],
+        Expression::Set(set) => vec![set.object(), set.value()],
+        Expression::List(elements) => elements.clone(),
+        Expression::Map(entries) => entries
+            .iter()
+            .flat_map(|(key, value)| [*key, *value])
+            .collect(),
+        Expression::Index(index) => vec![index.object(), index.index()],
+        Expression::SetIndex(set_index) => {
+            vec![set_index.object(), set_index.index(), set_index.value()]
+        }
+        Expression::Interpolation(parts) => parts.clone(),
+        Expression::Update(update) => std::iter::once(update.target())
+            .chain(update.value())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Direct child statements and expressions of a statement.
+pub fn statement_children(statement: &Statement) -> (Vec<StmtId>, Vec<ExprId>) {
+    match statement {
+        Statement::Expression(expression)
+        | Statement::Print(expression)
+        | Statement::Throw(expression) => (Vec::new(), vec![*expression]),
+        Statement::Var(var) => (Vec::new(), var.initializer().into_iter().collect()),
+        Statement::Block(statements) => (statements.clone(), Vec::new()),
+        Statement::If(if_statement) => (
+            std::iter::once(if_statement.then_branch())
+                .chain(if_statement.else_branch())
+                .collect(),
+            vec![if_statement.condition()],
+        ),
+        Statement::While(while_statement) => (
+            vec![while_statement.body()],
+            vec![while_statement.condition()],
+        ),
+        Statement::For(for_statement) => (
+            for_statement
+                .initializer()
+                .into_iter()
+                .chain(std::iter::once(for_statement.body()))
+                .collect(),
+            for_statement
+                .condition()
+                .into_iter()
+                .chain(for_statement.increment())
+                .collect(),
+        ),
+        Statement::ForIn(for_in) => (vec![for_in.body()], vec![for_in.iterable()]),
+        Statement::Function(function) => (function.body().to_vec(), Vec::new()),
+        Statement::Return(return_statement) => {
+            (Vec::new(), return_statement.value().into_iter().collect())
+        }
+        Statement::Break(_) | Statement::Continue(_) | Statement::Import(_) => {
+            (Vec::new(), Vec::new())
+        }
+        Statement::Try(try_statement) => (
+            std::iter::once(try_statement.body())
+                .chain(try_statement.catch().map(Catch::body))
+                .chain(try_statement.finally())
+                .collect(),
+            Vec::new(),
+        ),
+        Statement::Class(class) => (
+            class.methods().to_vec(),
+            class.superclass().into_iter().collect(),
+        ),
+    }
+}
+
+/// Rebuilding traversal. Each method returns the id of the node replacing its
+/// input, which may be of a different kind (e.g. a binary folded into a
+/// literal). Replacements are appended to the arena with the span of the node
+/// they replace, and untouched subtrees keep their ids, so side tables stay
+/// valid for them.
+pub trait Fold: Sized {
+    fn fold_statement(&mut self, ast: &mut Ast, id: StmtId) -> StmtId {
+        fold_statement(self, ast, id)
+    }
+
+    fn fold_if(&mut self, ast: &mut Ast, id: StmtId, if_statement: If) -> StmtId {
+        fold_if(self, ast, id, if_statement)
+    }
+
+    fn fold_expression(&mut self, ast: &mut Ast, id: ExprId) -> ExprId {
+        fold_expression(self, ast, id)
+    }
+
+    fn fold_grouping(&mut self, ast: &mut Ast, id: ExprId, inner: ExprId) -> ExprId {
+        let folded = self.fold_expression(ast, inner);
+
+        if folded == inner {
+            id
+        } else {
+            ast.push(Expression::Grouping(folded), ast.span(id))
+        }
+    }
+
+    fn fold_binary(&mut self, ast: &mut Ast, id: ExprId, binary: Binary) -> ExprId {
+        fold_binary(self, ast, id, binary)
+    }
+
+    fn fold_unary(&mut self, ast: &mut Ast, id: ExprId, unary: Unary) -> ExprId {
+        fold_unary(self, ast, id, unary)
+    }
+
+    fn fold_logical(&mut self, ast: &mut Ast, id: ExprId, logical: Logical) -> ExprId {
+        fold_logical(self, ast, id, logical)
+    }
+
+    fn fold_literal(&mut self, _ast: &mut Ast, id: ExprId) -> ExprId {
+        id
+    }
+}
+
+/// Folds the parsed expression, or every top-level statement of a program.
+pub fn fold_ast<F: Fold>(folder: &mut F, ast: &mut Ast) {
+    if let Some(root) = ast.root() {
+        let root = folder.fold_expression(ast, root);
+        ast.set_root(root);
+    }
+
+    let program = ast
+        .program()
+        .to_vec()
+        .into_iter()
+        .map(|statement| folder.fold_statement(ast, statement))
+        .collect();
+    ast.set_program(program);
+}
+
+fn fold_statements<F: Fold>(folder: &mut F, ast: &mut Ast, statements: &[StmtId]) -> Vec<StmtId> {
+    statements
+        .iter()
+        .map(|statement| folder.fold_statement(ast, *statement))
+        .collect()
+}
+
+fn fold_optional<F: Fold>(folder: &mut F, ast: &mut Ast, id: Option<ExprId>) -> Option<ExprId> {
+    id.map(|id| folder.fold_expression(ast, id))
+}
+
+fn replace_statement(ast: &mut Ast, id: StmtId, statement: Statement) -> StmtId {
+    if ast[id] == statement {
+        id
+    } else {
+        ast.push_statement(statement, ast.statement_span(id))
+    }
+}
+
+pub fn fold_statement<F: Fold>(folder: &mut F, ast: &mut Ast, id: StmtId) -> StmtId {
+    let statement = match ast[id].clone() {
+        Statement::If(if_statement) => return folder.fold_if(ast, id, if_statement),
+        Statement::Expression(expression) => {
+            Statement::Expression(folder.fold_expression(ast, expression))
+        }
+        Statement::Print(expression) => Statement::Print(folder.fold_expression(ast, expression)),
+        Statement::Throw(value) => Statement::Throw(folder.fold_expression(ast, value)),
+        Statement::Try(try_statement) => {
+            let body = folder.fold_statement(ast, try_statement.body());
+            let catch = try_statement.catch().map(|catch| {
+                let catch_body = folder.fold_statement(ast, catch.body());
+                Catch::new(catch.get_name().clone(), catch_body)
+            });
+            let finally = try_statement
+                .finally()
+                .map(|finally| folder.fold_statement(ast, finally));
+            Statement::Try(Try::new(body, catch, finally))
+        }
+        Statement::Var(var) => {
+            let initializer = fold_optional(folder, ast, var.initializer());
+            Statement::Var(Var::new(var.get_name().clone(), initializer))
+        }
+        Statement::Block(statements) => Statement::Block(fold_statements(folder, ast, &statements)),
+        Statement::While(while_statement) => {
+            let condition = folder.fold_expression(ast, while_statement.condition());
+            let body = folder.fold_statement(ast, while_statement.body());
+            Statement::While(While::new(condition, body))
+        }
+        Statement::For(for_statement) => {
+            let initializer = for_statement
+                .initializer()
+                .map(|initializer| folder.fold_statement(ast, initializer));
+            let condition = fold_optional(folder, ast, for_statement.condition());
+            let increment = fold_optional(folder, ast, for_statement.increment());
+            let body = folder.fold_statement(ast, for_statement.body());
+            Statement::For(For::new(initializer, condition, increment, body))
+        }
+        Statement::ForIn(for_in) => {
+            let iterable = folder.fold_expression(ast, for_in.iterable());
+            let body = folder.fold_statement(ast, for_in.body());
+            Statement::ForIn(ForIn::new(for_in.get_name().clone(), iterable, body))
+        }
+        Statement::Function(function) => {
+            let body = fold_statements(folder, ast, function.body());
+            Statement::Function(Function::new(
+                function.get_name().clone(),
+                function.params().to_vec(),
+                body,
+            ))
+        }
+        Statement::Return(return_statement) => {
+            let value = fold_optional(folder, ast, return_statement.value());
+            Statement::Return(Return::new(return_statement.keyword_span(), value))
+        }
+        Statement::Break(_) | Statement::Continue(_) | Statement::Import(_) => return id,
+        Statement::Class(class) => {
+            let methods = fold_statements(folder, ast, class.methods());
+            Statement::Class(Class::new(
+                class.get_name().clone(),
+                class.superclass(),
+                methods,
+            ))
+        }
+    };
+
+    replace_statement(ast, id, statement)
+}
+
+pub fn fold_if<F: Fold>(folder: &mut F, ast: &mut Ast, id: StmtId, if_statement: If) -> StmtId {
+    let condition = folder.fold_expression(ast, if_statement.condition());
+    let then_branch = folder.fold_statement(ast, if_statement.then_branch());
+    let else_branch = if_statement
+        .else_branch()
+        .map(|else_branch| folder.fold_statement(ast, else_branch));
+
+    replace_statement(
+        ast,
+        id,
+        Statement::If(If::new(condition, then_branch, else_branch)),
+    )
+}
+
+pub fn fold_expression<F: Fold>(folder: &mut F, ast: &mut Ast, id: ExprId) -> ExprId {
+    let expression = match ast[id].clone() {
+        Expression::Grouping(inner) => return folder.fold_grouping(ast, id, inner),
+        Expression::Binary(binary) => return folder.fold_binary(ast, id, binary),
+        Expression::Unary(unary) => return folder.fold_unary(ast, id, unary),
+        Expression::Logical(logical) => return folder.fold_logical(ast, id, logical),
+        expression if expression.is_literal() => return folder.fold_literal(ast, id),
+        Expression::Conditional(conditional) => {
+            let condition = folder.fold_expression(ast, conditional.condition());
+            let then_branch = folder.fold_expression(ast, conditional.then_branch());
+            let else_branch = folder.fold_expression(ast, conditional.else_branch());
+            Expression::Conditional(Conditional::new(
+                condition,
+                then_branch,
+                else_branch,
+                conditional.question_span(),
+            ))
+        }
+        Expression::Assign(assign) => {
+            let value = folder.fold_expression(ast, assign.value());
+            Expression::Assign(Assign::new(assign.get_name().clone(), value))
+        }
+        Expression::Call(call) => {
+            let callee = folder.fold_expression(ast, call.callee());
+            let arguments = call
+                .arguments()
+                .iter()
+                .map(|argument| folder.fold_expression(ast, *argument))
+                .collect();
+            Expression::Call(Call::new(callee, arguments, call.paren_span()))
+        }
+        Expression::Get(get) => {
+            let object = folder.fold_expression(ast, get.object());
+            Expression::Get(Get::new(object, get.get_name().clone()))
+        }
+        Expression::Set(set) => {
+            let object = folder.fold_expression(ast, set.object());
+            let value = folder.fold_expression(ast, set.value());
+            Expression::Set(Set::new(object, set.get_name().clone(), value))
+        }
+        Expression::List(elements) => Expression::List(
+            elements
+                .iter()
+                .map(|element| folder.fold_expression(ast, *element))
+                .collect(),
+        ),
+        Expression::Map(entries) => Expression::Map(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        folder.fold_expression(ast, *key),
+                        folder.fold_expression(ast, *value),
+                    )
+                })
+                .collect(),
+        ),
+        Expression::Index(index) => {
+            let object = folder.fold_expression(ast, index.object());
+            let key = folder.fold_expression(ast, index.index());
+            Expression::Index(Index::new(object, key, index.bracket_span()))
+        }
+        Expression::SetIndex(set_index) => {
+            let object = folder.fold_expression(ast, set_index.object());
+            let key = folder.fold_expression(ast, set_index.index());
+            let value = folder.fold_expression(ast, set_index.value());
+            Expression::SetIndex(SetIndex::new(object, key, value, set_index.bracket_span()))
+        }
+        Expression::Interpolation(parts) => Expression::Interpolation(
+            parts
+                .iter()
+                .map(|part| folder.fold_expression(ast, *part))
+                .collect(),
+        ),
+        Expression::Update(update) => {
+            let target = folder.fold_expression(ast, update.target());
+            let kind = match update.kind() {
+                UpdateKind::Compound(value) => {
+                    UpdateKind::Compound(folder.fold_expression(ast, value))
+                }
+                kind => kind,
+            };
+            Expression::Update(Update::new(
+                target,
+                *update.get_type(),
+                kind,
+                update.operator_span(),
+            ))
+        }
+        Expression::Lambda(function) => Expression::Lambda(folder.fold_statement(ast, function)),
+        _ => return id,
+    };
+
+    if ast[id] == expression {
+        id
+    } else {
+        ast.push(expression, ast.span(id))
+    }
+}
+
+pub fn fold_binary<F: Fold>(folder: &mut F, ast: &mut Ast, id: ExprId, binary: Binary) -> ExprId {
+    let left = folder.fold_expression(ast, binary.left());
+    let right = folder.fold_expression(ast, binary.right());
+
+    if left == binary.left() && right == binary.right() {
+        return id;
+    }
+
+    let binary = Binary::new(*binary.get_type(), left, right, binary.operator_span());
+
+    ast.push(Expression::Binary(binary), ast.span(id))
+}
+
+pub fn fold_unary<F: Fold>(folder: &mut F, ast: &mut Ast, id: ExprId, unary: Unary) -> ExprId {
+    let expression = folder.fold_expression(ast, unary.expression());
+
+    if expression == unary.expression() {
+        return id;
+    }
+
+    let unary = Unary::new(*unary.get_type(), expression, unary.operator_span());
+
+    ast.push(Expression::Unary(unary), ast.span(id))
+}
+
+pub fn fold_logical<F: Fold>(
+    folder: &mut F,
+    ast: &mut Ast,
+    id: ExprId,
+    logical: Logical,
+) -> ExprId {
+    let left = folder.fold_expression(ast, logical.left());
+    let right = folder.fold_expression(ast, logical.right());
+
+    if left == logical.left() && right == logical.right() {
+        return id;
+    }
+
+    let logical = Logical::new(*logical.get_type(), left, right, logical.operator_span());
+
+    ast.push(Expression::Logical(logical), ast.span(id))
+}
@@ -0,0 +1,267 @@
+use std::{
+    io::{BufRead, IsTerminal, Stdout, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    interpreter::{Interpreter, Program},
+    module::Loader,
+    parser::{optimizer::OptLevel, printer::AstPrinter, Parser},
+    tokenizer::{TokenType, Tokenizer},
+};
+
+const HISTORY_FILE: &str = ".lox_history";
+const HISTORY_LIMIT: usize = 1000;
+const HELP: &str = "\
+:help           show this message
+:tokens <src>   print the tokens of <src>
+:ast <src>      print the syntax tree of <src>
+:env            list global variables
+:history        list previous inputs
+:quit           leave the REPL";
+
+/// Interactive read-eval-print loop over a single interpreter, so globals
+/// persist between inputs. Input with unclosed brackets or strings is
+/// accumulated until it is complete; bare expressions print their value.
+pub struct Repl<W: Write = Stdout> {
+    interpreter: Interpreter<W>,
+    buffer: String,
+    history: Vec<String>,
+    history_file: Option<PathBuf>,
+    interactive: bool,
+    extensions: bool,
+}
+
+impl Repl<Stdout> {
+    pub fn new() -> Self {
+        let history_file =
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        let history = history_file
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|history| history.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Self {
+            interpreter: Interpreter::new(),
+            buffer: String::new(),
+            history,
+            history_file,
+            interactive: std::io::stdin().is_terminal(),
+            extensions: false,
+        }
+    }
+}
+
+impl Default for Repl<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Repl<W> {
+    /// A non-interactive REPL without prompts or a history file, writing all output to `output`.
+    pub fn with_output(output: W) -> Self {
+        Self {
+            interpreter: Interpreter::with_output(output),
+            buffer: String::new(),
+            history: Vec::new(),
+            history_file: None,
+            interactive: false,
+            extensions: false,
+        }
+    }
+
+    /// Accepts the syntax extensions, e.g. list literals, in later input.
+    pub fn set_extensions(&mut self, extensions: bool) {
+        self.extensions = extensions;
+        self.interpreter
+            .set_loader(Loader::new(extensions, OptLevel::default()));
+    }
+
+    pub fn output(&self) -> &W {
+        self.interpreter.output()
+    }
+
+    pub fn run(&mut self, mut input: impl BufRead) -> std::io::Result<()> {
+        loop {
+            if self.interactive {
+                let prompt = match self.buffer.is_empty() {
+                    true => "> ",
+                    false => "... ",
+                };
+                print!("{prompt}");
+                std::io::stdout().flush()?;
+            }
+
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 || !self.line(&line)? {
+                break;
+            }
+        }
+
+        self.save_history()
+    }
+
+    /// Feeds one line of input. Returns `false` once the user asked to quit.
+    pub fn line(&mut self, line: &str) -> std::io::Result<bool> {
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if self.buffer.is_empty() {
+            if let Some(command) = line.trim().strip_prefix(':') {
+                self.history.push(line.trim().to_string());
+                return self.command(command);
+            }
+        }
+
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+
+        if is_incomplete(&self.buffer, self.extensions) {
+            return Ok(true);
+        }
+
+        let source = std::mem::take(&mut self.buffer);
+        if source.trim().is_empty() {
+            return Ok(true);
+        }
+
+        // Joined into one line, a `//` comment would swallow the lines after it.
+        self.history
+            .extend(source.trim().lines().map(str::to_string));
+        self.eval(&source)?;
+
+        Ok(true)
+    }
+
+    fn command(&mut self, command: &str) -> std::io::Result<bool> {
+        let (name, argument) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        let output = self.interpreter.output_mut();
+
+        match name {
+            "help" => writeln!(output, "{HELP}")?,
+            "quit" | "q" => return Ok(false),
+            "tokens" => {
+                let tokenized = Tokenizer::tokenize_with(argument.to_string(), self.extensions)?;
+
+                tokenized.get_errors().iter().for_each(|err| {
+                    eprintln!("[line {}] Error: {}", err.get_line(), err.get_message())
+                });
+                for token in tokenized.get_tokens() {
+                    if !matches!(
+                        token.get_type(),
+                        TokenType::Whitespace
+                            | TokenType::Tab
+                            | TokenType::Unkonwn
+                            | TokenType::Comment
+                    ) {
+                        writeln!(output, "{token}")?;
+                    }
+                }
+            }
+            "ast" => {
+                let tokenized = Tokenizer::tokenize_with(argument.to_string(), self.extensions)?;
+                let tokens = tokenized.get_tokens();
+
+                match Parser::parse_tokens_with(tokens, self.extensions) {
+                    Ok(ast) => writeln!(output, "{ast}")?,
+                    Err(_) => match Parser::parse_program_with(tokens, self.extensions) {
+                        Ok(ast) => writeln!(output, "{}", AstPrinter::print_program(&ast))?,
+                        Err(errors) => errors.iter().for_each(|err| eprintln!("{err}")),
+                    },
+                }
+            }
+            "env" => {
+                for (name, value) in self.interpreter.globals() {
+                    writeln!(self.interpreter.output_mut(), "{name} = {value}")?;
+                }
+            }
+            "history" => {
+                for (index, entry) in self.history.iter().enumerate() {
+                    writeln!(output, "{:>4}  {entry}", index + 1)?;
+                }
+            }
+            _ => eprintln!("Unknown command ':{name}'. Type :help for a list of commands."),
+        }
+
+        Ok(true)
+    }
+
+    fn eval(&mut self, source: &str) -> std::io::Result<()> {
+        let tokenized = Tokenizer::tokenize_with(source.to_string(), self.extensions)?;
+
+        if !tokenized.get_errors().is_empty() {
+            tokenized.get_errors().iter().for_each(|err| {
+                eprintln!("[line {}] Error: {}", err.get_line(), err.get_message())
+            });
+            return Ok(());
+        }
+
+        let tokens = tokenized.get_tokens();
+
+        // A lone expression without a trailing `;` is evaluated and echoed.
+        if let Ok(ast) = Parser::parse_tokens_with(tokens, self.extensions) {
+            match Program::new(ast) {
+                Ok(program) => match self.interpreter.evaluate_root(&program) {
+                    Ok(value) => writeln!(self.interpreter.output_mut(), "{value}")?,
+                    Err(err) => eprintln!("{err}"),
+                },
+                Err(errors) => errors.iter().for_each(|err| eprintln!("{err}")),
+            }
+            return Ok(());
+        }
+
+        match Parser::parse_program_with(tokens, self.extensions).and_then(Program::new) {
+            Ok(program) => {
+                if let Err(err) = self.interpreter.interpret(&program) {
+                    eprintln!("{err}");
+                }
+            }
+            Err(errors) => errors.iter().for_each(|err| eprintln!("{err}")),
+        }
+
+        Ok(())
+    }
+
+    fn save_history(&self) -> std::io::Result<()> {
+        let Some(path) = &self.history_file else {
+            return Ok(());
+        };
+
+        let skip = self.history.len().saturating_sub(HISTORY_LIMIT);
+        let mut history = self.history[skip..].join("\n");
+        history.push('\n');
+
+        std::fs::write(path, history)
+    }
+}
+
+/// Whether `source` still has an unterminated string or more opening than closing brackets.
+fn is_incomplete(source: &str, extensions: bool) -> bool {
+    let Ok(tokenized) = Tokenizer::tokenize_with(source.to_string(), extensions) else {
+        return false;
+    };
+
+    if tokenized
+        .get_errors()
+        .iter()
+        .any(|err| err.get_message() == "Unterminated string.")
+    {
+        return true;
+    }
+
+    let depth = tokenized
+        .get_tokens()
+        .iter()
+        .fold(0i32, |depth, token| match token.get_type() {
+            TokenType::LeftParenthesis | TokenType::LeftCurly | TokenType::LeftBracket => depth + 1,
+            TokenType::RightParenthesis | TokenType::RightCurly | TokenType::RightBracket => {
+                depth - 1
+            }
+            _ => depth,
+        });
+
+    depth > 0
+}
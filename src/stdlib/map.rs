@@ -0,0 +1,98 @@
+use std::{collections::HashMap, hash::Hash};
+
+pub const KEY_ERROR: &str = "Map keys must be numbers, strings, booleans or nil.";
+
+/// A hashable map key. `S` is how a backend refers to a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MapKey<S> {
+    Nil,
+    Bool(bool),
+    Number(u64),
+    String(S),
+}
+
+impl<S> MapKey<S> {
+    /// Numbers are keyed by value rather than by bits: `-0` is the same key as
+    /// `0`, and every NaN is one key even though `NaN != NaN`.
+    pub fn number(value: f64) -> Self {
+        let value = if value == 0.0 {
+            0.0
+        } else if value.is_nan() {
+            f64::NAN
+        } else {
+            value
+        };
+
+        MapKey::Number(value.to_bits())
+    }
+}
+
+/// Hash map that remembers insertion order, so `keys()` and printing are
+/// deterministic. Overwriting a key keeps its position.
+#[derive(Clone, Debug)]
+pub struct Map<K, V> {
+    indices: HashMap<K, usize>,
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self {
+            indices: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> Map<K, V> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.indices.get(key).map(|index| &self.entries[*index].1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.indices.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        match self.indices.get(&key) {
+            Some(index) => self.entries[*index].1 = value,
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.indices.remove(key)?;
+        let (_, value) = self.entries.remove(index);
+
+        for (key, _) in &self.entries[index..] {
+            if let Some(shifted) = self.indices.get_mut(key) {
+                *shifted -= 1;
+            }
+        }
+
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
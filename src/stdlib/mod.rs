@@ -0,0 +1,346 @@
+//! Native functions every Lox program can call, shared by both backends.
+//!
+//! | function               | returns                                                  |
+//! |------------------------|----------------------------------------------------------|
+//! | `clock()`              | seconds since the Unix epoch                             |
+//! | `len(string)`          | number of characters, or elements of a list or map       |
+//! | `str(value)`           | the value as `print` would show it                       |
+//! | `num(value)`           | a number parsed from a string, or `nil` if it isn't one  |
+//! | `type(value)`          | `"nil"`, `"boolean"`, `"number"`, `"string"`, `"function"`, `"class"`, `"instance"`, `"list"` or `"map"` |
+//! | `substr(string, start, length)` | `length` characters starting at index `start`   |
+//! | `upper(string)`, `lower(string)` | the string in upper or lower case              |
+//! | `floor(x)`, `ceil(x)`, `sqrt(x)`, `abs(x)` | the usual rounding and math          |
+//! | `min(a, b)`, `max(a, b)`, `pow(base, exponent)` | the usual math                  |
+//! | `random()`             | a number in `[0, 1)`, reproducible after [`Stdlib::seed`] |
+//! | `input()`              | the next line of stdin without its newline, `nil` at EOF |
+//!
+//! Each backend adds the list natives on top: `push(list, value)`,
+//! `pop(list)`, `insert(list, index, value)`, `remove(list, index)` and
+//! `slice(list, start, end)`. They share the bounds checks below.
+//! Maps get `keys(map)` and `values(map)` as lists in insertion order,
+//! `has(map, key)` and `delete(map, key)`, which returns the removed value or
+//! `nil`. Both backends key their maps with [`map::MapKey`].
+//!
+//! Calling one with the wrong number of arguments is the usual
+//! "Expected N arguments but got M." runtime error. Arguments of the wrong type
+//! raise "`name`() expects ..." naming what it accepts, e.g.
+//! "sqrt() expects a number.", and `substr` outside the string raises
+//! "substr() range is out of bounds.".
+
+use std::{
+    cell::{Cell, RefCell},
+    io::BufRead,
+    ops::Range,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::interpreter::value::format_number;
+
+pub mod map;
+mod tests;
+
+/// Backend-independent view of a native argument or result. Values natives
+/// cannot inspect are passed as their type name and printed form.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NativeValue {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(Rc<str>),
+    Object {
+        type_name: &'static str,
+        display: String,
+    },
+}
+
+pub type StdFn = fn(&Stdlib, &[NativeValue]) -> Result<NativeValue, String>;
+
+/// Name, arity and implementation of every standard library function.
+pub const FUNCTIONS: &[(&str, usize, StdFn)] = &[
+    ("clock", 0, clock),
+    ("len", 1, len),
+    ("str", 1, str),
+    ("num", 1, num),
+    ("type", 1, type_of),
+    ("substr", 3, substr),
+    ("upper", 1, upper),
+    ("lower", 1, lower),
+    ("floor", 1, floor),
+    ("ceil", 1, ceil),
+    ("sqrt", 1, sqrt),
+    ("abs", 1, abs),
+    ("min", 2, min),
+    ("max", 2, max),
+    ("pow", 2, pow),
+    ("random", 0, random),
+    ("input", 0, input),
+];
+
+/// State behind the standard library of one interpreter: the random number
+/// generator and where `input()` reads from.
+pub struct Stdlib {
+    rng: Cell<u64>,
+    input: RefCell<Option<Box<dyn BufRead>>>,
+}
+
+impl Default for Stdlib {
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_nanos() as u64)
+            .unwrap_or_default();
+
+        let stdlib = Self {
+            rng: Cell::new(0),
+            input: RefCell::new(None),
+        };
+        stdlib.seed(now);
+
+        stdlib
+    }
+}
+
+impl Stdlib {
+    /// Restarts `random()` at a fixed point of its sequence.
+    pub fn seed(&self, seed: u64) {
+        // splitmix64 spreads nearby seeds apart and never yields the zero state.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        self.rng.set((z ^ (z >> 31)) | 1);
+    }
+
+    /// Makes `input()` read from `input` instead of stdin.
+    pub fn set_input(&self, input: impl BufRead + 'static) {
+        *self.input.borrow_mut() = Some(Box::new(input));
+    }
+
+    /// xorshift64*, scaled to `[0, 1)`.
+    fn next_random(&self) -> f64 {
+        let mut x = self.rng.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng.set(x);
+
+        (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn read_line(&self) -> std::io::Result<Option<String>> {
+        let mut line = String::new();
+        let read = match self.input.borrow_mut().as_mut() {
+            Some(input) => input.read_line(&mut line)?,
+            None => std::io::stdin().read_line(&mut line)?,
+        };
+
+        if read == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(trimmed);
+
+        Ok(Some(line))
+    }
+}
+
+impl NativeValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            NativeValue::Nil => "nil",
+            NativeValue::Bool(_) => "boolean",
+            NativeValue::Number(_) => "number",
+            NativeValue::String(_) => "string",
+            NativeValue::Object { type_name, .. } => type_name,
+        }
+    }
+}
+
+impl std::fmt::Display for NativeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NativeValue::Nil => write!(f, "nil"),
+            NativeValue::Bool(value) => write!(f, "{value}"),
+            NativeValue::Number(value) => write!(f, "{}", format_number(*value)),
+            NativeValue::String(value) => write!(f, "{value}"),
+            NativeValue::Object { display, .. } => write!(f, "{display}"),
+        }
+    }
+}
+
+/// Position of `index` in a list of `len` elements. With `end` the position
+/// just past the last element is valid too, as for `insert`.
+pub fn list_index(index: f64, len: usize, end: bool) -> Result<usize, String> {
+    if index.fract() != 0.0 {
+        return Err("List index must be an integer.".to_string());
+    }
+
+    let limit = if end { len + 1 } else { len };
+    match index >= 0.0 && index < limit as f64 {
+        true => Ok(index as usize),
+        false => Err(format!(
+            "List index {} is out of bounds for length {len}.",
+            format_number(index)
+        )),
+    }
+}
+
+/// Elements `start` up to but excluding `end` of a list of `len` elements.
+pub fn list_slice(start: f64, end: f64, len: usize) -> Result<Range<usize>, String> {
+    let start = list_index(start, len, true)?;
+    let end = list_index(end, len, true)?;
+
+    match start <= end {
+        true => Ok(start..end),
+        false => Err("slice() start is after its end.".to_string()),
+    }
+}
+
+fn string(value: String) -> NativeValue {
+    NativeValue::String(Rc::from(value))
+}
+
+fn expect_string<'a>(name: &str, value: &'a NativeValue) -> Result<&'a str, String> {
+    match value {
+        NativeValue::String(string) => Ok(string),
+        _ => Err(format!("{name}() expects a string.")),
+    }
+}
+
+fn expect_numbers<const N: usize>(
+    name: &str,
+    arguments: &[NativeValue],
+) -> Result<[f64; N], String> {
+    let mut numbers = [0.0; N];
+
+    for (number, argument) in numbers.iter_mut().zip(arguments) {
+        match argument {
+            NativeValue::Number(value) => *number = *value,
+            _ if N == 1 => return Err(format!("{name}() expects a number.")),
+            _ => return Err(format!("{name}() expects numbers.")),
+        }
+    }
+
+    Ok(numbers)
+}
+
+fn math(name: &str, arguments: &[NativeValue], f: fn(f64) -> f64) -> Result<NativeValue, String> {
+    let [x] = expect_numbers(name, arguments)?;
+
+    Ok(NativeValue::Number(f(x)))
+}
+
+fn clock(_: &Stdlib, _: &[NativeValue]) -> Result<NativeValue, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| err.to_string())?;
+
+    Ok(NativeValue::Number(now.as_secs_f64()))
+}
+
+fn len(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    let string = expect_string("len", &arguments[0])
+        .map_err(|_| "len() expects a string, list or map.".to_string())?;
+
+    Ok(NativeValue::Number(string.chars().count() as f64))
+}
+
+fn str(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    Ok(string(arguments[0].to_string()))
+}
+
+fn num(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    match &arguments[0] {
+        NativeValue::Number(value) => Ok(NativeValue::Number(*value)),
+        NativeValue::String(string) => Ok(string
+            .trim()
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+            .map_or(NativeValue::Nil, NativeValue::Number)),
+        _ => Err("num() expects a string or number.".to_string()),
+    }
+}
+
+fn type_of(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    Ok(NativeValue::String(Rc::from(arguments[0].type_name())))
+}
+
+fn substr(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    let expects = "substr() expects a string, a start index and a length.";
+    let text = expect_string("substr", &arguments[0]).map_err(|_| expects.to_string())?;
+    let [start, length] =
+        expect_numbers::<2>("substr", &arguments[1..]).map_err(|_| expects.to_string())?;
+
+    let count = text.chars().count() as f64;
+    let integral = |value: f64| value.fract() == 0.0 && value >= 0.0;
+    if !integral(start) || !integral(length) || start + length > count {
+        return Err("substr() range is out of bounds.".to_string());
+    }
+
+    Ok(string(
+        text.chars()
+            .skip(start as usize)
+            .take(length as usize)
+            .collect(),
+    ))
+}
+
+fn upper(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    Ok(string(
+        expect_string("upper", &arguments[0])?.to_uppercase(),
+    ))
+}
+
+fn lower(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    Ok(string(
+        expect_string("lower", &arguments[0])?.to_lowercase(),
+    ))
+}
+
+fn floor(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    math("floor", arguments, f64::floor)
+}
+
+fn ceil(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    math("ceil", arguments, f64::ceil)
+}
+
+fn sqrt(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    math("sqrt", arguments, f64::sqrt)
+}
+
+fn abs(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    math("abs", arguments, f64::abs)
+}
+
+fn min(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    let [a, b] = expect_numbers("min", arguments)?;
+
+    Ok(NativeValue::Number(a.min(b)))
+}
+
+fn max(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    let [a, b] = expect_numbers("max", arguments)?;
+
+    Ok(NativeValue::Number(a.max(b)))
+}
+
+fn pow(_: &Stdlib, arguments: &[NativeValue]) -> Result<NativeValue, String> {
+    let [base, exponent] = expect_numbers("pow", arguments)?;
+
+    Ok(NativeValue::Number(base.powf(exponent)))
+}
+
+fn random(stdlib: &Stdlib, _: &[NativeValue]) -> Result<NativeValue, String> {
+    Ok(NativeValue::Number(stdlib.next_random()))
+}
+
+fn input(stdlib: &Stdlib, _: &[NativeValue]) -> Result<NativeValue, String> {
+    match stdlib.read_line() {
+        Ok(Some(line)) => Ok(string(line)),
+        Ok(None) => Ok(NativeValue::Nil),
+        Err(err) => Err(format!("input() failed: {err}.")),
+    }
+}
@@ -0,0 +1,192 @@
+#[allow(unused_imports)]
+use crate::{
+    interpreter::{Interpreter, Program},
+    parser::Parser,
+    stdlib::Stdlib,
+    tokenizer::Tokenizer,
+    vm::{compiler::Compiler, Vm},
+};
+
+fn _program(source: &str) -> std::rc::Rc<Program> {
+    let output = Tokenizer::tokenize_with(source.to_string(), true).unwrap();
+
+    Parser::parse_program_with(output.get_tokens(), true)
+        .and_then(Program::new)
+        .unwrap()
+}
+
+/// Runs `source` on both backends after `configure` sets up their stdlib,
+/// asserting they agree, and returns the output and error.
+fn _run(source: &str, configure: impl Fn(&Stdlib)) -> (String, Option<String>) {
+    let program = _program(source);
+
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    configure(interpreter.stdlib());
+    let error = interpreter.interpret(&program).err();
+    let tree = (
+        String::from_utf8(interpreter.output().clone()).unwrap(),
+        error.map(|err| err.to_string()),
+    );
+
+    let mut vm = Vm::with_output(Vec::new());
+    configure(vm.stdlib());
+    let error = vm
+        .interpret(Compiler::compile(program.ast()).unwrap())
+        .err();
+    let vm = (
+        String::from_utf8(vm.output().clone()).unwrap(),
+        error.map(|err| err.to_string()),
+    );
+
+    assert_eq!(tree, vm, "backends disagree on {source:?}");
+    tree
+}
+
+fn _error(source: &str) -> String {
+    _run(source, |_| {}).1.unwrap()
+}
+
+#[test]
+fn seeded_random_is_reproducible() {
+    let source = "for (var i = 0; i < 5; i = i + 1) print random();";
+
+    let (first, _) = _run(source, |stdlib| stdlib.seed(42));
+    let (second, _) = _run(source, |stdlib| stdlib.seed(42));
+    let (other, _) = _run(source, |stdlib| stdlib.seed(43));
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+    assert_eq!(
+        first
+            .lines()
+            .collect::<std::collections::HashSet<_>>()
+            .len(),
+        5
+    );
+}
+
+#[test]
+fn input_reads_lines_until_eof() {
+    let source = "print input(); print input(); print input();";
+    let (output, error) = _run(source, |stdlib| {
+        stdlib.set_input(std::io::Cursor::new("first\r\nsecond\n"))
+    });
+
+    assert_eq!(output, "first\nsecond\nnil\n");
+    assert_eq!(error, None);
+}
+
+#[test]
+fn wrong_argument_types_are_runtime_errors() {
+    assert_eq!(
+        _error("len(1);"),
+        "len() expects a string, list or map.\n[line 1]"
+    );
+    assert_eq!(_error("upper(nil);"), "upper() expects a string.\n[line 1]");
+    assert_eq!(_error("\nabs(\"x\");"), "abs() expects a number.\n[line 2]");
+    assert_eq!(_error("max(1, true);"), "max() expects numbers.\n[line 1]");
+    assert_eq!(
+        _error("num(clock);"),
+        "num() expects a string or number.\n[line 1]"
+    );
+    assert_eq!(
+        _error("substr(\"abc\", \"1\", 1);"),
+        "substr() expects a string, a start index and a length.\n[line 1]"
+    );
+    assert_eq!(
+        _error("substr(\"abc\", 2, 2);"),
+        "substr() range is out of bounds.\n[line 1]"
+    );
+    assert_eq!(
+        _error("substr(\"abc\", 0.5, 1);"),
+        "substr() range is out of bounds.\n[line 1]"
+    );
+}
+
+#[test]
+fn arity_is_checked() {
+    assert_eq!(
+        _error("len();"),
+        "Expected 1 arguments but got 0.\n[line 1]"
+    );
+    assert_eq!(
+        _error("pow(1);"),
+        "Expected 2 arguments but got 1.\n[line 1]"
+    );
+    assert_eq!(
+        _error("random(1);"),
+        "Expected 0 arguments but got 1.\n[line 1]"
+    );
+}
+
+#[test]
+fn strings_are_counted_in_characters() {
+    let (output, _) = _run(
+        "print len(\"héllo\"); print substr(\"héllo\", 1, 2);",
+        |_| {},
+    );
+
+    assert_eq!(output, "5\nél\n");
+}
+
+#[test]
+fn list_natives_check_bounds() {
+    assert_eq!(
+        _error("pop([]);"),
+        "Can't pop from an empty list.\n[line 1]"
+    );
+    assert_eq!(
+        _error("insert([1], 2, 0);"),
+        "List index 2 is out of bounds for length 1.\n[line 1]"
+    );
+    assert_eq!(
+        _error("remove([1], -1);"),
+        "List index -1 is out of bounds for length 1.\n[line 1]"
+    );
+    assert_eq!(
+        _error("[1][0.5];"),
+        "List index must be an integer.\n[line 1]"
+    );
+    assert_eq!(
+        _error("slice([1, 2], 2, 1);"),
+        "slice() start is after its end.\n[line 1]"
+    );
+    assert_eq!(
+        _error("push(\"a\", 1);"),
+        "push() expects a list.\n[line 1]"
+    );
+    assert_eq!(
+        _error("\"a\"[0];"),
+        "Can only index lists and maps.\n[line 1]"
+    );
+}
+
+#[test]
+fn map_natives_check_keys() {
+    assert_eq!(
+        _error("var m = {[]: 1};"),
+        "Map keys must be numbers, strings, booleans or nil.\n[line 1]"
+    );
+    assert_eq!(
+        _error("var m = {};\nm[m] = 1;"),
+        "Map keys must be numbers, strings, booleans or nil.\n[line 2]"
+    );
+    assert_eq!(_error("print {}[1];"), "Undefined key 1.\n[line 1]");
+    assert_eq!(_error("has([], 1);"), "has() expects a map.\n[line 1]");
+    assert_eq!(_error("keys(\"a\");"), "keys() expects a map.\n[line 1]");
+}
+
+#[test]
+fn maps_keep_insertion_order() {
+    let (output, _) = _run(
+        "var m = {\"x\": 1, \"y\": 2, \"z\": 3};\n\
+         delete(m, \"x\");\n\
+         m[\"x\"] = 4;\n\
+         m[\"y\"] = 5;\n\
+         print keys(m);\n\
+         print m[\"z\"];",
+        |_| {},
+    );
+
+    assert_eq!(output, "[\"y\", \"z\", \"x\"]\n3\n");
+}
@@ -1,37 +1,66 @@
 use std::io::{Error, ErrorKind};
 
+pub use span::Span;
 pub use token::Token;
 pub use token_type::TokenType;
 
+pub mod span;
 pub mod token;
 pub mod token_type;
 
 mod tokenize;
 
+use tokenize::OpenState;
+
 pub struct Tokenizer {}
 
 impl Tokenizer {
     pub fn tokenize(file_contents: String) -> Result<TokenizerOutput, std::io::Error> {
+        Tokenizer::tokenize_with(file_contents, false)
+    }
+
+    /// Tokenizes with the syntax extensions (e.g. list literals) enabled when
+    /// `extensions` is set. Standard Lox treats their characters as unexpected.
+    pub fn tokenize_with(
+        file_contents: String,
+        extensions: bool,
+    ) -> Result<TokenizerOutput, std::io::Error> {
         let mut tokens: Vec<Token> = Vec::new();
         let mut errors: Vec<TokenizerError> = Vec::new();
 
+        let mut eof = Span::new(1, 1, 0);
+        let mut open = OpenState::default();
+
         if !file_contents.is_empty() {
             for (index, line) in file_contents.lines().enumerate() {
-                Tokenizer::tokenize_line(&mut tokens, &mut errors, index, line);
+                Tokenizer::tokenize_line(
+                    &mut tokens,
+                    &mut errors,
+                    index,
+                    line,
+                    &mut open,
+                    extensions,
+                );
+                eof = Span::new(index + 1, line.chars().count() + 1, 0);
             }
         }
 
-        tokens.push(Token::new_eof());
+        // Strings may span lines; one still open at the end of input is unterminated.
+        if open.is_unterminated() {
+            errors.push(TokenizerError::unterminated_string(eof.line));
+        }
+
+        tokens.push(Token::new_eof(eof));
 
         Ok(TokenizerOutput { tokens, errors })
     }
 
-    pub fn serialize(tokens: &Vec<Token>, errors: &Vec<TokenizerError>) -> Result<(), Error> {
+    pub fn serialize(tokens: &[Token], errors: &[TokenizerError]) -> Result<(), Error> {
         errors.iter().for_each(|err| err.print());
         tokens.iter().for_each(|t| {
             if !matches!(
                 t.get_type(),
-                TokenType::Whitespace | TokenType::Tab | TokenType::Unkonwn
+                TokenType::Whitespace | TokenType::Tab | TokenType::Unkonwn | TokenType::Comment
             ) {
                 t.print()
             }
@@ -59,7 +88,7 @@ impl TokenizerOutput {
         |t: &&Token| {
             !matches!(
                 t.get_type(),
-                TokenType::Whitespace | TokenType::Tab | TokenType::Unkonwn
+                TokenType::Whitespace | TokenType::Tab | TokenType::Unkonwn | TokenType::Comment
             )
         }
     }
@@ -69,6 +98,7 @@ impl TokenizerOutput {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct TokenizerError {
     line: usize,
     error_string: String,
@@ -92,7 +122,21 @@ impl TokenizerError {
         Self::new("Unterminated string.", line)
     }
 
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.error_string
+    }
+
     pub(crate) fn print(&self) {
-        eprintln!("[line {}] Error: {}", self.line, self.error_string);
+        eprintln!("{self}");
+    }
+}
+
+impl std::fmt::Display for TokenizerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] Error: {}", self.line, self.error_string)
     }
 }
@@ -0,0 +1,50 @@
+/// A region of source code. Lines and columns are 1-based, `end_column` is exclusive.
+/// The default span (line 0) marks a node without a source location.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Self {
+        Self {
+            line,
+            column,
+            end_line: line,
+            end_column: column + length,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.line == 0
+    }
+
+    pub fn merge(self, other: Span) -> Span {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+
+        let (line, column) = (self.line, self.column).min((other.line, other.column));
+        let (end_line, end_column) =
+            (self.end_line, self.end_column).max((other.end_line, other.end_column));
+
+        Span {
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
@@ -1,11 +1,11 @@
-use super::token_type::TokenType;
+use super::{span::Span, token_type::TokenType};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Token {
     token_type: TokenType,
     string: String,
     literal: String,
-    line: usize,
+    span: Span,
 }
 
 impl Token {
@@ -17,68 +17,83 @@ impl Token {
         self.literal.to_string()
     }
 
+    pub fn get_lexeme(&self) -> &str {
+        &self.string
+    }
+
     pub fn get_line(&self) -> &usize {
-        &self.line
+        &self.span.line
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
     }
 
-    pub(super) fn new(token_type: TokenType, string: &str, literal: &str, line: usize) -> Self {
+    pub(super) fn new(token_type: TokenType, string: &str, literal: &str, span: Span) -> Self {
         Self {
             token_type,
             string: string.to_string(),
             literal: literal.to_string(),
-            line,
+            span,
         }
     }
 
-    pub(super) fn new_punctuator(token_type: TokenType, line: usize) -> Self {
+    pub(super) fn new_punctuator(token_type: TokenType, span: Span) -> Self {
         let string = token_type.get_lexeme();
 
         Self {
             token_type,
             string,
             literal: "null".to_string(),
-            line,
+            span,
         }
     }
 
-    pub(super) fn new_identifier(string: &str, line: usize) -> Self {
+    pub(super) fn new_identifier(string: &str, span: Span) -> Self {
         Self {
             token_type: TokenType::Identifier,
             string: string.to_string(),
             literal: "null".to_string(),
-            line,
+            span,
         }
     }
 
-    pub(super) fn new_reserved(token_type: TokenType, line: usize) -> Self {
+    pub(super) fn new_reserved(token_type: TokenType, span: Span) -> Self {
         Self {
             string: token_type.get_lexeme(),
             token_type,
             literal: "null".to_string(),
-            line,
+            span,
         }
     }
 
-    pub(super) fn new_eof() -> Self {
+    pub(super) fn new_eof(span: Span) -> Self {
         Self {
             token_type: TokenType::EOF,
             string: "".to_string(),
             literal: "null".to_string(),
-            line: 0,
+            span,
         }
     }
 
-    pub(super) fn new_unknown(line: usize) -> Self {
+    pub(super) fn new_unknown(span: Span) -> Self {
         Self {
             token_type: TokenType::Unkonwn,
             string: "".to_string(),
             literal: "".to_string(),
-            line,
+            span,
         }
     }
 
     pub(super) fn print(&self) {
-        println!(
+        println!("{self}")
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
             "{} {} {}",
             self.token_type.get_type_string(),
             self.string,
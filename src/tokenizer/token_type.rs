@@ -1,5 +1,5 @@
 #[allow(dead_code)]
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum TokenType {
     // Single-character tokens.
     LeftParenthesis,
@@ -13,6 +13,15 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    LeftBracket,
+    RightBracket,
+    Colon,
+    Question,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
 
     // One or two character tokens.
     Bang,
@@ -23,11 +32,23 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    StarStar,
+    LessLess,
+    GreaterGreater,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
 
     // Literals.
     Identifier,
     String,
     Number,
+    /// The part of a string before a `${`; the embedded expression follows.
+    /// Only produced with extensions, otherwise `${` is plain string text.
+    Interpolation,
 
     // Keywords.
     And,
@@ -46,6 +67,16 @@ pub enum TokenType {
     True,
     Var,
     While,
+    Break,
+    Continue,
+    In,
+    Import,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+
+    Comment,
 
     EOF,
     None,
@@ -57,8 +88,34 @@ pub enum TokenType {
 }
 
 impl TokenType {
-    pub(crate) fn from_one(ch: &char) -> Self {
-        match ch {
+    /// Tokens only produced when extensions are enabled, so standard Lox
+    /// tokenizes as before. `:` is standard for `?:`, so the parser rejects map
+    /// literals on its own.
+    pub fn is_extension(&self) -> bool {
+        matches!(
+            self,
+            Self::LeftBracket
+                | Self::RightBracket
+                | Self::In
+                | Self::Percent
+                | Self::Ampersand
+                | Self::Pipe
+                | Self::Caret
+                | Self::Tilde
+                | Self::StarStar
+                | Self::LessLess
+                | Self::GreaterGreater
+                | Self::PlusEqual
+                | Self::MinusEqual
+                | Self::StarEqual
+                | Self::SlashEqual
+                | Self::PlusPlus
+                | Self::MinusMinus
+        )
+    }
+
+    pub(crate) fn from_one(ch: &char, extensions: bool) -> Self {
+        let token = match ch {
             '(' => Self::LeftParenthesis,
             ')' => Self::RightParenthesis,
             '{' => Self::LeftCurly,
@@ -76,12 +133,51 @@ impl TokenType {
             '!' => Self::Bang,
             ' ' => Self::Whitespace,
             '\u{0009}' => Self::Tab,
+            '[' => Self::LeftBracket,
+            ']' => Self::RightBracket,
+            ':' => Self::Colon,
+            '?' => Self::Question,
+            '%' => Self::Percent,
+            '&' => Self::Ampersand,
+            '|' => Self::Pipe,
+            '^' => Self::Caret,
+            '~' => Self::Tilde,
             _ => Self::None,
+        };
+
+        match token.is_extension() && !extensions {
+            true => Self::None,
+            false => token,
         }
     }
 
-    pub(crate) fn from_string(string: &str) -> Self {
-        match string {
+    /// The two-character token formed by `previous` directly followed by `ch`.
+    pub(crate) fn join(previous: &TokenType, ch: char, extensions: bool) -> Option<Self> {
+        let token = match (previous, ch) {
+            (Self::Bang, '=') => Some(Self::BangEqual),
+            (Self::Equal, '=') => Some(Self::EqualEqual),
+            (Self::Greater, '=') => Some(Self::GreaterEqual),
+            (Self::Less, '=') => Some(Self::LessEqual),
+            (Self::Star, '*') => Some(Self::StarStar),
+            (Self::Less, '<') => Some(Self::LessLess),
+            (Self::Greater, '>') => Some(Self::GreaterGreater),
+            (Self::Plus, '=') => Some(Self::PlusEqual),
+            (Self::Minus, '=') => Some(Self::MinusEqual),
+            (Self::Star, '=') => Some(Self::StarEqual),
+            (Self::Slash, '=') => Some(Self::SlashEqual),
+            (Self::Plus, '+') => Some(Self::PlusPlus),
+            (Self::Minus, '-') => Some(Self::MinusMinus),
+            _ => None,
+        }?;
+
+        match token.is_extension() && !extensions {
+            true => None,
+            false => Some(token),
+        }
+    }
+
+    pub(crate) fn from_string(string: &str, extensions: bool) -> Self {
+        let token = match string {
             "and" => Self::And,
             "class" => Self::Class,
             "else" => Self::Else,
@@ -98,7 +194,20 @@ impl TokenType {
             "true" => Self::True,
             "var" => Self::Var,
             "while" => Self::While,
+            "break" => Self::Break,
+            "continue" => Self::Continue,
+            "in" => Self::In,
+            "import" => Self::Import,
+            "throw" => Self::Throw,
+            "try" => Self::Try,
+            "catch" => Self::Catch,
+            "finally" => Self::Finally,
             _ => Self::String,
+        };
+
+        match token.is_extension() && !extensions {
+            true => Self::String,
+            false => token,
         }
     }
 
@@ -115,6 +224,15 @@ impl TokenType {
             Self::Semicolon => "SEMICOLON",
             Self::Slash => "SLASH",
             Self::Star => "STAR",
+            Self::LeftBracket => "LEFT_BRACKET",
+            Self::RightBracket => "RIGHT_BRACKET",
+            Self::Colon => "COLON",
+            Self::Question => "QUESTION",
+            Self::Percent => "PERCENT",
+            Self::Ampersand => "AMPERSAND",
+            Self::Pipe => "PIPE",
+            Self::Caret => "CARET",
+            Self::Tilde => "TILDE",
             Self::Bang => "BANG",
             Self::BangEqual => "BANG_EQUAL",
             Self::Equal => "EQUAL",
@@ -123,9 +241,19 @@ impl TokenType {
             Self::GreaterEqual => "GREATER_EQUAL",
             Self::Less => "LESS",
             Self::LessEqual => "LESS_EQUAL",
+            Self::StarStar => "STAR_STAR",
+            Self::LessLess => "LESS_LESS",
+            Self::GreaterGreater => "GREATER_GREATER",
+            Self::PlusEqual => "PLUS_EQUAL",
+            Self::MinusEqual => "MINUS_EQUAL",
+            Self::StarEqual => "STAR_EQUAL",
+            Self::SlashEqual => "SLASH_EQUAL",
+            Self::PlusPlus => "PLUS_PLUS",
+            Self::MinusMinus => "MINUS_MINUS",
             Self::Identifier => "IDENTIFIER",
             Self::String => "STRING",
             Self::Number => "NUMBER",
+            Self::Interpolation => "INTERPOLATION",
             Self::And => "AND",
             Self::Class => "CLASS",
             Self::Else => "ELSE",
@@ -142,6 +270,14 @@ impl TokenType {
             Self::True => "TRUE",
             Self::Var => "VAR",
             Self::While => "WHILE",
+            Self::Break => "BREAK",
+            Self::Continue => "CONTINUE",
+            Self::In => "IN",
+            Self::Import => "IMPORT",
+            Self::Throw => "THROW",
+            Self::Try => "TRY",
+            Self::Catch => "CATCH",
+            Self::Finally => "FINALLY",
             Self::EOF => "EOF",
             _ => "",
         })
@@ -161,6 +297,15 @@ impl TokenType {
             Self::Semicolon => ";",
             Self::Slash => "/",
             Self::Star => "*",
+            Self::LeftBracket => "[",
+            Self::RightBracket => "]",
+            Self::Colon => ":",
+            Self::Question => "?",
+            Self::Percent => "%",
+            Self::Ampersand => "&",
+            Self::Pipe => "|",
+            Self::Caret => "^",
+            Self::Tilde => "~",
             Self::Bang => "!",
             Self::BangEqual => "!=",
             Self::Equal => "=",
@@ -169,6 +314,15 @@ impl TokenType {
             Self::GreaterEqual => ">=",
             Self::Less => "<",
             Self::LessEqual => "<=",
+            Self::StarStar => "**",
+            Self::LessLess => "<<",
+            Self::GreaterGreater => ">>",
+            Self::PlusEqual => "+=",
+            Self::MinusEqual => "-=",
+            Self::StarEqual => "*=",
+            Self::SlashEqual => "/=",
+            Self::PlusPlus => "++",
+            Self::MinusMinus => "--",
             Self::And => "and",
             Self::Class => "class",
             Self::Else => "else",
@@ -185,6 +339,14 @@ impl TokenType {
             Self::True => "true",
             Self::Var => "var",
             Self::While => "while",
+            Self::Break => "break",
+            Self::Continue => "continue",
+            Self::In => "in",
+            Self::Import => "import",
+            Self::Throw => "throw",
+            Self::Try => "try",
+            Self::Catch => "catch",
+            Self::Finally => "finally",
             _ => "",
         })
         .to_string()
@@ -1,4 +1,4 @@
-use super::{Token, TokenType, Tokenizer, TokenizerError};
+use super::{span::Span, Token, TokenType, Tokenizer, TokenizerError};
 
 #[derive(PartialEq)]
 enum TokenizerMode {
@@ -8,21 +8,54 @@ enum TokenizerMode {
     None,
 }
 
+/// What a line leaves open for the next one.
+#[derive(Default)]
+pub(super) struct OpenState {
+    /// Start and text so far of a string spanning lines, and whether it
+    /// resumed after an interpolated expression rather than at a quote.
+    string: Option<(Span, String, bool)>,
+    /// Unmatched `{` inside each enclosing `${ ... }`, innermost last.
+    interpolations: Vec<usize>,
+}
+
+impl OpenState {
+    pub(super) fn is_unterminated(&self) -> bool {
+        self.string.is_some() || !self.interpolations.is_empty()
+    }
+}
+
 impl Tokenizer {
     pub(super) fn tokenize_line(
         tokens: &mut Vec<Token>,
         errors: &mut Vec<TokenizerError>,
         index: usize,
         line: &str,
+        open: &mut OpenState,
+        extensions: bool,
     ) {
+        let line_number = index + 1;
+        let span = |start: usize, end: usize| Span::new(line_number, start + 1, end - start);
+
         let mut iter = line.chars().enumerate().peekable();
         let mut buffer = String::new();
+        let mut start = 0;
         let mut mode = TokenizerMode::None;
+        let mut string_start = None;
+        let mut resumed = false;
 
-        while let Some((_, ch)) = iter.peek() {
+        if let Some((opened, text, was_resumed)) = open.string.take() {
+            buffer = text;
+            buffer.push('\n');
+            string_start = Some(opened);
+            resumed = was_resumed;
+            mode = TokenizerMode::String;
+        }
+
+        while let Some(&(column, ch)) = iter.peek() {
             match mode {
                 TokenizerMode::None => {
-                    let (_, ch) = iter.next().unwrap();
+                    iter.next();
+                    start = column;
 
                     mode = match ch {
                         '"' => TokenizerMode::String,
@@ -36,74 +69,128 @@ impl Tokenizer {
                         }
                         '/' => {
                             if matches!(iter.peek(), Some((_, '/'))) {
+                                let comment: String = line.chars().skip(column).collect();
+                                let end = column + comment.chars().count();
+
+                                tokens.push(Token::new(
+                                    TokenType::Comment,
+                                    comment.trim_end(),
+                                    "null",
+                                    span(column, end),
+                                ));
                                 return;
                             } else {
-                                tokens.push(Token::new_punctuator(TokenType::Slash, index + 1));
+                                tokens.push(Token::new_punctuator(
+                                    TokenType::Slash,
+                                    span(column, column + 1),
+                                ));
                                 TokenizerMode::None
                             }
                         }
-                        '=' if tokens.len() >= 1 => {
+                        '=' | '*' | '<' | '>' | '+' | '-' if !tokens.is_empty() => {
                             let token = tokens.pop().unwrap();
+                            let adjacent = token.get_span().end_line == line_number
+                                && token.get_span().end_column == column + 1;
+                            let joined = token.get_span().merge(span(column, column + 1));
 
-                            match token.get_type() {
-                                &TokenType::Bang => {
-                                    tokens.push(Token::new_punctuator(
-                                        TokenType::BangEqual,
-                                        index + 1,
-                                    ));
-                                }
-                                &TokenType::Equal => {
-                                    tokens.push(Token::new_punctuator(
-                                        TokenType::EqualEqual,
-                                        index + 1,
-                                    ));
-                                }
-                                &TokenType::Greater => {
-                                    tokens.push(Token::new_punctuator(
-                                        TokenType::GreaterEqual,
-                                        index + 1,
-                                    ));
-                                }
-                                &TokenType::Less => {
-                                    tokens.push(Token::new_punctuator(
-                                        TokenType::LessEqual,
-                                        index + 1,
-                                    ));
+                            match TokenType::join(token.get_type(), ch, extensions) {
+                                Some(two) if adjacent => {
+                                    tokens.push(Token::new_punctuator(two, joined));
                                 }
                                 _ => {
                                     tokens.push(token);
-                                    tokens.push(Token::new_punctuator(TokenType::Equal, index + 1));
+                                    tokens.push(Token::new_punctuator(
+                                        TokenType::from_one(&ch, extensions),
+                                        span(column, column + 1),
+                                    ));
                                 }
                             }
 
                             TokenizerMode::None
                         }
-                        _ => match TokenType::from_one(&ch) {
+                        '{' if !open.interpolations.is_empty() => {
+                            if let Some(depth) = open.interpolations.last_mut() {
+                                *depth += 1;
+                            }
+                            tokens.push(Token::new_punctuator(
+                                TokenType::LeftCurly,
+                                span(column, column + 1),
+                            ));
+                            TokenizerMode::None
+                        }
+                        // The `}` closing an interpolated expression resumes its string.
+                        '}' if open.interpolations.last() == Some(&0) => {
+                            open.interpolations.pop();
+                            tokens.push(Token::new_punctuator(
+                                TokenType::RightCurly,
+                                span(column, column + 1),
+                            ));
+                            start = column + 1;
+                            resumed = true;
+                            TokenizerMode::String
+                        }
+                        '}' if !open.interpolations.is_empty() => {
+                            if let Some(depth) = open.interpolations.last_mut() {
+                                *depth -= 1;
+                            }
+                            tokens.push(Token::new_punctuator(
+                                TokenType::RightCurly,
+                                span(column, column + 1),
+                            ));
+                            TokenizerMode::None
+                        }
+                        _ => match TokenType::from_one(&ch, extensions) {
                             TokenType::None => {
-                                tokens.push(Token::new_unknown(index + 1));
-                                errors.push(TokenizerError::unexpected_char(ch, index + 1));
+                                tokens.push(Token::new_unknown(span(column, column + 1)));
+                                errors.push(TokenizerError::unexpected_char(ch, line_number));
                                 TokenizerMode::None
                             }
                             token => {
-                                tokens.push(Token::new_punctuator(token, index + 1));
+                                tokens.push(Token::new_punctuator(token, span(column, column + 1)));
                                 TokenizerMode::None
                             }
                         },
                     };
                 }
                 TokenizerMode::String => {
-                    let (_, ch) = iter.next().unwrap();
+                    iter.next();
+                    let quote = if resumed { "" } else { "\"" };
 
                     match ch {
                         '"' => {
+                            let string_span = match string_start.take() {
+                                Some(opened) => opened.merge(span(column, column + 1)),
+                                None => span(start, column + 1),
+                            };
+
                             tokens.push(Token::new(
                                 TokenType::String,
-                                format!("\"{}\"", buffer).as_str(),
+                                format!("{quote}{buffer}\"").as_str(),
                                 &buffer,
-                                index + 1,
+                                string_span,
                             ));
 
                             buffer.clear();
+                            resumed = false;
+                            mode = TokenizerMode::None;
+                        }
+                        '$' if extensions && matches!(iter.peek(), Some((_, '{'))) => {
+                            iter.next();
+                            let part_span = match string_start.take() {
+                                Some(opened) => opened.merge(span(column, column + 2)),
+                                None => span(start, column + 2),
+                            };
+
+                            tokens.push(Token::new(
+                                TokenType::Interpolation,
+                                format!("{quote}{buffer}${{").as_str(),
+                                &buffer,
+                                part_span,
+                            ));
+
+                            buffer.clear();
+                            resumed = false;
+                            open.interpolations.push(0);
                             mode = TokenizerMode::None;
                         }
                         _ => buffer.push(ch),
@@ -122,7 +209,7 @@ impl Tokenizer {
                             TokenType::Number,
                             buffer.as_str(),
                             format!("{buffer}.0").as_str(),
-                            index + 1,
+                            span(start, column),
                         ));
 
                         buffer.clear();
@@ -131,18 +218,19 @@ impl Tokenizer {
                 },
                 TokenizerMode::Number(true) => match ch {
                     '0'..='9' => {
-                        let (_, ch) = iter.next().unwrap();
-
-                        buffer.push(ch);
+                        buffer.push(iter.next().unwrap().1);
                     }
                     _ if buffer.ends_with('.') => {
                         tokens.push(Token::new(
                             TokenType::Number,
                             &buffer[..buffer.len() - 1],
                             &format!("{buffer}0"),
-                            index + 1,
+                            span(start, column - 1),
+                        ));
+                        tokens.push(Token::new_punctuator(
+                            TokenType::Dot,
+                            span(column - 1, column),
                         ));
-                        tokens.push(Token::new_punctuator(TokenType::Dot, index + 1));
 
                         buffer.clear();
                         mode = TokenizerMode::None;
@@ -161,7 +249,7 @@ impl Tokenizer {
                             TokenType::Number,
                             buffer.as_str(),
                             literal.as_str(),
-                            index + 1,
+                            span(start, column),
                         ));
 
                         buffer.clear();
@@ -173,12 +261,12 @@ impl Tokenizer {
                         buffer.push(iter.next().unwrap().1);
                     }
                     _ => {
-                        match TokenType::from_string(&buffer) {
+                        match TokenType::from_string(&buffer, extensions) {
                             TokenType::String => {
-                                tokens.push(Token::new_identifier(&buffer, index + 1));
+                                tokens.push(Token::new_identifier(&buffer, span(start, column)));
                             }
                             token_type => {
-                                tokens.push(Token::new_reserved(token_type, index + 1));
+                                tokens.push(Token::new_reserved(token_type, span(start, column)));
                             }
                         }
                         buffer.clear();
@@ -188,14 +276,19 @@ impl Tokenizer {
             }
         }
 
+        let end = line.chars().count();
+
         match mode {
-            TokenizerMode::String => errors.push(TokenizerError::unterminated_string(index + 1)),
+            TokenizerMode::String => {
+                let opened = string_start.unwrap_or_else(|| span(start, start + 1));
+                open.string = Some((opened, buffer, resumed));
+            }
             TokenizerMode::Number(false) => {
                 tokens.push(Token::new(
                     TokenType::Number,
                     buffer.as_str(),
                     format!("{buffer}.0").as_str(),
-                    index + 1,
+                    span(start, end),
                 ));
             }
             TokenizerMode::Number(true) if buffer.ends_with('.') => {
@@ -203,9 +296,9 @@ impl Tokenizer {
                     TokenType::Number,
                     &buffer[..buffer.len() - 1],
                     &format!("{buffer}0"),
-                    index + 1,
+                    span(start, end - 1),
                 ));
-                tokens.push(Token::new_punctuator(TokenType::Dot, index + 1));
+                tokens.push(Token::new_punctuator(TokenType::Dot, span(end - 1, end)));
             }
             TokenizerMode::Number(true) => {
                 let mut literal = buffer.to_string();
@@ -221,15 +314,15 @@ impl Tokenizer {
                     TokenType::Number,
                     buffer.as_str(),
                     literal.as_str(),
-                    index + 1,
+                    span(start, end),
                 ));
             }
-            TokenizerMode::Identifier => match TokenType::from_string(&buffer) {
+            TokenizerMode::Identifier => match TokenType::from_string(&buffer, extensions) {
                 TokenType::String => {
-                    tokens.push(Token::new_identifier(&buffer, index + 1));
+                    tokens.push(Token::new_identifier(&buffer, span(start, end)));
                 }
                 token_type => {
-                    tokens.push(Token::new_reserved(token_type, index + 1));
+                    tokens.push(Token::new_reserved(token_type, span(start, end)));
                 }
             },
             TokenizerMode::None => {}
@@ -0,0 +1,333 @@
+use std::{fmt::Display, rc::Rc};
+
+use super::chunk::{Chunk, Constant, FunctionProto, OpCode};
+
+pub const MAGIC: &[u8; 4] = b"LOXC";
+/// Bumped whenever the opcode set or the encoding changes:
+///
+/// - 2: upvalues
+/// - 3: lists
+/// - 4: maps
+/// - 5: arithmetic and bitwise operators
+/// - 6: `Dup` and `Bury` for compound assignment
+/// - 7: string interpolation
+/// - 8: imports
+/// - 9: exceptions
+pub const VERSION: u16 = 9;
+
+const TAG_NUMBER: u8 = 0;
+const TAG_STRING: u8 = 1;
+const TAG_FUNCTION: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeError {
+    NotBytecode,
+    UnsupportedVersion(u16),
+    ChecksumMismatch,
+    Truncated,
+    Malformed(String),
+}
+
+impl Display for BytecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BytecodeError::NotBytecode => write!(f, "Not a Lox bytecode file."),
+            BytecodeError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported bytecode version {version}, expected {VERSION}. Recompile the source."
+            ),
+            BytecodeError::ChecksumMismatch => {
+                write!(f, "Bytecode checksum mismatch, the file is corrupted.")
+            }
+            BytecodeError::Truncated => write!(f, "Unexpected end of bytecode file."),
+            BytecodeError::Malformed(msg) => write!(f, "Malformed bytecode: {msg}."),
+        }
+    }
+}
+
+impl std::error::Error for BytecodeError {}
+
+/// The `.loxc` file format: `LOXC` magic, a big-endian `u16` version, the
+/// script function and a CRC-32 of everything before it.
+///
+/// A function is its name, arity, code, run-length encoded line table and
+/// constant pool, where each constant is tagged and nested functions are
+/// written recursively.
+pub struct Bytecode;
+
+impl Bytecode {
+    pub fn serialize(function: &FunctionProto) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION.to_be_bytes());
+        write_function(&mut bytes, function);
+
+        let checksum = crc32(&bytes);
+        bytes.extend(checksum.to_be_bytes());
+
+        bytes
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Rc<FunctionProto>, BytecodeError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(BytecodeError::NotBytecode);
+        }
+
+        let mut reader = Reader {
+            bytes,
+            offset: MAGIC.len(),
+        };
+        let version = u16::from_be_bytes(reader.array()?);
+        if version != VERSION {
+            return Err(BytecodeError::UnsupportedVersion(version));
+        }
+
+        let Some(body_len) = bytes.len().checked_sub(4) else {
+            return Err(BytecodeError::Truncated);
+        };
+        let (body, checksum) = bytes.split_at(body_len);
+        if body.len() < reader.offset {
+            return Err(BytecodeError::Truncated);
+        }
+        if crc32(body) != u32::from_be_bytes(checksum.try_into().expect("four bytes")) {
+            return Err(BytecodeError::ChecksumMismatch);
+        }
+
+        reader.bytes = body;
+        let function = reader.function()?;
+        if reader.offset != body.len() {
+            return Err(BytecodeError::Malformed("trailing data".to_string()));
+        }
+
+        Ok(Rc::new(function))
+    }
+}
+
+fn write_u32(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend((value as u32).to_be_bytes());
+}
+
+fn write_str(bytes: &mut Vec<u8>, string: &str) {
+    write_u32(bytes, string.len());
+    bytes.extend(string.as_bytes());
+}
+
+fn write_function(bytes: &mut Vec<u8>, function: &FunctionProto) {
+    let chunk = &function.chunk;
+
+    write_str(bytes, &function.name);
+    write_u32(bytes, function.arity);
+    write_u32(bytes, function.upvalue_count);
+
+    write_u32(bytes, chunk.len());
+    bytes.extend(chunk.code());
+
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for line in chunk.lines() {
+        match runs.last_mut() {
+            Some((last, count)) if last == line => *count += 1,
+            _ => runs.push((*line, 1)),
+        }
+    }
+    write_u32(bytes, runs.len());
+    for (line, count) in runs {
+        write_u32(bytes, line);
+        write_u32(bytes, count);
+    }
+
+    write_u32(bytes, chunk.constants().len());
+    for constant in chunk.constants() {
+        match constant {
+            Constant::Number(value) => {
+                bytes.push(TAG_NUMBER);
+                bytes.extend(value.to_bits().to_be_bytes());
+            }
+            Constant::String(string) => {
+                bytes.push(TAG_STRING);
+                write_str(bytes, string);
+            }
+            Constant::Function(function) => {
+                bytes.push(TAG_FUNCTION);
+                write_function(bytes, function);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], BytecodeError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(BytecodeError::Truncated)?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], BytecodeError> {
+        Ok(self.take(N)?.try_into().expect("exact length"))
+    }
+
+    fn u32(&mut self) -> Result<usize, BytecodeError> {
+        Ok(u32::from_be_bytes(self.array()?) as usize)
+    }
+
+    fn string(&mut self) -> Result<Rc<str>, BytecodeError> {
+        let len = self.u32()?;
+        let bytes = self.take(len)?;
+
+        std::str::from_utf8(bytes)
+            .map(Rc::from)
+            .map_err(|_| BytecodeError::Malformed("invalid UTF-8 in string".to_string()))
+    }
+
+    fn function(&mut self) -> Result<FunctionProto, BytecodeError> {
+        let name = self.string()?.to_string();
+        let arity = self.u32()?;
+        let upvalue_count = self.u32()?;
+
+        let len = self.u32()?;
+        let code = self.take(len)?.to_vec();
+
+        let mut lines = Vec::with_capacity(code.len());
+        for _ in 0..self.u32()? {
+            let (line, count) = (self.u32()?, self.u32()?);
+            if lines.len() + count > code.len() {
+                return Err(malformed(&name, "line table longer than code"));
+            }
+            lines.extend(std::iter::repeat_n(line, count));
+        }
+        if lines.len() != code.len() {
+            return Err(malformed(&name, "line table shorter than code"));
+        }
+
+        let mut constants = Vec::new();
+        for _ in 0..self.u32()? {
+            let constant = match self.array::<1>()?[0] {
+                TAG_NUMBER => Constant::Number(f64::from_bits(u64::from_be_bytes(self.array()?))),
+                TAG_STRING => Constant::String(self.string()?),
+                TAG_FUNCTION => Constant::Function(Rc::new(self.function()?)),
+                tag => return Err(malformed(&name, &format!("unknown constant tag {tag}"))),
+            };
+            constants.push(constant);
+        }
+
+        let function = FunctionProto {
+            name,
+            arity,
+            upvalue_count,
+            chunk: Chunk::from_parts(code, lines, constants),
+        };
+        validate(&function)?;
+
+        Ok(function)
+    }
+}
+
+fn malformed(function: &str, msg: &str) -> BytecodeError {
+    let function = match function.is_empty() {
+        true => "script",
+        false => function,
+    };
+
+    BytecodeError::Malformed(format!("{msg} in {function}"))
+}
+
+/// Checks that every instruction decodes and its operands stay inside the
+/// chunk, so a hand-edited file fails to load instead of crashing the VM.
+fn validate(function: &FunctionProto) -> Result<(), BytecodeError> {
+    let chunk = &function.chunk;
+    let error = |msg: String| malformed(&function.name, &msg);
+
+    let mut offset = 0;
+    let mut last = None;
+    while offset < chunk.len() {
+        let op = OpCode::try_from(chunk.code()[offset])
+            .map_err(|byte| error(format!("unknown opcode {byte} at {offset}")))?;
+        let mut next = offset + 1 + op.operand_len();
+        if next > chunk.len() {
+            return Err(error(format!("truncated instruction at {offset}")));
+        }
+
+        match op {
+            OpCode::Jump | OpCode::JumpIfFalse | OpCode::ForIter | OpCode::Try
+                if next + chunk.read_u16(offset + 1) as usize >= chunk.len() =>
+            {
+                return Err(error(format!("jump out of bounds at {offset}")));
+            }
+            OpCode::Loop if chunk.read_u16(offset + 1) as usize > next => {
+                return Err(error(format!("loop out of bounds at {offset}")));
+            }
+            OpCode::Jump | OpCode::JumpIfFalse | OpCode::ForIter | OpCode::Loop | OpCode::Try => {}
+            OpCode::BuildList | OpCode::BuildMap | OpCode::BuildString => {}
+            OpCode::GetUpvalue | OpCode::SetUpvalue
+                if chunk.code()[offset + 1] as usize >= function.upvalue_count =>
+            {
+                return Err(error(format!("invalid upvalue operand at {offset}")));
+            }
+            OpCode::Closure => {
+                let constant = chunk.constants().get(chunk.read_u16(offset + 1) as usize);
+                let Some(Constant::Function(closure)) = constant else {
+                    return Err(error(format!("invalid constant operand at {offset}")));
+                };
+
+                let captures = next..next + 2 * closure.upvalue_count;
+                let valid = chunk.code().get(captures.clone()).is_some_and(|captures| {
+                    captures.chunks(2).all(|capture| match capture[0] {
+                        0 => (capture[1] as usize) < function.upvalue_count,
+                        1 => true,
+                        _ => false,
+                    })
+                });
+                if !valid {
+                    return Err(error(format!("invalid upvalue capture at {offset}")));
+                }
+                next = captures.end;
+            }
+            _ if op.operand_len() == 2 => {
+                let constant = chunk.constants().get(chunk.read_u16(offset + 1) as usize);
+                let valid = match (op, constant) {
+                    (_, None) => false,
+                    (OpCode::Constant, Some(constant)) => {
+                        !matches!(constant, Constant::Function(_))
+                    }
+                    (_, Some(constant)) => matches!(constant, Constant::String(_)),
+                };
+                if !valid {
+                    return Err(error(format!("invalid constant operand at {offset}")));
+                }
+            }
+            _ => {}
+        }
+
+        last = Some(op);
+        offset = next;
+    }
+
+    match last {
+        Some(OpCode::Return) => Ok(()),
+        _ => Err(error("missing final return".to_string())),
+    }
+}
+
+/// CRC-32 (IEEE 802.3), computed bitwise.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+
+    !crc
+}
@@ -0,0 +1,306 @@
+use std::{fmt::Display, rc::Rc};
+
+use super::object::format_function;
+use crate::interpreter::value::format_number;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OpCode {
+    Constant,
+    Nil,
+    True,
+    False,
+    Pop,
+    GetLocal,
+    SetLocal,
+    GetGlobal,
+    DefineGlobal,
+    SetGlobal,
+    GetUpvalue,
+    SetUpvalue,
+    GetProperty,
+    SetProperty,
+    GetSuper,
+    Equal,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Not,
+    Negate,
+    Print,
+    Jump,
+    JumpIfFalse,
+    Loop,
+    Call,
+    Closure,
+    CloseUpvalue,
+    Return,
+    Class,
+    Inherit,
+    Method,
+    BuildList,
+    GetIndex,
+    SetIndex,
+    ForIter,
+    BuildMap,
+    Modulo,
+    Power,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    BitNot,
+    /// Pushes a copy of the top `n` values, keeping their order.
+    Dup,
+    /// Moves the top value down below the `n` values under it.
+    Bury,
+    BuildString,
+    /// Runs the file named by the string operand unless it already ran, and
+    /// pushes its namespace.
+    Import,
+    /// Pops a namespace and copies its globals into the current file's.
+    ImportNames,
+    /// Pops a value and raises it as an error.
+    Throw,
+    /// Installs a handler jumping forward by the operand when an error is
+    /// raised. The stack is restored to how it was here, then the error value
+    /// and its line are pushed.
+    Try,
+    /// Removes the innermost handler.
+    EndTry,
+    /// Pops a line and a value and raises the value again as if from that line.
+    Rethrow,
+}
+
+impl OpCode {
+    const ALL: [OpCode; 59] = [
+        OpCode::Constant,
+        OpCode::Nil,
+        OpCode::True,
+        OpCode::False,
+        OpCode::Pop,
+        OpCode::GetLocal,
+        OpCode::SetLocal,
+        OpCode::GetGlobal,
+        OpCode::DefineGlobal,
+        OpCode::SetGlobal,
+        OpCode::GetUpvalue,
+        OpCode::SetUpvalue,
+        OpCode::GetProperty,
+        OpCode::SetProperty,
+        OpCode::GetSuper,
+        OpCode::Equal,
+        OpCode::Greater,
+        OpCode::GreaterEqual,
+        OpCode::Less,
+        OpCode::LessEqual,
+        OpCode::Add,
+        OpCode::Subtract,
+        OpCode::Multiply,
+        OpCode::Divide,
+        OpCode::Not,
+        OpCode::Negate,
+        OpCode::Print,
+        OpCode::Jump,
+        OpCode::JumpIfFalse,
+        OpCode::Loop,
+        OpCode::Call,
+        OpCode::Closure,
+        OpCode::CloseUpvalue,
+        OpCode::Return,
+        OpCode::Class,
+        OpCode::Inherit,
+        OpCode::Method,
+        OpCode::BuildList,
+        OpCode::GetIndex,
+        OpCode::SetIndex,
+        OpCode::ForIter,
+        OpCode::BuildMap,
+        OpCode::Modulo,
+        OpCode::Power,
+        OpCode::BitAnd,
+        OpCode::BitOr,
+        OpCode::BitXor,
+        OpCode::ShiftLeft,
+        OpCode::ShiftRight,
+        OpCode::BitNot,
+        OpCode::Dup,
+        OpCode::Bury,
+        OpCode::BuildString,
+        OpCode::Import,
+        OpCode::ImportNames,
+        OpCode::Throw,
+        OpCode::Try,
+        OpCode::EndTry,
+        OpCode::Rethrow,
+    ];
+}
+
+impl OpCode {
+    /// Number of operand bytes following the opcode. `Closure` is additionally
+    /// followed by an `(is_local, index)` byte pair for each upvalue it captures.
+    pub fn operand_len(self) -> usize {
+        match self {
+            OpCode::GetLocal
+            | OpCode::SetLocal
+            | OpCode::GetUpvalue
+            | OpCode::SetUpvalue
+            | OpCode::Call
+            | OpCode::Dup
+            | OpCode::Bury => 1,
+            OpCode::Constant
+            | OpCode::GetGlobal
+            | OpCode::DefineGlobal
+            | OpCode::SetGlobal
+            | OpCode::GetProperty
+            | OpCode::SetProperty
+            | OpCode::GetSuper
+            | OpCode::Jump
+            | OpCode::JumpIfFalse
+            | OpCode::Loop
+            | OpCode::Closure
+            | OpCode::Class
+            | OpCode::Method
+            | OpCode::BuildList
+            | OpCode::BuildMap
+            | OpCode::BuildString
+            | OpCode::ForIter
+            | OpCode::Import
+            | OpCode::Try => 2,
+            _ => 0,
+        }
+    }
+}
+
+impl TryFrom<u8> for OpCode {
+    type Error = u8;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        OpCode::ALL.get(byte as usize).copied().ok_or(byte)
+    }
+}
+
+/// Prints the clox-style mnemonic, e.g. `OP_GET_LOCAL`.
+impl Display for OpCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut name = String::from("OP");
+        for char in format!("{self:?}").chars() {
+            if char.is_ascii_uppercase() {
+                name.push('_');
+            }
+            name.push(char.to_ascii_uppercase());
+        }
+
+        write!(f, "{name}")
+    }
+}
+
+/// Compile-time constants. Strings are interned by the VM when loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Number(f64),
+    String(Rc<str>),
+    Function(Rc<FunctionProto>),
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constant::Number(value) => write!(f, "{}", format_number(*value)),
+            Constant::String(string) => write!(f, "{string}"),
+            Constant::Function(function) => write!(f, "{}", format_function(function)),
+        }
+    }
+}
+
+/// A compiled function body: its bytecode and the metadata needed to call it.
+#[derive(Debug, Default, PartialEq)]
+pub struct FunctionProto {
+    pub name: String,
+    pub arity: usize,
+    pub upvalue_count: usize,
+    pub chunk: Chunk,
+}
+
+/// Bytecode with its constant pool and the source line of every byte.
+#[derive(Debug, Default, PartialEq)]
+pub struct Chunk {
+    code: Vec<u8>,
+    lines: Vec<usize>,
+    constants: Vec<Constant>,
+}
+
+impl Chunk {
+    /// Rebuilds a chunk as-is, without deduplicating constants.
+    pub(crate) fn from_parts(code: Vec<u8>, lines: Vec<usize>, constants: Vec<Constant>) -> Self {
+        Self {
+            code,
+            lines,
+            constants,
+        }
+    }
+
+    pub fn write(&mut self, byte: u8, line: usize) {
+        self.code.push(byte);
+        self.lines.push(line);
+    }
+
+    pub fn write_op(&mut self, op: OpCode, line: usize) {
+        self.write(op as u8, line);
+    }
+
+    pub fn write_u16(&mut self, value: u16, line: usize) {
+        let [high, low] = value.to_be_bytes();
+        self.write(high, line);
+        self.write(low, line);
+    }
+
+    /// Adds a constant, reusing an identical number or string already in the
+    /// pool. Numbers are compared bitwise so `-0` and `0` stay distinct.
+    pub fn add_constant(&mut self, constant: Constant) -> usize {
+        let existing = self.constants.iter().position(|c| match (c, &constant) {
+            (Constant::Number(a), Constant::Number(b)) => a.to_bits() == b.to_bits(),
+            (Constant::String(a), Constant::String(b)) => a == b,
+            _ => false,
+        });
+
+        existing.unwrap_or_else(|| {
+            self.constants.push(constant);
+            self.constants.len() - 1
+        })
+    }
+
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    pub fn code_mut(&mut self) -> &mut [u8] {
+        &mut self.code
+    }
+
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
+
+    pub fn constants(&self) -> &[Constant] {
+        &self.constants
+    }
+
+    pub fn read_u16(&self, offset: usize) -> u16 {
+        u16::from_be_bytes([self.code[offset], self.code[offset + 1]])
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+}
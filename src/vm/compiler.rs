@@ -0,0 +1,1017 @@
+use std::rc::Rc;
+
+use super::chunk::{Chunk, Constant, FunctionProto, OpCode};
+use crate::{
+    parser::{
+        ast::{Ast, ExprId, Identifier, StmtId},
+        error::ParseExprError,
+        expression::{
+            binary::BinaryType,
+            logical::LogicalType,
+            unary::UnaryType,
+            update::{Update, UpdateKind},
+            Expression,
+        },
+        statement::{control_flow::Try, Statement},
+    },
+    tokenizer::Span,
+};
+
+const MAX_LOCALS: usize = 256;
+const MAX_UPVALUES: usize = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FunctionKind {
+    Script,
+    Function,
+    Method,
+    Initializer,
+}
+
+struct Local {
+    name: String,
+    depth: usize,
+    is_captured: bool,
+}
+
+/// Where a closure finds a captured variable when it is created: a local slot
+/// of the enclosing function, or one of the enclosing function's own upvalues.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Upvalue {
+    index: u8,
+    is_local: bool,
+}
+
+/// An enclosing loop: the scope depth its body starts from, and the forward
+/// jumps of the `break` and `continue` statements still waiting for a target.
+struct Loop {
+    depth: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+/// A region of code running with a `try` handler installed: a try body, or a
+/// catch body followed by `finally`. Jumping out of it must remove the handler
+/// and run the `finally` block first.
+#[derive(Clone, Copy)]
+struct TryBlock {
+    finally: Option<StmtId>,
+    /// How many loops enclose the region.
+    loops: usize,
+}
+
+/// Compilation state of the function currently being emitted.
+struct FunctionState {
+    proto: FunctionProto,
+    kind: FunctionKind,
+    locals: Vec<Local>,
+    upvalues: Vec<Upvalue>,
+    scope_depth: usize,
+    loops: Vec<Loop>,
+    tries: Vec<TryBlock>,
+}
+
+impl FunctionState {
+    fn new(name: &str, kind: FunctionKind) -> Self {
+        // Slot zero holds the callee, or the receiver inside methods.
+        let slot_zero = match kind {
+            FunctionKind::Method | FunctionKind::Initializer => "this",
+            _ => "",
+        };
+
+        Self {
+            proto: FunctionProto {
+                name: name.to_string(),
+                ..FunctionProto::default()
+            },
+            kind,
+            locals: vec![Local {
+                name: slot_zero.to_string(),
+                depth: 0,
+                is_captured: false,
+            }],
+            upvalues: Vec::new(),
+            scope_depth: 0,
+            loops: Vec::new(),
+            tries: Vec::new(),
+        }
+    }
+}
+
+enum Variable {
+    Local(u8),
+    Upvalue(u8),
+    Global(u16),
+}
+
+/// Single pass from a resolved program AST to bytecode. Locals are assigned
+/// stack slots at compile time; everything declared at the top level is a global.
+pub struct Compiler<'a> {
+    ast: &'a Ast,
+    states: Vec<FunctionState>,
+    errors: Vec<ParseExprError>,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn compile(ast: &'a Ast) -> Result<Rc<FunctionProto>, Vec<ParseExprError>> {
+        let mut compiler = Compiler {
+            ast,
+            states: vec![FunctionState::new("", FunctionKind::Script)],
+            errors: Vec::new(),
+        };
+
+        for statement in ast.program() {
+            compiler.statement(*statement);
+        }
+
+        let line = ast
+            .program()
+            .last()
+            .map(|statement| ast.statement_span(*statement).end_line)
+            .unwrap_or(1);
+        compiler.emit_return(line);
+
+        let state = compiler.states.pop().expect("script state");
+
+        match compiler.errors.is_empty() {
+            true => Ok(Rc::new(state.proto)),
+            false => Err(compiler.errors),
+        }
+    }
+
+    fn state(&mut self) -> &mut FunctionState {
+        self.states.last_mut().expect("function state")
+    }
+
+    fn chunk(&mut self) -> &mut Chunk {
+        &mut self.state().proto.chunk
+    }
+
+    fn emit(&mut self, op: OpCode, line: usize) {
+        self.chunk().write_op(op, line);
+    }
+
+    fn emit_u8(&mut self, op: OpCode, operand: u8, line: usize) {
+        self.emit(op, line);
+        self.chunk().write(operand, line);
+    }
+
+    fn emit_u16(&mut self, op: OpCode, operand: u16, line: usize) {
+        self.emit(op, line);
+        self.chunk().write_u16(operand, line);
+    }
+
+    fn emit_jump(&mut self, op: OpCode, line: usize) -> usize {
+        self.emit_u16(op, u16::MAX, line);
+        self.chunk().len() - 2
+    }
+
+    fn patch_jump(&mut self, offset: usize, span: Span) {
+        let jump = self.chunk().len() - offset - 2;
+
+        match u16::try_from(jump) {
+            Ok(jump) => {
+                let [high, low] = jump.to_be_bytes();
+                self.chunk().code_mut()[offset] = high;
+                self.chunk().code_mut()[offset + 1] = low;
+            }
+            Err(_) => self.error(span, "Too much code to jump over."),
+        }
+    }
+
+    fn emit_loop(&mut self, start: usize, span: Span) {
+        self.emit(OpCode::Loop, span.line);
+        let offset = self.chunk().len() - start + 2;
+
+        match u16::try_from(offset) {
+            Ok(offset) => self.chunk().write_u16(offset, span.line),
+            Err(_) => {
+                self.chunk().write_u16(0, span.line);
+                self.error(span, "Loop body too large.");
+            }
+        }
+    }
+
+    fn emit_return(&mut self, line: usize) {
+        match self.state().kind {
+            FunctionKind::Initializer => self.emit_u8(OpCode::GetLocal, 0, line),
+            _ => self.emit(OpCode::Nil, line),
+        }
+        self.emit(OpCode::Return, line);
+    }
+
+    fn constant(&mut self, constant: Constant, span: Span) -> u16 {
+        let index = self.chunk().add_constant(constant);
+
+        u16::try_from(index).unwrap_or_else(|_| {
+            self.error(span, "Too many constants in one chunk.");
+            0
+        })
+    }
+
+    fn identifier_constant(&mut self, name: &Identifier) -> u16 {
+        self.constant(Constant::String(Rc::from(name.get_name())), name.get_span())
+    }
+
+    fn error(&mut self, span: Span, msg: &str) {
+        self.errors.push(ParseExprError::compile(span, msg));
+    }
+
+    fn error_at(&mut self, name: &Identifier, msg: &str) {
+        self.errors.push(ParseExprError::resolution(
+            name.get_span(),
+            name.get_name(),
+            msg,
+        ));
+    }
+
+    fn begin_scope(&mut self) {
+        self.state().scope_depth += 1;
+    }
+
+    fn end_scope(&mut self, line: usize) {
+        let state = self.state();
+        state.scope_depth -= 1;
+
+        let depth = state.scope_depth;
+        while let Some(local) = self.state().locals.pop_if(|local| local.depth > depth) {
+            match local.is_captured {
+                true => self.emit(OpCode::CloseUpvalue, line),
+                false => self.emit(OpCode::Pop, line),
+            }
+        }
+    }
+
+    fn begin_loop(&mut self) {
+        let depth = self.state().scope_depth;
+        self.state().loops.push(Loop {
+            depth,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+    }
+
+    fn patch_continues(&mut self, span: Span) {
+        let continues = self
+            .state()
+            .loops
+            .last_mut()
+            .map(|current| std::mem::take(&mut current.continues))
+            .unwrap_or_default();
+        for offset in continues {
+            self.patch_jump(offset, span);
+        }
+    }
+
+    fn end_loop(&mut self, span: Span) {
+        if let Some(current) = self.state().loops.pop() {
+            for offset in current.breaks {
+                self.patch_jump(offset, span);
+            }
+        }
+    }
+
+    /// Emits a `break` or `continue`: the locals declared inside the loop body
+    /// are discarded without leaving their scopes, then the jump is recorded.
+    fn jump_out_of_loop(&mut self, is_break: bool, line: usize) {
+        let Some(depth) = self.state().loops.last().map(|current| current.depth) else {
+            return;
+        };
+
+        let loops = self.state().loops.len();
+        let tries = self
+            .state()
+            .tries
+            .iter()
+            .rev()
+            .take_while(|try_block| try_block.loops >= loops)
+            .count();
+        self.exit_tries(tries, line);
+
+        let captured: Vec<bool> = self
+            .state()
+            .locals
+            .iter()
+            .rev()
+            .take_while(|local| local.depth > depth)
+            .map(|local| local.is_captured)
+            .collect();
+        for is_captured in captured {
+            match is_captured {
+                true => self.emit(OpCode::CloseUpvalue, line),
+                false => self.emit(OpCode::Pop, line),
+            }
+        }
+
+        let offset = self.emit_jump(OpCode::Jump, line);
+        if let Some(current) = self.state().loops.last_mut() {
+            match is_break {
+                true => current.breaks.push(offset),
+                false => current.continues.push(offset),
+            }
+        }
+    }
+
+    /// Leaves the innermost `count` try regions before jumping out of them,
+    /// innermost first: each handler is removed and its `finally` block runs.
+    fn exit_tries(&mut self, count: usize, line: usize) {
+        let tries = std::mem::take(&mut self.state().tries);
+
+        for index in (tries.len() - count..tries.len()).rev() {
+            self.emit(OpCode::EndTry, line);
+            if let Some(finally) = tries[index].finally {
+                self.state().tries = tries[..index].to_vec();
+                self.statement(finally);
+            }
+        }
+
+        self.state().tries = tries;
+    }
+
+    /// Every way out of the statement runs `finally`. Handlers resume with the
+    /// error and its line on top of the stack; the catch variable names the
+    /// error and the line stays in a hidden local.
+    fn try_statement(&mut self, try_statement: &Try, span: Span) {
+        let finally = try_statement.finally();
+        let loops = self.state().loops.len();
+
+        let handler = self.emit_jump(OpCode::Try, span.line);
+        self.state().tries.push(TryBlock { finally, loops });
+        self.statement(try_statement.body());
+        self.state().tries.pop();
+        self.emit(OpCode::EndTry, span.line);
+        if let Some(finally) = finally {
+            self.statement(finally);
+        }
+        let mut exits = vec![self.emit_jump(OpCode::Jump, span.line)];
+
+        self.patch_jump(handler, span);
+        let Some(catch) = try_statement.catch() else {
+            self.rethrow_after(finally, 2, span);
+            self.patch_jumps(exits, span);
+            return;
+        };
+
+        let handler = finally.map(|_| self.emit_jump(OpCode::Try, span.line));
+        self.state().tries.push(TryBlock { finally, loops });
+        self.begin_scope();
+        self.add_local(catch.get_name());
+        self.add_local(&Identifier::new("", span));
+        self.statement(catch.body());
+        self.end_scope(span.line);
+        self.state().tries.pop();
+
+        if let Some(handler) = handler {
+            self.emit(OpCode::EndTry, span.line);
+            self.statement_opt(finally);
+            exits.push(self.emit_jump(OpCode::Jump, span.line));
+
+            // Below the new error is the one the catch variable held.
+            self.patch_jump(handler, span);
+            self.rethrow_after(finally, 4, span);
+        }
+        self.patch_jumps(exits, span);
+    }
+
+    /// Runs `finally` and raises the error on top of the stack again; `hidden`
+    /// is the number of values the handlers left on the stack.
+    fn rethrow_after(&mut self, finally: Option<StmtId>, hidden: usize, span: Span) {
+        self.begin_scope();
+        for _ in 0..hidden {
+            self.add_local(&Identifier::new("", span));
+        }
+        let line = (self.state().locals.len() - 1) as u8;
+
+        self.statement_opt(finally);
+        self.emit_u8(OpCode::GetLocal, line - 1, span.line);
+        self.emit_u8(OpCode::GetLocal, line, span.line);
+        self.emit(OpCode::Rethrow, span.line);
+        self.discard_scope();
+    }
+
+    fn statement_opt(&mut self, statement: Option<StmtId>) {
+        if let Some(statement) = statement {
+            self.statement(statement);
+        }
+    }
+
+    fn patch_jumps(&mut self, jumps: Vec<usize>, span: Span) {
+        for jump in jumps {
+            self.patch_jump(jump, span);
+        }
+    }
+
+    /// Ends a scope whose code never falls through, so its locals need no pops.
+    fn discard_scope(&mut self) {
+        let state = self.state();
+        state.scope_depth -= 1;
+
+        let depth = state.scope_depth;
+        while state.locals.pop_if(|local| local.depth > depth).is_some() {}
+    }
+
+    fn add_local(&mut self, name: &Identifier) {
+        if self.state().locals.len() >= MAX_LOCALS {
+            self.error_at(name, "Too many local variables in function.");
+            return;
+        }
+
+        let depth = self.state().scope_depth;
+        self.state().locals.push(Local {
+            name: name.get_name().to_string(),
+            depth,
+            is_captured: false,
+        });
+    }
+
+    /// Declares `name` in the current scope: a new local slot inside blocks,
+    /// or a global name constant at the top level.
+    fn declare(&mut self, name: &Identifier) -> Option<u16> {
+        match self.state().scope_depth {
+            0 => Some(self.identifier_constant(name)),
+            _ => {
+                self.add_local(name);
+                None
+            }
+        }
+    }
+
+    fn define(&mut self, global: Option<u16>, line: usize) {
+        if let Some(global) = global {
+            self.emit_u16(OpCode::DefineGlobal, global, line);
+        }
+    }
+
+    fn resolve(&mut self, name: &Identifier) -> Variable {
+        let current = self.states.len() - 1;
+
+        if let Some(slot) = Self::find_local(&self.states[current], name.get_name()) {
+            return Variable::Local(slot as u8);
+        }
+        if let Some(index) = self.resolve_upvalue(current, name) {
+            return Variable::Upvalue(index);
+        }
+
+        Variable::Global(self.identifier_constant(name))
+    }
+
+    /// Finds `name` in the functions enclosing `states[current]`, threading an
+    /// upvalue through every function in between.
+    fn resolve_upvalue(&mut self, current: usize, name: &Identifier) -> Option<u8> {
+        let enclosing = current.checked_sub(1)?;
+
+        if let Some(slot) = Self::find_local(&self.states[enclosing], name.get_name()) {
+            self.states[enclosing].locals[slot].is_captured = true;
+            return self.add_upvalue(current, slot as u8, true, name);
+        }
+
+        let index = self.resolve_upvalue(enclosing, name)?;
+        self.add_upvalue(current, index, false, name)
+    }
+
+    fn add_upvalue(
+        &mut self,
+        current: usize,
+        index: u8,
+        is_local: bool,
+        name: &Identifier,
+    ) -> Option<u8> {
+        let upvalue = Upvalue { index, is_local };
+        let upvalues = &mut self.states[current].upvalues;
+
+        if let Some(existing) = upvalues.iter().position(|other| *other == upvalue) {
+            return Some(existing as u8);
+        }
+        if upvalues.len() >= MAX_UPVALUES {
+            self.error_at(name, "Too many closure variables in function.");
+            return Some(0);
+        }
+
+        upvalues.push(upvalue);
+        Some((upvalues.len() - 1) as u8)
+    }
+
+    fn find_local(state: &FunctionState, name: &str) -> Option<usize> {
+        state
+            .locals
+            .iter()
+            .rposition(|local| !local.name.is_empty() && local.name == name)
+    }
+
+    fn get_variable(&mut self, name: &Identifier) {
+        let line = name.get_span().line;
+
+        match self.resolve(name) {
+            Variable::Local(slot) => self.emit_u8(OpCode::GetLocal, slot, line),
+            Variable::Upvalue(index) => self.emit_u8(OpCode::GetUpvalue, index, line),
+            Variable::Global(index) => self.emit_u16(OpCode::GetGlobal, index, line),
+        }
+    }
+
+    fn set_variable(&mut self, name: &Identifier) {
+        let line = name.get_span().line;
+
+        match self.resolve(name) {
+            Variable::Local(slot) => self.emit_u8(OpCode::SetLocal, slot, line),
+            Variable::Upvalue(index) => self.emit_u8(OpCode::SetUpvalue, index, line),
+            Variable::Global(index) => self.emit_u16(OpCode::SetGlobal, index, line),
+        }
+    }
+
+    fn statement(&mut self, id: StmtId) {
+        let ast = self.ast;
+        let span = ast.statement_span(id);
+
+        match &ast[id] {
+            Statement::Expression(expression) => {
+                self.expression(*expression);
+                self.emit(OpCode::Pop, span.end_line);
+            }
+            Statement::Print(expression) => {
+                self.expression(*expression);
+                self.emit(OpCode::Print, span.line);
+            }
+            Statement::Var(var) => {
+                let global = self.declare(var.get_name());
+
+                match var.initializer() {
+                    Some(initializer) => self.expression(initializer),
+                    None => self.emit(OpCode::Nil, span.line),
+                }
+                self.define(global, span.line);
+            }
+            Statement::Import(import) => {
+                let path = self.constant(Constant::String(Rc::from(import.path())), span);
+                self.emit_u16(OpCode::Import, path, span.line);
+
+                match import.alias() {
+                    Some(alias) => {
+                        let global = self.declare(alias);
+                        self.define(global, span.line);
+                    }
+                    None => self.emit(OpCode::ImportNames, span.line),
+                }
+            }
+            Statement::Block(statements) => {
+                self.begin_scope();
+                for statement in statements {
+                    self.statement(*statement);
+                }
+                self.end_scope(span.end_line);
+            }
+            Statement::If(if_statement) => {
+                self.expression(if_statement.condition());
+                let then_jump = self.emit_jump(OpCode::JumpIfFalse, span.line);
+                self.emit(OpCode::Pop, span.line);
+                self.statement(if_statement.then_branch());
+
+                let else_jump = self.emit_jump(OpCode::Jump, span.line);
+                self.patch_jump(then_jump, span);
+                self.emit(OpCode::Pop, span.line);
+
+                if let Some(else_branch) = if_statement.else_branch() {
+                    self.statement(else_branch);
+                }
+                self.patch_jump(else_jump, span);
+            }
+            Statement::While(while_statement) => {
+                let start = self.chunk().len();
+                self.expression(while_statement.condition());
+
+                let exit = self.emit_jump(OpCode::JumpIfFalse, span.line);
+                self.emit(OpCode::Pop, span.line);
+                self.begin_loop();
+                self.statement(while_statement.body());
+                self.patch_continues(span);
+                self.emit_loop(start, span);
+
+                self.patch_jump(exit, span);
+                self.emit(OpCode::Pop, span.line);
+                self.end_loop(span);
+            }
+            Statement::For(for_statement) => {
+                self.begin_scope();
+                if let Some(initializer) = for_statement.initializer() {
+                    self.statement(initializer);
+                }
+
+                let start = self.chunk().len();
+                let exit = for_statement.condition().map(|condition| {
+                    self.expression(condition);
+                    let exit = self.emit_jump(OpCode::JumpIfFalse, span.line);
+                    self.emit(OpCode::Pop, span.line);
+                    exit
+                });
+
+                self.begin_loop();
+                self.statement(for_statement.body());
+                self.patch_continues(span);
+                if let Some(increment) = for_statement.increment() {
+                    self.expression(increment);
+                    self.emit(OpCode::Pop, span.line);
+                }
+                self.emit_loop(start, span);
+
+                if let Some(exit) = exit {
+                    self.patch_jump(exit, span);
+                    self.emit(OpCode::Pop, span.line);
+                }
+                self.end_loop(span);
+                self.end_scope(span.end_line);
+            }
+            Statement::ForIn(for_in) => {
+                // Two hidden locals hold the list and the position of the next element.
+                self.begin_scope();
+                self.expression(for_in.iterable());
+                self.add_local(&Identifier::new("", span));
+                let zero = self.constant(Constant::Number(0.0), span);
+                self.emit_u16(OpCode::Constant, zero, span.line);
+                self.add_local(&Identifier::new("", span));
+                let index = (self.state().locals.len() - 1) as u8;
+
+                let start = self.chunk().len();
+                self.emit_u8(OpCode::GetLocal, index - 1, span.line);
+                self.emit_u8(OpCode::GetLocal, index, span.line);
+                let exit = self.emit_jump(OpCode::ForIter, span.line);
+
+                self.begin_loop();
+                self.begin_scope();
+                self.add_local(for_in.get_name());
+                self.statement(for_in.body());
+                self.end_scope(span.end_line);
+                self.patch_continues(span);
+
+                let one = self.constant(Constant::Number(1.0), span);
+                self.emit_u8(OpCode::GetLocal, index, span.line);
+                self.emit_u16(OpCode::Constant, one, span.line);
+                self.emit(OpCode::Add, span.line);
+                self.emit_u8(OpCode::SetLocal, index, span.line);
+                self.emit(OpCode::Pop, span.line);
+                self.emit_loop(start, span);
+
+                self.patch_jump(exit, span);
+                self.end_loop(span);
+                self.end_scope(span.end_line);
+            }
+            Statement::Function(function) => {
+                let global = self.declare(function.get_name());
+                self.function(id, FunctionKind::Function);
+                self.define(global, span.line);
+            }
+            Statement::Return(return_statement) => {
+                let tries = self.state().tries.len();
+
+                match return_statement.value() {
+                    Some(value) if tries > 0 => {
+                        // The value waits in a hidden local while `finally` blocks run.
+                        self.expression(value);
+                        self.begin_scope();
+                        self.add_local(&Identifier::new("", span));
+                        self.exit_tries(tries, span.line);
+                        self.emit(OpCode::Return, span.line);
+                        self.discard_scope();
+                    }
+                    Some(value) => {
+                        self.expression(value);
+                        self.emit(OpCode::Return, span.line);
+                    }
+                    None => {
+                        self.exit_tries(tries, span.line);
+                        self.emit_return(span.line);
+                    }
+                }
+            }
+            Statement::Throw(value) => {
+                self.expression(*value);
+                self.emit(OpCode::Throw, span.line);
+            }
+            Statement::Try(try_statement) => self.try_statement(try_statement, span),
+            Statement::Break(_) => self.jump_out_of_loop(true, span.line),
+            Statement::Continue(_) => self.jump_out_of_loop(false, span.line),
+            Statement::Class(class) => {
+                let name = class.get_name();
+                let name_constant = self.identifier_constant(name);
+                let global = self.declare(name);
+
+                self.emit_u16(OpCode::Class, name_constant, span.line);
+                self.define(global, span.line);
+
+                if let Some(superclass) = class.superclass() {
+                    self.begin_scope();
+                    self.add_local(&Identifier::new("super", ast.span(superclass)));
+
+                    self.expression(superclass);
+                    self.get_variable(name);
+                    self.emit(OpCode::Inherit, ast.span(superclass).line);
+                }
+
+                self.get_variable(name);
+                for method in class.methods() {
+                    let Statement::Function(function) = &ast[*method] else {
+                        continue;
+                    };
+
+                    let method_name = self.identifier_constant(function.get_name());
+                    let kind = match function.get_name().get_name() {
+                        "init" => FunctionKind::Initializer,
+                        _ => FunctionKind::Method,
+                    };
+                    let line = ast.statement_span(*method).line;
+
+                    self.function(*method, kind);
+                    self.emit_u16(OpCode::Method, method_name, line);
+                }
+                self.emit(OpCode::Pop, span.end_line);
+
+                if class.superclass().is_some() {
+                    self.end_scope(span.end_line);
+                }
+            }
+        }
+    }
+
+    /// Compiles a function body into its own prototype and emits the closure for it.
+    fn function(&mut self, id: StmtId, kind: FunctionKind) {
+        let ast = self.ast;
+        let span = ast.statement_span(id);
+        let Statement::Function(function) = &ast[id] else {
+            return;
+        };
+
+        self.states
+            .push(FunctionState::new(function.get_name().get_name(), kind));
+        self.begin_scope();
+
+        self.state().proto.arity = function.params().len();
+        for param in function.params() {
+            self.add_local(param);
+        }
+        for statement in function.body() {
+            self.statement(*statement);
+        }
+        self.emit_return(span.end_line);
+
+        let mut state = self.states.pop().expect("function state");
+        state.proto.upvalue_count = state.upvalues.len();
+        let index = self.constant(Constant::Function(Rc::new(state.proto)), span);
+        self.emit_u16(OpCode::Closure, index, span.line);
+
+        for upvalue in state.upvalues {
+            self.chunk().write(upvalue.is_local as u8, span.line);
+            self.chunk().write(upvalue.index, span.line);
+        }
+    }
+
+    fn expression(&mut self, id: ExprId) {
+        let ast = self.ast;
+        let span = ast.span(id);
+        let line = span.line;
+
+        match &ast[id] {
+            Expression::Number(literal) => {
+                let value = literal.parse().unwrap_or_default();
+                let index = self.constant(Constant::Number(value), span);
+                self.emit_u16(OpCode::Constant, index, line);
+            }
+            Expression::String(value) => {
+                let index = self.constant(Constant::String(Rc::from(value.as_str())), span);
+                self.emit_u16(OpCode::Constant, index, line);
+            }
+            Expression::True => self.emit(OpCode::True, line),
+            Expression::False => self.emit(OpCode::False, line),
+            Expression::Nil => self.emit(OpCode::Nil, line),
+            Expression::Grouping(inner) => self.expression(*inner),
+            Expression::Unary(unary) => {
+                self.expression(unary.expression());
+
+                let op = match unary.get_type() {
+                    UnaryType::Bang => OpCode::Not,
+                    UnaryType::Minus => OpCode::Negate,
+                    UnaryType::Tilde => OpCode::BitNot,
+                };
+                self.emit(op, unary.operator_span().line);
+            }
+            Expression::Binary(binary) => {
+                self.expression(binary.left());
+                let line = binary.operator_span().line;
+
+                // The left operand of a comma only runs for its side effects.
+                if *binary.get_type() == BinaryType::Comma {
+                    self.emit(OpCode::Pop, line);
+                    self.expression(binary.right());
+                    return;
+                }
+                self.expression(binary.right());
+
+                match binary.get_type() {
+                    BinaryType::EqualEqual => self.emit(OpCode::Equal, line),
+                    BinaryType::BangEqual => {
+                        self.emit(OpCode::Equal, line);
+                        self.emit(OpCode::Not, line);
+                    }
+                    BinaryType::Greater => self.emit(OpCode::Greater, line),
+                    BinaryType::GreaterEqual => self.emit(OpCode::GreaterEqual, line),
+                    BinaryType::Less => self.emit(OpCode::Less, line),
+                    BinaryType::LessEqual => self.emit(OpCode::LessEqual, line),
+                    BinaryType::Plus => self.emit(OpCode::Add, line),
+                    BinaryType::Minus => self.emit(OpCode::Subtract, line),
+                    BinaryType::Star => self.emit(OpCode::Multiply, line),
+                    BinaryType::Slash => self.emit(OpCode::Divide, line),
+                    BinaryType::Percent => self.emit(OpCode::Modulo, line),
+                    BinaryType::StarStar => self.emit(OpCode::Power, line),
+                    BinaryType::Ampersand => self.emit(OpCode::BitAnd, line),
+                    BinaryType::Pipe => self.emit(OpCode::BitOr, line),
+                    BinaryType::Caret => self.emit(OpCode::BitXor, line),
+                    BinaryType::LessLess => self.emit(OpCode::ShiftLeft, line),
+                    BinaryType::GreaterGreater => self.emit(OpCode::ShiftRight, line),
+                    BinaryType::Comma => unreachable!("comma is compiled above"),
+                }
+            }
+            Expression::Logical(logical) => {
+                self.expression(logical.left());
+                let line = logical.operator_span().line;
+
+                match logical.get_type() {
+                    LogicalType::And => {
+                        let end = self.emit_jump(OpCode::JumpIfFalse, line);
+                        self.emit(OpCode::Pop, line);
+                        self.expression(logical.right());
+                        self.patch_jump(end, span);
+                    }
+                    LogicalType::Or => {
+                        let else_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                        let end = self.emit_jump(OpCode::Jump, line);
+                        self.patch_jump(else_jump, span);
+                        self.emit(OpCode::Pop, line);
+                        self.expression(logical.right());
+                        self.patch_jump(end, span);
+                    }
+                }
+            }
+            Expression::Conditional(conditional) => {
+                self.expression(conditional.condition());
+                let line = conditional.question_span().line;
+
+                let else_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+                self.emit(OpCode::Pop, line);
+                self.expression(conditional.then_branch());
+                let end = self.emit_jump(OpCode::Jump, line);
+
+                self.patch_jump(else_jump, span);
+                self.emit(OpCode::Pop, line);
+                self.expression(conditional.else_branch());
+                self.patch_jump(end, span);
+            }
+            Expression::Variable(name) => self.get_variable(name),
+            Expression::Assign(assign) => {
+                self.expression(assign.value());
+                self.set_variable(assign.get_name());
+            }
+            Expression::Call(call) => {
+                self.expression(call.callee());
+                for argument in call.arguments() {
+                    self.expression(*argument);
+                }
+                // The parser reports calls with more than 255 arguments.
+                let count = call.arguments().len().min(u8::MAX as usize) as u8;
+                self.emit_u8(OpCode::Call, count, call.paren_span().line);
+            }
+            Expression::Get(get) => {
+                self.expression(get.object());
+                let name = self.identifier_constant(get.get_name());
+                self.emit_u16(OpCode::GetProperty, name, get.get_name().get_span().line);
+            }
+            Expression::Set(set) => {
+                self.expression(set.object());
+                self.expression(set.value());
+                let name = self.identifier_constant(set.get_name());
+                self.emit_u16(OpCode::SetProperty, name, set.get_name().get_span().line);
+            }
+            Expression::List(elements) => {
+                for element in elements {
+                    self.expression(*element);
+                }
+
+                let count = u16::try_from(elements.len()).unwrap_or_else(|_| {
+                    self.error(span, "Too many elements in list literal.");
+                    0
+                });
+                self.emit_u16(OpCode::BuildList, count, span.end_line);
+            }
+            Expression::Interpolation(parts) => {
+                for part in parts {
+                    self.expression(*part);
+                }
+
+                let count = u16::try_from(parts.len()).unwrap_or_else(|_| {
+                    self.error(span, "Too many parts in interpolated string.");
+                    0
+                });
+                self.emit_u16(OpCode::BuildString, count, span.end_line);
+            }
+            Expression::Map(entries) => {
+                for (key, value) in entries {
+                    self.expression(*key);
+                    self.expression(*value);
+                }
+
+                let count = u16::try_from(entries.len()).unwrap_or_else(|_| {
+                    self.error(span, "Too many entries in map literal.");
+                    0
+                });
+                self.emit_u16(OpCode::BuildMap, count, line);
+            }
+            Expression::Index(index) => {
+                self.expression(index.object());
+                self.expression(index.index());
+                self.emit(OpCode::GetIndex, index.bracket_span().line);
+            }
+            Expression::SetIndex(set_index) => {
+                self.expression(set_index.object());
+                self.expression(set_index.index());
+                self.expression(set_index.value());
+                self.emit(OpCode::SetIndex, set_index.bracket_span().line);
+            }
+            Expression::Update(update) => self.update(update),
+            Expression::Lambda(function) => self.function(*function, FunctionKind::Function),
+            Expression::This => self.get_variable(&Identifier::new("this", span)),
+            Expression::Super(method) => {
+                self.get_variable(&Identifier::new("this", span));
+                self.get_variable(&Identifier::new("super", span));
+                let name = self.identifier_constant(method);
+                self.emit_u16(OpCode::GetSuper, name, method.get_span().line);
+            }
+        }
+    }
+
+    /// Object and key of a property or index target stay on the stack under
+    /// the old value, so they are evaluated once and reused by the store. A
+    /// postfix update buries a copy of the old value beneath them to be left
+    /// as the result once the stored value is popped.
+    fn update(&mut self, update: &Update) {
+        let ast = self.ast;
+        let line = update.operator_span().line;
+        let postfix = update.kind() == UpdateKind::Postfix;
+
+        match &ast[update.target()] {
+            Expression::Variable(name) => {
+                self.get_variable(name);
+                if postfix {
+                    self.emit_u8(OpCode::Dup, 1, line);
+                }
+                self.update_operation(update);
+                self.set_variable(name);
+            }
+            Expression::Get(get) => {
+                self.expression(get.object());
+                let name = self.identifier_constant(get.get_name());
+                self.emit_u8(OpCode::Dup, 1, line);
+                self.emit_u16(OpCode::GetProperty, name, get.get_name().get_span().line);
+                if postfix {
+                    self.emit_u8(OpCode::Dup, 1, line);
+                    self.emit_u8(OpCode::Bury, 2, line);
+                }
+                self.update_operation(update);
+                self.emit_u16(OpCode::SetProperty, name, line);
+            }
+            Expression::Index(index) => {
+                self.expression(index.object());
+                self.expression(index.index());
+                self.emit_u8(OpCode::Dup, 2, line);
+                self.emit(OpCode::GetIndex, index.bracket_span().line);
+                if postfix {
+                    self.emit_u8(OpCode::Dup, 1, line);
+                    self.emit_u8(OpCode::Bury, 3, line);
+                }
+                self.update_operation(update);
+                self.emit(OpCode::SetIndex, index.bracket_span().line);
+            }
+            _ => unreachable!("the parser only updates variables, properties and indexes"),
+        }
+
+        if postfix {
+            self.emit(OpCode::Pop, line);
+        }
+    }
+
+    /// Combines the old value on top of the stack with the update's operand.
+    fn update_operation(&mut self, update: &Update) {
+        let line = update.operator_span().line;
+
+        match update.value() {
+            Some(value) => self.expression(value),
+            None => {
+                let one = self.constant(Constant::Number(1.0), update.operator_span());
+                self.emit_u16(OpCode::Constant, one, line);
+            }
+        }
+
+        let op = match update.get_type() {
+            BinaryType::Plus => OpCode::Add,
+            BinaryType::Minus => OpCode::Subtract,
+            BinaryType::Star => OpCode::Multiply,
+            _ => OpCode::Divide,
+        };
+        self.emit(op, line);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use super::{
+    chunk::{Chunk, Constant, FunctionProto, OpCode},
+    object::format_function,
+};
+
+/// Renders compiled chunks as one instruction per line: offset, source line
+/// (`|` when unchanged from the previous instruction), mnemonic and operands.
+pub struct Disassembler;
+
+impl Disassembler {
+    /// Disassembles `function` followed by every function nested in its constants.
+    pub fn disassemble(function: &FunctionProto) -> String {
+        let mut output = String::new();
+        let mut pending = vec![function];
+
+        while let Some(function) = pending.pop() {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            writeln!(output, "== {} ==", format_function(function)).unwrap();
+
+            let chunk = &function.chunk;
+            let mut offset = 0;
+            while offset < chunk.len() {
+                offset = Self::instruction(chunk, offset, &mut output);
+            }
+
+            // Reversed so nested functions come out in source order.
+            pending.extend(
+                chunk
+                    .constants()
+                    .iter()
+                    .rev()
+                    .filter_map(|constant| match constant {
+                        Constant::Function(function) => Some(function.as_ref()),
+                        _ => None,
+                    }),
+            );
+        }
+
+        output
+    }
+
+    /// Writes the instruction at `offset` and returns the offset of the next one.
+    pub fn instruction(chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+        let lines = chunk.lines();
+        write!(output, "{offset:04} ").unwrap();
+        match offset > 0 && lines[offset] == lines[offset - 1] {
+            true => write!(output, "   | ").unwrap(),
+            false => write!(output, "{:>4} ", lines[offset]).unwrap(),
+        }
+
+        let byte = chunk.code()[offset];
+        let Ok(op) = OpCode::try_from(byte) else {
+            writeln!(output, "Unknown opcode {byte}").unwrap();
+            return offset + 1;
+        };
+
+        match op {
+            OpCode::Constant
+            | OpCode::GetGlobal
+            | OpCode::DefineGlobal
+            | OpCode::SetGlobal
+            | OpCode::GetProperty
+            | OpCode::SetProperty
+            | OpCode::GetSuper
+            | OpCode::Class
+            | OpCode::Method
+            | OpCode::Import => {
+                let index = chunk.read_u16(offset + 1);
+                let constant = &chunk.constants()[index as usize];
+                writeln!(output, "{:<16} {index:4} '{constant}'", op.to_string()).unwrap();
+                offset + 3
+            }
+            OpCode::Closure => {
+                let index = chunk.read_u16(offset + 1);
+                let constant = &chunk.constants()[index as usize];
+                writeln!(output, "{:<16} {index:4} '{constant}'", op.to_string()).unwrap();
+
+                let upvalue_count = match constant {
+                    Constant::Function(function) => function.upvalue_count,
+                    _ => 0,
+                };
+                let mut offset = offset + 3;
+                for _ in 0..upvalue_count {
+                    let kind = match chunk.code()[offset] {
+                        1 => "local",
+                        _ => "upvalue",
+                    };
+                    let index = chunk.code()[offset + 1];
+                    writeln!(
+                        output,
+                        "{offset:04}    |                     {kind} {index}"
+                    )
+                    .unwrap();
+                    offset += 2;
+                }
+
+                offset
+            }
+            OpCode::GetLocal
+            | OpCode::SetLocal
+            | OpCode::GetUpvalue
+            | OpCode::SetUpvalue
+            | OpCode::Call
+            | OpCode::Dup
+            | OpCode::Bury => {
+                let operand = chunk.code()[offset + 1];
+                writeln!(output, "{:<16} {operand:4}", op.to_string()).unwrap();
+                offset + 2
+            }
+            OpCode::BuildList | OpCode::BuildMap | OpCode::BuildString => {
+                let count = chunk.read_u16(offset + 1);
+                writeln!(output, "{:<16} {count:4}", op.to_string()).unwrap();
+                offset + 3
+            }
+            OpCode::Jump | OpCode::JumpIfFalse | OpCode::ForIter | OpCode::Loop | OpCode::Try => {
+                let jump = chunk.read_u16(offset + 1) as usize;
+                let target = match op {
+                    OpCode::Loop => (offset + 3).saturating_sub(jump),
+                    _ => offset + 3 + jump,
+                };
+                writeln!(output, "{:<16} {offset:4} -> {target}", op.to_string()).unwrap();
+                offset + 3
+            }
+            _ => {
+                writeln!(output, "{op}").unwrap();
+                offset + 1
+            }
+        }
+    }
+}
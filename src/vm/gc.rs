@@ -0,0 +1,141 @@
+use std::fmt::Display;
+
+use super::object::{Heap, ObjRef, Object, Upvalue, Value};
+
+/// Live object count that triggers the first collection.
+pub const INITIAL_THRESHOLD: usize = 1024;
+/// After a collection the next one runs once the live count has grown by this factor.
+const GROW_FACTOR: usize = 2;
+
+/// Running totals over every collection of a heap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GcStats {
+    pub collections: usize,
+    pub freed: usize,
+    pub live: usize,
+}
+
+impl Display for GcStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} collections, {} objects freed, {} live",
+            self.collections, self.freed, self.live
+        )
+    }
+}
+
+/// Outcome of a single collection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Collection {
+    pub before: usize,
+    pub after: usize,
+    pub next_gc: usize,
+}
+
+impl Display for Collection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "collected {} objects ({} -> {}), next at {}",
+            self.before - self.after,
+            self.before,
+            self.after,
+            self.next_gc
+        )
+    }
+}
+
+impl Heap {
+    pub fn should_collect(&self) -> bool {
+        self.len() >= self.next_gc
+    }
+
+    /// Mark-and-sweep: frees every object not reachable from `roots`.
+    /// Interned strings are weak, so unreachable ones leave the intern table too.
+    pub fn collect(&mut self, roots: impl IntoIterator<Item = ObjRef>) -> Collection {
+        let before = self.len();
+
+        let mut gray: Vec<ObjRef> = Vec::new();
+        for root in roots {
+            self.mark(root, &mut gray);
+        }
+        while let Some(reference) = gray.pop() {
+            self.blacken(reference, &mut gray);
+        }
+
+        self.sweep();
+
+        let after = self.len();
+        self.next_gc = (after * GROW_FACTOR).max(INITIAL_THRESHOLD);
+
+        Collection {
+            before,
+            after,
+            next_gc: self.next_gc,
+        }
+    }
+
+    fn mark(&mut self, reference: ObjRef, gray: &mut Vec<ObjRef>) {
+        let marked = &mut self.marks[reference.index()];
+        if !*marked {
+            *marked = true;
+            gray.push(reference);
+        }
+    }
+
+    fn mark_value(&mut self, value: Value, gray: &mut Vec<ObjRef>) {
+        if let Value::Obj(reference) = value {
+            self.mark(reference, gray);
+        }
+    }
+
+    fn blacken(&mut self, reference: ObjRef, gray: &mut Vec<ObjRef>) {
+        let mut references = Vec::new();
+        let mut values = Vec::new();
+
+        match self.get(reference) {
+            Object::String(_) | Object::Native(_) | Object::Namespace(_) | Object::Exception(_) => {
+            }
+            Object::Closure(closure) => references.extend(&closure.upvalues),
+            Object::Upvalue(Upvalue::Closed(value)) => values.push(*value),
+            Object::Upvalue(Upvalue::Open(_)) => {}
+            Object::Class(class) => references.extend(class.methods.values()),
+            Object::Instance(instance) => {
+                references.push(instance.class);
+                values.extend(instance.fields.values());
+            }
+            Object::BoundMethod(bound) => {
+                references.push(bound.method);
+                values.push(bound.receiver);
+            }
+            Object::List(elements) => values.extend(elements),
+            Object::Map(map) => {
+                for (key, value) in map.iter() {
+                    values.push(Heap::from_key(*key));
+                    values.push(*value);
+                }
+            }
+        }
+
+        for reference in references {
+            self.mark(reference, gray);
+        }
+        for value in values {
+            self.mark_value(value, gray);
+        }
+    }
+
+    fn sweep(&mut self) {
+        let marks = &self.marks;
+        self.strings.retain(|_, reference| marks[reference.index()]);
+
+        for (index, object) in self.objects.iter_mut().enumerate() {
+            if object.is_some() && !self.marks[index] {
+                *object = None;
+                self.free.push(index as u32);
+            }
+            self.marks[index] = false;
+        }
+    }
+}
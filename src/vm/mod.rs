@@ -0,0 +1,1234 @@
+use std::{
+    collections::HashMap,
+    io::{Stdout, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use chunk::{Constant, FunctionProto, OpCode};
+use compiler::Compiler;
+use disassembler::Disassembler;
+use gc::GcStats;
+use object::{
+    BoundMethod, Class, Closure, Exception, Heap, Instance, Namespace, Native, ObjRef, Object,
+    Upvalue, Value,
+};
+
+use crate::{
+    interpreter::{
+        error::{RuntimeError, TraceFrame},
+        value::{bitwise, bitwise_not, modulo},
+    },
+    module::{cycle_error, module_name, Loader},
+    parser::expression::binary::BinaryType,
+    stdlib::{list_index, map, NativeValue, Stdlib, FUNCTIONS},
+    tokenizer::Span,
+};
+
+pub mod bytecode;
+pub mod chunk;
+pub mod compiler;
+pub mod disassembler;
+pub mod gc;
+mod natives;
+pub mod object;
+mod tests;
+
+/// How deep calls may nest, counting the script; the tree-walker shares it.
+pub(crate) const FRAMES_MAX: usize = 1024;
+
+struct CallFrame {
+    closure: ObjRef,
+    function: Rc<FunctionProto>,
+    ip: usize,
+    base: usize,
+    module: usize,
+}
+
+/// An installed `try`: where to resume in which frame, and how far to unwind the stack.
+#[derive(Clone, Copy)]
+struct Handler {
+    frame: usize,
+    ip: usize,
+    stack: usize,
+}
+
+/// The globals of one file. The script given to `interpret` is module 0 and
+/// every imported file gets the next index.
+#[derive(Default)]
+struct Module {
+    path: Option<PathBuf>,
+    globals: HashMap<ObjRef, Value>,
+    /// Set once the file has run to completion.
+    namespace: Option<ObjRef>,
+}
+
+/// Stack-based virtual machine executing compiled chunks. `print` output goes to `W`.
+pub struct Vm<W: Write = Stdout> {
+    heap: Heap,
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+    handlers: Vec<Handler>,
+    /// The value of the `throw` being handled, if the error came from one.
+    thrown: Option<Value>,
+    /// Stack traces of caught errors by the stack slot their value went to,
+    /// so a rethrow after `finally` reports where they were first raised.
+    caught: Vec<(usize, Vec<TraceFrame>)>,
+    modules: Vec<Module>,
+    module_ids: HashMap<PathBuf, usize>,
+    /// Files currently executing, outermost first.
+    loading: Vec<PathBuf>,
+    /// Natives every file's globals start out with.
+    builtins: HashMap<ObjRef, Value>,
+    loader: Loader,
+    open_upvalues: Vec<ObjRef>,
+    init_string: ObjRef,
+    trace: bool,
+    gc_stress: bool,
+    gc_log: bool,
+    gc_stats: GcStats,
+    stdlib: Rc<Stdlib>,
+    output: W,
+}
+
+impl Vm<Stdout> {
+    pub fn new() -> Self {
+        Self::with_output(std::io::stdout())
+    }
+}
+
+impl Default for Vm<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Vm<W> {
+    pub fn with_output(output: W) -> Self {
+        let mut heap = Heap::default();
+        let init_string = heap.intern("init");
+
+        let mut vm = Self {
+            heap,
+            stack: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
+            thrown: None,
+            caught: Vec::new(),
+            modules: vec![Module::default()],
+            module_ids: HashMap::new(),
+            loading: Vec::new(),
+            builtins: HashMap::new(),
+            loader: Loader::default(),
+            open_upvalues: Vec::new(),
+            init_string,
+            trace: false,
+            gc_stress: false,
+            gc_log: false,
+            gc_stats: GcStats::default(),
+            stdlib: Rc::new(Stdlib::default()),
+            output,
+        };
+        for (name, arity, function) in FUNCTIONS {
+            let stdlib = Rc::clone(&vm.stdlib);
+            vm.define_native(name, *arity, move |heap, arguments| {
+                let arguments: Vec<_> = arguments
+                    .iter()
+                    .map(|argument| native_value(heap, *argument))
+                    .collect();
+                function(&stdlib, &arguments).map(|result| from_native_value(heap, result))
+            });
+        }
+        natives::define_list_natives(&mut vm);
+
+        vm
+    }
+
+    /// State of the standard library natives, e.g. to seed `random()`.
+    pub fn stdlib(&self) -> &Stdlib {
+        &self.stdlib
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// The file the script was read from, which its imports are relative to.
+    pub fn set_script_path(&mut self, path: &Path) {
+        self.modules[0].path = Some(Loader::resolve(None, path));
+    }
+
+    /// How imported files are tokenized and optimized.
+    pub fn set_loader(&mut self, loader: Loader) {
+        self.loader = loader;
+    }
+
+    /// Writes the stack and the next instruction to the output before every step.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Collects garbage before every allocation instead of when the heap has grown.
+    pub fn set_gc_stress(&mut self, gc_stress: bool) {
+        self.gc_stress = gc_stress;
+    }
+
+    /// Reports every collection and a summary after each `interpret` on stderr.
+    pub fn set_gc_log(&mut self, gc_log: bool) {
+        self.gc_log = gc_log;
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        GcStats {
+            live: self.heap.len(),
+            ..self.gc_stats
+        }
+    }
+
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&mut Heap, &[Value]) -> Result<Value, String> + 'static,
+    ) {
+        let key = self.intern(name);
+        self.stack.push(Value::Obj(key));
+        let native = self.alloc(Object::Native(Native {
+            name: name.to_string(),
+            arity,
+            function: Rc::new(function),
+        }));
+        self.pop();
+
+        self.modules[0].globals.insert(key, Value::Obj(native));
+        self.builtins.insert(key, Value::Obj(native));
+    }
+
+    pub fn interpret(&mut self, function: Rc<FunctionProto>) -> Result<(), RuntimeError> {
+        let closure = self.alloc(Object::Closure(Closure {
+            function,
+            upvalues: Vec::new(),
+            module: 0,
+        }));
+        self.stack.push(Value::Obj(closure));
+
+        if let Some(path) = self.modules[0].path.clone() {
+            self.loading.push(path);
+        }
+        let result = self.call(closure, 0).and_then(|()| self.run(0));
+        self.loading.clear();
+
+        if result.is_err() {
+            self.stack.clear();
+            self.frames.clear();
+            self.handlers.clear();
+            self.thrown = None;
+            self.caught.clear();
+            self.open_upvalues.clear();
+        }
+        if self.gc_log {
+            eprintln!("[gc] {}", self.gc_stats());
+        }
+
+        result
+    }
+
+    /// Executes until the frame at `depth` returns. Errors raised meanwhile go
+    /// to the innermost handler installed by one of those frames.
+    fn run(&mut self, depth: usize) -> Result<(), RuntimeError> {
+        loop {
+            let Err(err) = self.execute(depth) else {
+                return Ok(());
+            };
+            let err = self.traced(err);
+            let Some(handler) = self.handlers.pop_if(|handler| handler.frame >= depth) else {
+                return Err(err);
+            };
+
+            let line = err.get_span().line;
+            let value = match self.thrown.take() {
+                Some(value) => value,
+                None => Value::Obj(self.alloc(Object::Exception(Exception {
+                    message: Rc::from(err.get_message()),
+                    line,
+                }))),
+            };
+
+            self.frames.truncate(handler.frame + 1);
+            self.close_upvalues(handler.stack);
+            self.stack.truncate(handler.stack);
+            self.caught.retain(|(slot, _)| *slot < handler.stack);
+            self.caught.push((handler.stack, err.trace().to_vec()));
+            self.stack.push(value);
+            self.stack.push(Value::Number(line as f64));
+            self.frame_mut().ip = handler.ip;
+        }
+    }
+
+    fn execute(&mut self, depth: usize) -> Result<(), RuntimeError> {
+        loop {
+            if self.trace {
+                self.trace_instruction()
+                    .map_err(|err| self.error(&err.to_string()))?;
+            }
+
+            let byte = self.read_byte();
+            let op = OpCode::try_from(byte)
+                .map_err(|byte| self.error(&format!("Unknown opcode {byte}.")))?;
+
+            match op {
+                OpCode::Constant => {
+                    let value = match self.read_constant() {
+                        Constant::Number(value) => Value::Number(value),
+                        Constant::String(string) => Value::Obj(self.intern(&string)),
+                        Constant::Function(_) => Value::Nil,
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::Nil => self.stack.push(Value::Nil),
+                OpCode::True => self.stack.push(Value::Bool(true)),
+                OpCode::False => self.stack.push(Value::Bool(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::Dup => {
+                    let count = self.read_byte() as usize;
+                    let start = self.stack.len() - count;
+                    self.stack.extend_from_within(start..);
+                }
+                OpCode::Bury => {
+                    let depth = self.read_byte() as usize;
+                    let value = self.pop();
+                    self.stack.insert(self.stack.len() - depth, value);
+                }
+                OpCode::GetLocal => {
+                    let slot = self.read_byte() as usize;
+                    let base = self.frame().base;
+                    self.stack.push(self.stack[base + slot]);
+                }
+                OpCode::SetLocal => {
+                    let slot = self.read_byte() as usize;
+                    let base = self.frame().base;
+                    self.stack[base + slot] = self.peek(0);
+                }
+                OpCode::GetGlobal => {
+                    let name = self.read_string();
+                    match self.globals().get(&name) {
+                        Some(value) => self.stack.push(*value),
+                        None => return Err(self.undefined_variable(name)),
+                    }
+                }
+                OpCode::DefineGlobal => {
+                    let name = self.read_string();
+                    let value = self.pop();
+                    self.globals_mut().insert(name, value);
+                }
+                OpCode::SetGlobal => {
+                    let name = self.read_string();
+                    if !self.globals().contains_key(&name) {
+                        return Err(self.undefined_variable(name));
+                    }
+                    let value = self.peek(0);
+                    self.globals_mut().insert(name, value);
+                }
+                OpCode::GetUpvalue => {
+                    let index = self.read_byte();
+                    let upvalue = self.upvalue(index);
+                    let value = match self.heap.get(upvalue) {
+                        Object::Upvalue(Upvalue::Open(slot)) => self.stack[*slot],
+                        Object::Upvalue(Upvalue::Closed(value)) => *value,
+                        _ => unreachable!("closures only capture upvalues"),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::SetUpvalue => {
+                    let index = self.read_byte();
+                    let upvalue = self.upvalue(index);
+                    let value = self.peek(0);
+                    match self.heap.get_mut(upvalue) {
+                        Object::Upvalue(Upvalue::Open(slot)) => {
+                            let slot = *slot;
+                            self.stack[slot] = value;
+                        }
+                        Object::Upvalue(closed) => *closed = Upvalue::Closed(value),
+                        _ => unreachable!("closures only capture upvalues"),
+                    }
+                }
+                OpCode::GetProperty => {
+                    let name = self.read_string();
+                    if let Some(value) = self.object_property(self.peek(0), name) {
+                        let value = value.ok_or_else(|| self.undefined_property(name))?;
+                        self.pop();
+                        self.stack.push(value);
+                        continue;
+                    }
+
+                    let Some(instance) = self.as_instance(self.peek(0)) else {
+                        return Err(self.error("Only instances have properties."));
+                    };
+
+                    let value = match self.instance(instance).fields.get(&self.string(name)) {
+                        Some(value) => *value,
+                        None => {
+                            self.bind_method(self.instance(instance).class, self.peek(0), name)?
+                        }
+                    };
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::SetProperty => {
+                    let name = self.read_string();
+                    let Some(instance) = self.as_instance(self.peek(1)) else {
+                        return Err(self.error("Only instances have fields."));
+                    };
+
+                    let value = self.pop();
+                    let name = self.string(name);
+                    if let Object::Instance(instance) = self.heap.get_mut(instance) {
+                        instance.fields.insert(name, value);
+                    }
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::GetSuper => {
+                    let name = self.read_string();
+                    let Value::Obj(superclass) = self.peek(0) else {
+                        unreachable!("super is always a class");
+                    };
+
+                    let bound = self.bind_method(superclass, self.peek(1), name)?;
+                    self.pop();
+                    self.pop();
+                    self.stack.push(bound);
+                }
+                OpCode::Equal => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(Value::Bool(a == b));
+                }
+                OpCode::Greater => self.comparison(|a, b| a > b)?,
+                OpCode::GreaterEqual => self.comparison(|a, b| a >= b)?,
+                OpCode::Less => self.comparison(|a, b| a < b)?,
+                OpCode::LessEqual => self.comparison(|a, b| a <= b)?,
+                OpCode::Add => {
+                    let (a, b) = (self.peek(1), self.peek(0));
+
+                    let value = match (a, b) {
+                        (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+                        _ => match (self.heap.as_string(a), self.heap.as_string(b)) {
+                            (Some(a), Some(b)) => {
+                                let concatenated = format!("{a}{b}");
+                                Value::Obj(self.intern(&concatenated))
+                            }
+                            _ => {
+                                return Err(
+                                    self.error("Operands must be two numbers or two strings.")
+                                )
+                            }
+                        },
+                    };
+
+                    self.pop();
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::Subtract => self.arithmetic(|a, b| a - b)?,
+                OpCode::Multiply => self.arithmetic(|a, b| a * b)?,
+                OpCode::Divide => self.arithmetic(|a, b| a / b)?,
+                OpCode::Modulo => self.arithmetic(modulo)?,
+                OpCode::Power => self.arithmetic(f64::powf)?,
+                OpCode::BitAnd => self.bitwise(BinaryType::Ampersand)?,
+                OpCode::BitOr => self.bitwise(BinaryType::Pipe)?,
+                OpCode::BitXor => self.bitwise(BinaryType::Caret)?,
+                OpCode::ShiftLeft => self.bitwise(BinaryType::LessLess)?,
+                OpCode::ShiftRight => self.bitwise(BinaryType::GreaterGreater)?,
+                OpCode::Not => {
+                    let value = self.pop();
+                    self.stack.push(Value::Bool(!value.is_truthy()));
+                }
+                OpCode::Negate => match self.peek(0) {
+                    Value::Number(value) => {
+                        self.pop();
+                        self.stack.push(Value::Number(-value));
+                    }
+                    _ => return Err(self.error("Operand must be a number.")),
+                },
+                OpCode::BitNot => match self.peek(0) {
+                    Value::Number(value) => {
+                        let value = bitwise_not(value).map_err(|msg| self.error(msg))?;
+                        self.pop();
+                        self.stack.push(Value::Number(value));
+                    }
+                    _ => return Err(self.error("Operand must be a number.")),
+                },
+                OpCode::Print => {
+                    let value = self.pop();
+                    let text = self.heap.format(value);
+                    writeln!(self.output, "{text}").map_err(|err| self.error(&err.to_string()))?;
+                }
+                OpCode::Jump => {
+                    let offset = self.read_u16() as usize;
+                    self.frame_mut().ip += offset;
+                }
+                OpCode::JumpIfFalse => {
+                    let offset = self.read_u16() as usize;
+                    if !self.peek(0).is_truthy() {
+                        self.frame_mut().ip += offset;
+                    }
+                }
+                OpCode::Loop => {
+                    let offset = self.read_u16() as usize;
+                    self.frame_mut().ip -= offset;
+                }
+                OpCode::Call => {
+                    let count = self.read_byte() as usize;
+                    self.call_value(self.peek(count), count)?;
+                }
+                OpCode::Closure => {
+                    let Constant::Function(function) = self.read_constant() else {
+                        unreachable!("closure operand is a function");
+                    };
+
+                    let mut upvalues = Vec::with_capacity(function.upvalue_count);
+                    for _ in 0..function.upvalue_count {
+                        let is_local = self.read_byte() == 1;
+                        let index = self.read_byte();
+
+                        upvalues.push(match is_local {
+                            true => self.capture_upvalue(self.frame().base + index as usize),
+                            false => self.upvalue(index),
+                        });
+                    }
+
+                    let closure = self.alloc(Object::Closure(Closure {
+                        function,
+                        upvalues,
+                        module: self.frame().module,
+                    }));
+                    self.stack.push(Value::Obj(closure));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    let frame = self.frames.pop().expect("call frame");
+                    self.close_upvalues(frame.base);
+
+                    if self.frames.len() == depth {
+                        self.stack.truncate(frame.base);
+                        return Ok(());
+                    }
+
+                    self.stack.truncate(frame.base);
+                    self.stack.push(result);
+                }
+                OpCode::Class => {
+                    let name = self.read_string();
+                    let class = self.alloc(Object::Class(Class {
+                        name: self.string(name),
+                        methods: HashMap::new(),
+                    }));
+                    self.stack.push(Value::Obj(class));
+                }
+                OpCode::Inherit => {
+                    let Some(superclass) = self.as_class(self.peek(1)) else {
+                        return Err(self.error("Superclass must be a class."));
+                    };
+                    let methods = self.class(superclass).methods.clone();
+
+                    if let Value::Obj(subclass) = self.peek(0) {
+                        if let Object::Class(subclass) = self.heap.get_mut(subclass) {
+                            subclass.methods.extend(methods);
+                        }
+                    }
+                    self.pop();
+                }
+                OpCode::Method => {
+                    let name = self.read_string();
+                    let name = self.string(name);
+
+                    if let (Value::Obj(method), Value::Obj(class)) = (self.peek(0), self.peek(1)) {
+                        if let Object::Class(class) = self.heap.get_mut(class) {
+                            class.methods.insert(name, method);
+                        }
+                    }
+                    self.pop();
+                }
+                OpCode::BuildList => {
+                    let count = self.read_u16() as usize;
+                    let start = self.stack.len() - count;
+
+                    // The elements stay on the stack until the list holds them.
+                    let elements = self.stack[start..].to_vec();
+                    let list = self.alloc(Object::List(elements));
+                    self.stack.truncate(start);
+                    self.stack.push(Value::Obj(list));
+                }
+                OpCode::BuildString => {
+                    let count = self.read_u16() as usize;
+                    let start = self.stack.len() - count;
+
+                    let string: String = self.stack[start..]
+                        .iter()
+                        .map(|part| self.heap.format(*part))
+                        .collect();
+                    let string = self.intern(&string);
+                    self.stack.truncate(start);
+                    self.stack.push(Value::Obj(string));
+                }
+                OpCode::BuildMap => {
+                    let count = self.read_u16() as usize;
+                    let start = self.stack.len() - 2 * count;
+
+                    let mut map = map::Map::default();
+                    for entry in self.stack[start..].chunks(2) {
+                        let key = self.heap.to_key(entry[0]).map_err(|msg| self.error(&msg))?;
+                        map.insert(key, entry[1]);
+                    }
+
+                    // The entries stay on the stack until the map holds them.
+                    let map = self.alloc(Object::Map(map));
+                    self.stack.truncate(start);
+                    self.stack.push(Value::Obj(map));
+                }
+                OpCode::GetIndex => {
+                    let element = self
+                        .get_index(self.peek(1), self.peek(0))
+                        .map_err(|msg| self.error(&msg))?;
+
+                    self.pop();
+                    self.pop();
+                    self.stack.push(element);
+                }
+                OpCode::SetIndex => {
+                    let value = self.peek(0);
+                    self.set_index(self.peek(2), self.peek(1), value)
+                        .map_err(|msg| self.error(&msg))?;
+
+                    self.pop();
+                    self.pop();
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::Import => {
+                    let Constant::String(path) = self.read_constant() else {
+                        unreachable!("import operand is a string");
+                    };
+
+                    let namespace = self.import(&path)?;
+                    self.stack.push(Value::Obj(namespace));
+                }
+                OpCode::ImportNames => {
+                    let Some(module) = self.as_namespace(self.peek(0)) else {
+                        unreachable!("ImportNames follows Import");
+                    };
+
+                    let exports: Vec<(ObjRef, Value)> = self.modules[module]
+                        .globals
+                        .iter()
+                        .filter(|(name, value)| self.builtins.get(name) != Some(value))
+                        .map(|(name, value)| (*name, *value))
+                        .collect();
+                    self.globals_mut().extend(exports);
+                    self.pop();
+                }
+                OpCode::Throw => {
+                    let value = self.pop();
+                    self.thrown = Some(value);
+
+                    // A rethrown error value is reported where it was first raised.
+                    return Err(match value {
+                        Value::Obj(reference) => match self.heap.get(reference) {
+                            Object::Exception(exception) => RuntimeError::new(
+                                &exception.message,
+                                Span::new(exception.line, 0, 0),
+                            ),
+                            _ => self.error(&self.heap.format(value)),
+                        },
+                        _ => self.error(&self.heap.format(value)),
+                    });
+                }
+                OpCode::Try => {
+                    let offset = self.read_u16() as usize;
+                    let frame = self.frames.len() - 1;
+                    self.handlers.push(Handler {
+                        frame,
+                        ip: self.frame().ip + offset,
+                        stack: self.stack.len(),
+                    });
+                }
+                OpCode::EndTry => {
+                    self.handlers.pop();
+                }
+                OpCode::Rethrow => {
+                    let Value::Number(line) = self.pop() else {
+                        unreachable!("handlers push the line of the error");
+                    };
+                    let value = self.pop();
+                    self.thrown = Some(value);
+
+                    let mut err =
+                        RuntimeError::new(&self.heap.format(value), Span::new(line as usize, 0, 0));
+                    // Anything caught above the hidden locals is gone by now.
+                    let top = self.stack.len();
+                    self.caught.retain(|(slot, _)| *slot < top);
+                    if let Some((_, trace)) = self.caught.pop() {
+                        err.set_trace(trace);
+                    }
+                    return Err(err);
+                }
+                OpCode::ForIter => {
+                    let offset = self.read_u16() as usize;
+                    let index = self.pop();
+                    let sequence = self.pop();
+                    let Some(list) = self.as_list(sequence) else {
+                        return Err(self.error("Can only iterate over lists."));
+                    };
+
+                    let element = match index {
+                        Value::Number(index) => self.list(list).get(index as usize).copied(),
+                        _ => None,
+                    };
+                    match element {
+                        Some(element) => self.stack.push(element),
+                        None => self.frame_mut().ip += offset,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs the file at `path` in its own globals the first time it is
+    /// imported and returns its namespace.
+    fn import(&mut self, path: &str) -> Result<ObjRef, RuntimeError> {
+        let importer = self.modules[self.frame().module].path.clone();
+        let path = Loader::resolve(importer.as_deref(), path);
+
+        if let Some(namespace) = self
+            .module_ids
+            .get(&path)
+            .and_then(|id| self.modules[*id].namespace)
+        {
+            return Ok(namespace);
+        }
+        if self.loading.contains(&path) {
+            return Err(self.error(&cycle_error(&self.loading, &path)));
+        }
+
+        let program = self.loader.load(&path).map_err(|msg| self.error(&msg))?;
+        let function = Compiler::compile(program.ast()).map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            self.error(&format!(
+                "Can't import '{}':\n{}",
+                module_name(&path),
+                errors.join("\n")
+            ))
+        })?;
+
+        let module = self.modules.len();
+        self.modules.push(Module {
+            path: Some(path.clone()),
+            globals: self.builtins.clone(),
+            namespace: None,
+        });
+        let closure = self.alloc(Object::Closure(Closure {
+            function,
+            upvalues: Vec::new(),
+            module,
+        }));
+        self.stack.push(Value::Obj(closure));
+
+        let depth = self.frames.len();
+        self.call(closure, 0)?;
+        self.loading.push(path.clone());
+        let result = self.run(depth);
+        self.loading.pop();
+        result?;
+
+        let namespace = self.alloc(Object::Namespace(Namespace {
+            name: module_name(&path),
+            module,
+        }));
+        self.modules[module].namespace = Some(namespace);
+        self.module_ids.insert(path, module);
+
+        Ok(namespace)
+    }
+
+    fn trace_instruction(&mut self) -> std::io::Result<()> {
+        let mut line = String::from("          ");
+        for value in &self.stack {
+            line.push_str(&format!("[ {} ]", self.heap.format(*value)));
+        }
+        line.push('\n');
+
+        let frame = self.frame();
+        Disassembler::instruction(&frame.function.chunk, frame.ip, &mut line);
+
+        write!(self.output, "{line}")
+    }
+
+    fn call_value(&mut self, callee: Value, count: usize) -> Result<(), RuntimeError> {
+        let Value::Obj(reference) = callee else {
+            return Err(self.error("Can only call functions and classes."));
+        };
+
+        match self.heap.get(reference) {
+            Object::Closure(_) => self.call(reference, count),
+            Object::Native(native) => {
+                let (arity, function) = (native.arity, Rc::clone(&native.function));
+                self.check_arity(arity, count)?;
+
+                let arguments = self.stack.split_off(self.stack.len() - count);
+                let result =
+                    function(&mut self.heap, &arguments).map_err(|msg| self.error(&msg))?;
+
+                self.pop();
+                self.stack.push(result);
+                Ok(())
+            }
+            Object::Class(class) => {
+                let initializer = class.methods.get(&self.string(self.init_string)).copied();
+                let instance = self.alloc(Object::Instance(Instance {
+                    class: reference,
+                    fields: HashMap::new(),
+                }));
+
+                let slot = self.stack.len() - count - 1;
+                self.stack[slot] = Value::Obj(instance);
+
+                match initializer {
+                    Some(initializer) => self.call(initializer, count),
+                    None => self.check_arity(0, count),
+                }
+            }
+            Object::BoundMethod(bound) => {
+                let (receiver, method) = (bound.receiver, bound.method);
+
+                let slot = self.stack.len() - count - 1;
+                self.stack[slot] = receiver;
+
+                self.call(method, count)
+            }
+            _ => Err(self.error("Can only call functions and classes.")),
+        }
+    }
+
+    fn call(&mut self, closure: ObjRef, count: usize) -> Result<(), RuntimeError> {
+        let (function, module) = match self.heap.get(closure) {
+            Object::Closure(closure) => (Rc::clone(&closure.function), closure.module),
+            _ => return Err(self.error("Can only call functions and classes.")),
+        };
+        self.check_arity(function.arity, count)?;
+
+        if self.frames.len() == FRAMES_MAX {
+            return Err(self.error("Stack overflow."));
+        }
+
+        self.frames.push(CallFrame {
+            closure,
+            function,
+            ip: 0,
+            base: self.stack.len() - count - 1,
+            module,
+        });
+
+        Ok(())
+    }
+
+    fn check_arity(&self, arity: usize, count: usize) -> Result<(), RuntimeError> {
+        match arity == count {
+            true => Ok(()),
+            false => Err(self.error(&format!("Expected {arity} arguments but got {count}."))),
+        }
+    }
+
+    fn bind_method(
+        &mut self,
+        class: ObjRef,
+        receiver: Value,
+        name: ObjRef,
+    ) -> Result<Value, RuntimeError> {
+        let key = self.string(name);
+
+        let Some(method) = self.class(class).methods.get(&key).copied() else {
+            return Err(self.undefined_property(name));
+        };
+
+        let bound = self.alloc(Object::BoundMethod(BoundMethod { receiver, method }));
+
+        Ok(Value::Obj(bound))
+    }
+
+    /// Allocates `object`, collecting garbage first when due. Anything `object`
+    /// refers to must already be reachable from the roots.
+    fn alloc(&mut self, object: Object) -> ObjRef {
+        if self.gc_stress || self.heap.should_collect() {
+            self.collect_garbage();
+        }
+
+        self.heap.alloc(object)
+    }
+
+    fn intern(&mut self, string: &str) -> ObjRef {
+        if !self.heap.is_interned(string) && (self.gc_stress || self.heap.should_collect()) {
+            self.collect_garbage();
+        }
+
+        self.heap.intern(string)
+    }
+
+    /// Roots are the stack, every file's globals and namespace, open upvalues
+    /// and the closures of active frames.
+    fn collect_garbage(&mut self) {
+        let globals = self.modules.iter().flat_map(|module| &module.globals);
+        let roots = self
+            .stack
+            .iter()
+            .chain(globals.clone().map(|(_, value)| value))
+            .chain(self.builtins.values())
+            .chain(&self.thrown)
+            .filter_map(|value| match value {
+                Value::Obj(reference) => Some(*reference),
+                _ => None,
+            })
+            .chain(globals.map(|(name, _)| *name))
+            .chain(self.builtins.keys().copied())
+            .chain(self.modules.iter().filter_map(|module| module.namespace))
+            .chain(self.open_upvalues.iter().copied())
+            .chain(self.frames.iter().map(|frame| frame.closure))
+            .chain([self.init_string])
+            .collect::<Vec<_>>();
+
+        let collection = self.heap.collect(roots);
+        self.gc_stats.collections += 1;
+        self.gc_stats.freed += collection.before - collection.after;
+
+        if self.gc_log {
+            eprintln!("[gc] {collection}");
+        }
+    }
+
+    /// Reuses the open upvalue for a stack slot so every closure capturing a
+    /// variable shares it.
+    fn capture_upvalue(&mut self, slot: usize) -> ObjRef {
+        let existing = self.open_upvalues.iter().copied().find(|upvalue| {
+            matches!(self.heap.get(*upvalue), Object::Upvalue(Upvalue::Open(open)) if *open == slot)
+        });
+
+        existing.unwrap_or_else(|| {
+            let upvalue = self.alloc(Object::Upvalue(Upvalue::Open(slot)));
+            self.open_upvalues.push(upvalue);
+            upvalue
+        })
+    }
+
+    /// Moves every captured variable at or above `from` off the stack.
+    fn close_upvalues(&mut self, from: usize) {
+        let (heap, stack) = (&mut self.heap, &self.stack);
+
+        self.open_upvalues.retain(|upvalue| {
+            let object = heap.get_mut(*upvalue);
+
+            match *object {
+                Object::Upvalue(Upvalue::Open(slot)) if slot >= from => {
+                    *object = Object::Upvalue(Upvalue::Closed(stack[slot]));
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+
+    fn upvalue(&self, index: u8) -> ObjRef {
+        match self.heap.get(self.frame().closure) {
+            Object::Closure(closure) => closure.upvalues[index as usize],
+            _ => unreachable!("frames always run closures"),
+        }
+    }
+
+    fn arithmetic(&mut self, operation: fn(f64, f64) -> f64) -> Result<(), RuntimeError> {
+        let (a, b) = self.number_operands()?;
+        self.stack.push(Value::Number(operation(a, b)));
+        Ok(())
+    }
+
+    /// The bitwise operators share the tree-walker's integer conversion.
+    fn bitwise(&mut self, binary_type: BinaryType) -> Result<(), RuntimeError> {
+        let (a, b) = self.number_operands()?;
+        let value = bitwise(&binary_type, a, b).map_err(|msg| self.error(msg))?;
+        self.stack.push(Value::Number(value));
+        Ok(())
+    }
+
+    fn comparison(&mut self, operation: fn(f64, f64) -> bool) -> Result<(), RuntimeError> {
+        let (a, b) = self.number_operands()?;
+        self.stack.push(Value::Bool(operation(a, b)));
+        Ok(())
+    }
+
+    fn number_operands(&mut self) -> Result<(f64, f64), RuntimeError> {
+        match (self.peek(1), self.peek(0)) {
+            (Value::Number(a), Value::Number(b)) => {
+                self.pop();
+                self.pop();
+                Ok((a, b))
+            }
+            _ => Err(self.error("Operands must be numbers.")),
+        }
+    }
+
+    /// Globals of the file the current function was declared in.
+    fn globals(&self) -> &HashMap<ObjRef, Value> {
+        let module = self.frames.last().map_or(0, |frame| frame.module);
+        &self.modules[module].globals
+    }
+
+    fn globals_mut(&mut self) -> &mut HashMap<ObjRef, Value> {
+        let module = self.frames.last().map_or(0, |frame| frame.module);
+        &mut self.modules[module].globals
+    }
+
+    fn frame(&self) -> &CallFrame {
+        self.frames.last().expect("call frame")
+    }
+
+    fn frame_mut(&mut self) -> &mut CallFrame {
+        self.frames.last_mut().expect("call frame")
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let frame = self.frame_mut();
+        let byte = frame.function.chunk.code()[frame.ip];
+        frame.ip += 1;
+        byte
+    }
+
+    fn read_u16(&mut self) -> u16 {
+        let frame = self.frame_mut();
+        let value = frame.function.chunk.read_u16(frame.ip);
+        frame.ip += 2;
+        value
+    }
+
+    fn read_constant(&mut self) -> Constant {
+        let index = self.read_u16() as usize;
+        self.frame().function.chunk.constants()[index].clone()
+    }
+
+    fn read_string(&mut self) -> ObjRef {
+        match self.read_constant() {
+            Constant::String(string) => self.intern(&string),
+            _ => self.intern(""),
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    fn peek(&self, distance: usize) -> Value {
+        self.stack[self.stack.len() - 1 - distance]
+    }
+
+    fn string(&self, reference: ObjRef) -> Rc<str> {
+        self.heap
+            .as_string(Value::Obj(reference))
+            .cloned()
+            .unwrap_or_else(|| Rc::from(""))
+    }
+
+    fn as_instance(&self, value: Value) -> Option<ObjRef> {
+        match value {
+            Value::Obj(reference) if matches!(self.heap.get(reference), Object::Instance(_)) => {
+                Some(reference)
+            }
+            _ => None,
+        }
+    }
+
+    fn as_namespace(&self, value: Value) -> Option<usize> {
+        match value {
+            Value::Obj(reference) => match self.heap.get(reference) {
+                Object::Namespace(namespace) => Some(namespace.module),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Looks `name` up on a namespace or error value, or returns `None` for
+    /// any other object.
+    fn object_property(&mut self, object: Value, name: ObjRef) -> Option<Option<Value>> {
+        let Value::Obj(reference) = object else {
+            return None;
+        };
+
+        match self.heap.get(reference) {
+            Object::Namespace(namespace) => {
+                Some(self.modules[namespace.module].globals.get(&name).copied())
+            }
+            Object::Exception(exception) => {
+                let (message, line) = (Rc::clone(&exception.message), exception.line);
+
+                match &*self.string(name) {
+                    "message" => Some(Some(Value::Obj(self.intern(&message)))),
+                    "line" => Some(Some(Value::Number(line as f64))),
+                    _ => Some(None),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn as_class(&self, value: Value) -> Option<ObjRef> {
+        match value {
+            Value::Obj(reference) if matches!(self.heap.get(reference), Object::Class(_)) => {
+                Some(reference)
+            }
+            _ => None,
+        }
+    }
+
+    fn as_list(&self, value: Value) -> Option<ObjRef> {
+        match value {
+            Value::Obj(reference) if matches!(self.heap.get(reference), Object::List(_)) => {
+                Some(reference)
+            }
+            _ => None,
+        }
+    }
+
+    fn list(&self, reference: ObjRef) -> &[Value] {
+        match self.heap.get(reference) {
+            Object::List(elements) => elements,
+            _ => unreachable!("checked by as_list"),
+        }
+    }
+
+    fn get_index(&self, object: Value, index: Value) -> Result<Value, String> {
+        let Value::Obj(reference) = object else {
+            return Err("Can only index lists and maps.".to_string());
+        };
+
+        match self.heap.get(reference) {
+            Object::List(elements) => Ok(elements[list_position(elements, index)?]),
+            Object::Map(map) => map
+                .get(&self.heap.to_key(index)?)
+                .copied()
+                .ok_or_else(|| format!("Undefined key {}.", self.heap.element(index))),
+            _ => Err("Can only index lists and maps.".to_string()),
+        }
+    }
+
+    fn set_index(&mut self, object: Value, index: Value, value: Value) -> Result<(), String> {
+        let Value::Obj(reference) = object else {
+            return Err("Can only index lists and maps.".to_string());
+        };
+
+        let key = match self.heap.get(reference) {
+            Object::Map(_) => Some(self.heap.to_key(index)?),
+            _ => None,
+        };
+        match (self.heap.get_mut(reference), key) {
+            (Object::List(elements), _) => {
+                let position = list_position(elements, index)?;
+                elements[position] = value;
+            }
+            (Object::Map(map), Some(key)) => map.insert(key, value),
+            _ => return Err("Can only index lists and maps.".to_string()),
+        }
+
+        Ok(())
+    }
+
+    fn instance(&self, reference: ObjRef) -> &Instance {
+        match self.heap.get(reference) {
+            Object::Instance(instance) => instance,
+            _ => unreachable!("checked by as_instance"),
+        }
+    }
+
+    fn class(&self, reference: ObjRef) -> &Class {
+        match self.heap.get(reference) {
+            Object::Class(class) => class,
+            _ => unreachable!("checked by as_class"),
+        }
+    }
+
+    fn undefined_property(&self, name: ObjRef) -> RuntimeError {
+        self.error(&format!("Undefined property '{}'.", self.string(name)))
+    }
+
+    fn undefined_variable(&self, name: ObjRef) -> RuntimeError {
+        self.error(&format!("Undefined variable '{}'.", self.string(name)))
+    }
+
+    /// Frames are only unwound once a handler is found, so until then they are
+    /// still the ones the error was raised in.
+    fn traced(&self, mut err: RuntimeError) -> RuntimeError {
+        if err.trace().is_empty() {
+            let trace = self
+                .frames
+                .iter()
+                .rev()
+                .map(|frame| {
+                    let function = &frame.function;
+                    TraceFrame::new(
+                        (!function.name.is_empty()).then_some(function.name.as_str()),
+                        self.modules[frame.module].path.as_deref(),
+                        function.chunk.lines()[frame.ip.saturating_sub(1)],
+                    )
+                })
+                .collect();
+            err.set_trace(trace);
+        }
+        err
+    }
+
+    /// A runtime error located at the instruction currently executing.
+    fn error(&self, msg: &str) -> RuntimeError {
+        let line = self
+            .frames
+            .last()
+            .map(|frame| frame.function.chunk.lines()[frame.ip.saturating_sub(1)])
+            .unwrap_or_default();
+
+        RuntimeError::new(msg, Span::new(line, 0, 0))
+    }
+}
+
+fn list_position(elements: &[Value], index: Value) -> Result<usize, String> {
+    let Value::Number(index) = index else {
+        return Err("List index must be an integer.".to_string());
+    };
+
+    list_index(index, elements.len(), false)
+}
+
+fn native_value(heap: &Heap, value: Value) -> NativeValue {
+    match value {
+        Value::Nil => NativeValue::Nil,
+        Value::Bool(value) => NativeValue::Bool(value),
+        Value::Number(value) => NativeValue::Number(value),
+        Value::Obj(reference) => match heap.get(reference) {
+            Object::String(string) => NativeValue::String(Rc::clone(string)),
+            _ => NativeValue::Object {
+                type_name: heap.type_name(value),
+                display: heap.format(value),
+            },
+        },
+    }
+}
+
+fn from_native_value(heap: &mut Heap, value: NativeValue) -> Value {
+    match value {
+        NativeValue::Nil => Value::Nil,
+        NativeValue::Bool(value) => Value::Bool(value),
+        NativeValue::Number(value) => Value::Number(value),
+        NativeValue::String(string) => Value::Obj(heap.intern(&string)),
+        NativeValue::Object { display, .. } => Value::Obj(heap.intern(&display)),
+    }
+}
@@ -0,0 +1,162 @@
+use std::{io::Write, rc::Rc};
+
+use super::{
+    object::{Heap, Key, Object, Value},
+    Vm,
+};
+use crate::stdlib::{list_index, list_slice, map::Map};
+
+/// Adds the list and map natives, wrapping the stdlib `len` so it also counts
+/// their elements.
+pub(super) fn define_list_natives<W: Write>(vm: &mut Vm<W>) {
+    let key = vm.intern("len");
+    let string_len = match vm.builtins.get(&key) {
+        Some(Value::Obj(len)) => match vm.heap.get(*len) {
+            Object::Native(native) => Some(Rc::clone(&native.function)),
+            _ => None,
+        },
+        _ => None,
+    };
+    vm.define_native("len", 1, move |heap, arguments| {
+        if let Ok(elements) = list(heap, "len", arguments[0]) {
+            return Ok(Value::Number(elements.len() as f64));
+        }
+        if let Ok(map) = map(heap, "len", arguments[0]) {
+            return Ok(Value::Number(map.len() as f64));
+        }
+
+        match &string_len {
+            Some(string_len) => string_len(heap, arguments),
+            None => Err("len() expects a string, list or map.".to_string()),
+        }
+    });
+
+    vm.define_native("push", 2, |heap, arguments| {
+        list_mut(heap, "push", arguments[0])?.push(arguments[1]);
+        Ok(Value::Nil)
+    });
+
+    vm.define_native("pop", 1, |heap, arguments| {
+        list_mut(heap, "pop", arguments[0])?
+            .pop()
+            .ok_or_else(|| "Can't pop from an empty list.".to_string())
+    });
+
+    vm.define_native("insert", 3, |heap, arguments| {
+        let index = index("insert", arguments[1])?;
+        let elements = list_mut(heap, "insert", arguments[0])?;
+
+        let index = list_index(index, elements.len(), true)?;
+        elements.insert(index, arguments[2]);
+
+        Ok(Value::Nil)
+    });
+
+    vm.define_native("remove", 2, |heap, arguments| {
+        let index = index("remove", arguments[1])?;
+        let elements = list_mut(heap, "remove", arguments[0])?;
+
+        let index = list_index(index, elements.len(), false)?;
+
+        Ok(elements.remove(index))
+    });
+
+    vm.define_native("slice", 3, |heap, arguments| {
+        let start = index("slice", arguments[1])?;
+        let end = index("slice", arguments[2])?;
+        let elements = list(heap, "slice", arguments[0])?;
+
+        let range = list_slice(start, end, elements.len())?;
+        let slice = elements[range].to_vec();
+
+        Ok(Value::Obj(heap.alloc(Object::List(slice))))
+    });
+
+    vm.define_native("keys", 1, |heap, arguments| {
+        let keys = map(heap, "keys", arguments[0])?
+            .keys()
+            .map(|key| Heap::from_key(*key))
+            .collect();
+
+        Ok(Value::Obj(heap.alloc(Object::List(keys))))
+    });
+
+    vm.define_native("values", 1, |heap, arguments| {
+        let values = map(heap, "values", arguments[0])?
+            .values()
+            .copied()
+            .collect();
+
+        Ok(Value::Obj(heap.alloc(Object::List(values))))
+    });
+
+    vm.define_native("has", 2, |heap, arguments| {
+        let map = map(heap, "has", arguments[0])?;
+        let key = heap.to_key(arguments[1])?;
+
+        Ok(Value::Bool(map.contains_key(&key)))
+    });
+
+    vm.define_native("delete", 2, |heap, arguments| {
+        map(heap, "delete", arguments[0])?;
+        let key = heap.to_key(arguments[1])?;
+        let removed = map_mut(heap, "delete", arguments[0])?.remove(&key);
+
+        Ok(removed.unwrap_or_default())
+    });
+}
+
+fn list<'a>(heap: &'a Heap, name: &str, value: Value) -> Result<&'a Vec<Value>, String> {
+    match value {
+        Value::Obj(reference) => match heap.get(reference) {
+            Object::List(elements) => Ok(elements),
+            _ => Err(format!("{name}() expects a list.")),
+        },
+        _ => Err(format!("{name}() expects a list.")),
+    }
+}
+
+fn list_mut<'a>(
+    heap: &'a mut Heap,
+    name: &str,
+    value: Value,
+) -> Result<&'a mut Vec<Value>, String> {
+    match value {
+        Value::Obj(reference) => match heap.get_mut(reference) {
+            Object::List(elements) => Ok(elements),
+            _ => Err(format!("{name}() expects a list.")),
+        },
+        _ => Err(format!("{name}() expects a list.")),
+    }
+}
+
+fn map<'a>(heap: &'a Heap, name: &str, value: Value) -> Result<&'a Map<Key, Value>, String> {
+    match value {
+        Value::Obj(reference) => match heap.get(reference) {
+            Object::Map(map) => Ok(map),
+            _ => Err(format!("{name}() expects a map.")),
+        },
+        _ => Err(format!("{name}() expects a map.")),
+    }
+}
+
+fn map_mut<'a>(
+    heap: &'a mut Heap,
+    name: &str,
+    value: Value,
+) -> Result<&'a mut Map<Key, Value>, String> {
+    match value {
+        Value::Obj(reference) => match heap.get_mut(reference) {
+            Object::Map(map) => Ok(map),
+            _ => Err(format!("{name}() expects a map.")),
+        },
+        _ => Err(format!("{name}() expects a map.")),
+    }
+}
+
+fn index(name: &str, value: Value) -> Result<f64, String> {
+    match value {
+        Value::Number(index) => Ok(index),
+        _ => Err(format!("{name}() expects a number index.")),
+    }
+}
@@ -0,0 +1,299 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::chunk::FunctionProto;
+use crate::stdlib::map::{self, MapKey, KEY_ERROR};
+
+/// Natives get the heap so they can allocate the strings they return. Nothing
+/// is collected while one runs.
+pub type NativeFn = Rc<dyn Fn(&mut Heap, &[Value]) -> Result<Value, String>>;
+
+/// Strings are interned, so a map keys them by handle.
+pub type Key = MapKey<ObjRef>;
+
+/// Handle to an object living in the VM heap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObjRef(pub(super) u32);
+
+impl ObjRef {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Nil,
+    Bool(bool),
+    Number(f64),
+    Obj(ObjRef),
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub function: Rc<FunctionProto>,
+    pub upvalues: Vec<ObjRef>,
+    /// Index of the file whose globals the function reads and writes.
+    pub module: usize,
+}
+
+/// A captured variable: still on the stack at the given slot while open,
+/// moved into the heap once that slot goes away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
+}
+
+pub struct Native {
+    pub name: String,
+    pub arity: usize,
+    pub function: NativeFn,
+}
+
+impl std::fmt::Debug for Native {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Native")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: Rc<str>,
+    pub methods: HashMap<Rc<str>, ObjRef>,
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub class: ObjRef,
+    pub fields: HashMap<Rc<str>, Value>,
+}
+
+#[derive(Debug)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: ObjRef,
+}
+
+/// The globals of an imported file, as bound by `import "path" as name;`.
+#[derive(Debug)]
+pub struct Namespace {
+    pub name: String,
+    pub module: usize,
+}
+
+/// A runtime error caught by `catch`, with `message` and `line` properties.
+#[derive(Debug)]
+pub struct Exception {
+    pub message: Rc<str>,
+    pub line: usize,
+}
+
+#[derive(Debug)]
+pub enum Object {
+    String(Rc<str>),
+    Closure(Closure),
+    Upvalue(Upvalue),
+    Native(Native),
+    Class(Class),
+    Instance(Instance),
+    BoundMethod(BoundMethod),
+    List(Vec<Value>),
+    Map(map::Map<Key, Value>),
+    Namespace(Namespace),
+    Exception(Exception),
+}
+
+/// Arena of heap objects addressed by `ObjRef`. Strings are interned, so two
+/// equal strings always share one handle and compare equal by reference.
+/// Unreachable objects are reclaimed by [`Heap::collect`].
+#[derive(Debug)]
+pub struct Heap {
+    pub(super) objects: Vec<Option<Object>>,
+    pub(super) marks: Vec<bool>,
+    pub(super) free: Vec<u32>,
+    pub(super) strings: HashMap<Rc<str>, ObjRef>,
+    pub(super) next_gc: usize,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self {
+            objects: Vec::new(),
+            marks: Vec::new(),
+            free: Vec::new(),
+            strings: HashMap::new(),
+            next_gc: super::gc::INITIAL_THRESHOLD,
+        }
+    }
+}
+
+impl Heap {
+    pub fn alloc(&mut self, object: Object) -> ObjRef {
+        match self.free.pop() {
+            Some(index) => {
+                self.objects[index as usize] = Some(object);
+                ObjRef(index)
+            }
+            None => {
+                self.objects.push(Some(object));
+                self.marks.push(false);
+                ObjRef(self.objects.len() as u32 - 1)
+            }
+        }
+    }
+
+    pub fn is_interned(&self, string: &str) -> bool {
+        self.strings.contains_key(string)
+    }
+
+    pub fn intern(&mut self, string: &str) -> ObjRef {
+        if let Some(reference) = self.strings.get(string) {
+            return *reference;
+        }
+
+        let string: Rc<str> = Rc::from(string);
+        let reference = self.alloc(Object::String(Rc::clone(&string)));
+        self.strings.insert(string, reference);
+
+        reference
+    }
+
+    pub fn get(&self, reference: ObjRef) -> &Object {
+        self.objects[reference.index()]
+            .as_ref()
+            .expect("live object")
+    }
+
+    pub fn get_mut(&mut self, reference: ObjRef) -> &mut Object {
+        self.objects[reference.index()]
+            .as_mut()
+            .expect("live object")
+    }
+
+    pub fn as_string(&self, value: Value) -> Option<&Rc<str>> {
+        match value {
+            Value::Obj(reference) => match self.get(reference) {
+                Object::String(string) => Some(string),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn type_name(&self, value: Value) -> &'static str {
+        match value {
+            Value::Nil => "nil",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::Obj(reference) => match self.get(reference) {
+                Object::String(_) => "string",
+                Object::Closure(_) | Object::Native(_) | Object::BoundMethod(_) => "function",
+                Object::Upvalue(_) => "upvalue",
+                Object::Class(_) => "class",
+                Object::Instance(_) => "instance",
+                Object::List(_) => "list",
+                Object::Map(_) => "map",
+                Object::Namespace(_) => "module",
+                Object::Exception(_) => "error",
+            },
+        }
+    }
+
+    pub fn format(&self, value: Value) -> String {
+        match value {
+            Value::Nil => "nil".to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Number(value) => crate::interpreter::value::format_number(value),
+            Value::Obj(reference) => match self.get(reference) {
+                Object::String(string) => string.to_string(),
+                Object::Closure(closure) => format_function(&closure.function),
+                Object::Upvalue(_) => "upvalue".to_string(),
+                Object::Native(_) => "<native fn>".to_string(),
+                Object::Class(class) => class.name.to_string(),
+                Object::Instance(instance) => {
+                    format!("{} instance", self.format(Value::Obj(instance.class)))
+                }
+                Object::BoundMethod(bound) => self.format(Value::Obj(bound.method)),
+                Object::List(elements) => {
+                    let elements: Vec<String> = elements
+                        .iter()
+                        .map(|element| self.element(*element))
+                        .collect();
+
+                    format!("[{}]", elements.join(", "))
+                }
+                Object::Map(map) => {
+                    let entries: Vec<String> = map
+                        .iter()
+                        .map(|(key, value)| {
+                            format!(
+                                "{}: {}",
+                                self.element(Self::from_key(*key)),
+                                self.element(*value)
+                            )
+                        })
+                        .collect();
+
+                    format!("{{{}}}", entries.join(", "))
+                }
+                Object::Namespace(namespace) => format!("<module {}>", namespace.name),
+                Object::Exception(exception) => exception.message.to_string(),
+            },
+        }
+    }
+
+    /// How `value` prints inside a list or map, where strings are quoted.
+    pub fn element(&self, value: Value) -> String {
+        match self.as_string(value) {
+            Some(string) => format!("\"{string}\""),
+            None => self.format(value),
+        }
+    }
+
+    /// The key `value` is stored under in a map.
+    pub fn to_key(&self, value: Value) -> Result<Key, String> {
+        match value {
+            Value::Nil => Ok(MapKey::Nil),
+            Value::Bool(value) => Ok(MapKey::Bool(value)),
+            Value::Number(value) => Ok(MapKey::number(value)),
+            Value::Obj(reference) if self.as_string(value).is_some() => {
+                Ok(MapKey::String(reference))
+            }
+            Value::Obj(_) => Err(KEY_ERROR.to_string()),
+        }
+    }
+
+    pub fn from_key(key: Key) -> Value {
+        match key {
+            MapKey::Nil => Value::Nil,
+            MapKey::Bool(value) => Value::Bool(value),
+            MapKey::Number(bits) => Value::Number(f64::from_bits(bits)),
+            MapKey::String(reference) => Value::Obj(reference),
+        }
+    }
+}
+
+pub fn format_function(function: &FunctionProto) -> String {
+    match function.name.is_empty() {
+        true => "<script>".to_string(),
+        false => format!("<fn {}>", function.name),
+    }
+}
@@ -0,0 +1,457 @@
+#[allow(unused_imports)]
+use crate::{
+    interpreter::{Interpreter, Program, STACK_SIZE},
+    module::Loader,
+    parser::{
+        optimizer::{OptLevel, Optimizer},
+        Parser,
+    },
+    tokenizer::Tokenizer,
+    vm::{
+        bytecode::{Bytecode, BytecodeError, VERSION},
+        chunk::OpCode,
+        compiler::Compiler,
+        disassembler::Disassembler,
+        Vm,
+    },
+};
+
+const _PROGRAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/lox");
+
+/// Output of a program and the runtime error it stopped with, if any.
+type _Outcome = (String, Option<String>);
+
+/// Test programs, including those under `extensions/` that use the syntax
+/// extensions. Files under `modules/` are only run when imported.
+fn _programs() -> Vec<std::path::PathBuf> {
+    let mut paths: Vec<_> = [_PROGRAMS, &format!("{_PROGRAMS}/extensions")]
+        .into_iter()
+        .flat_map(|directory| std::fs::read_dir(directory).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+        .collect();
+    paths.sort();
+
+    paths
+}
+
+fn _program(source: &str) -> std::rc::Rc<Program> {
+    let output = Tokenizer::tokenize_with(source.to_string(), true).unwrap();
+
+    Parser::parse_program_with(output.get_tokens(), true)
+        .and_then(Program::new)
+        .unwrap()
+}
+
+/// A test program that resolves its imports relative to `path`.
+fn _program_at(path: &std::path::Path) -> std::rc::Rc<Program> {
+    let source = std::fs::read_to_string(path).unwrap();
+    let output = Tokenizer::tokenize_with(source, true).unwrap();
+
+    Parser::parse_program_with(output.get_tokens(), true)
+        .and_then(|ast| Program::with_path(ast, path.to_path_buf()))
+        .unwrap()
+}
+
+fn _loader() -> Loader {
+    Loader::new(true, OptLevel::None)
+}
+
+/// Runs `test` with the stack the binary gives the tree-walker, which deep
+/// recursion in the test programs needs.
+fn _with_stack(test: impl FnOnce() + Send + 'static) {
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(test)
+        .unwrap()
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+fn _tree(program: &std::rc::Rc<Program>) -> _Outcome {
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    interpreter.set_loader(_loader());
+    interpreter.set_stack_limit(STACK_SIZE / 2);
+    let error = interpreter.interpret(program).err();
+
+    (
+        String::from_utf8(interpreter.output().clone()).unwrap(),
+        error.map(|err| err.to_string()),
+    )
+}
+
+fn _vm(source: &str) -> _Outcome {
+    _vm_with(&_program(source), |_| {})
+}
+
+fn _vm_with(program: &std::rc::Rc<Program>, configure: impl FnOnce(&mut Vm<Vec<u8>>)) -> _Outcome {
+    let function = Compiler::compile(program.ast()).unwrap();
+    let mut vm = Vm::with_output(Vec::new());
+    vm.set_loader(_loader());
+    if let Some(path) = program.path() {
+        vm.set_script_path(path);
+    }
+    configure(&mut vm);
+    let error = vm.interpret(function).err();
+
+    (
+        String::from_utf8(vm.output().clone()).unwrap(),
+        error.map(|err| err.to_string()),
+    )
+}
+
+/// Expected outcome from `// expect: value` and `// expect runtime error: message` comments,
+/// with `// expect trace: frame` lines for errors raised inside calls.
+fn _expected(source: &str) -> _Outcome {
+    let mut output = String::new();
+    let mut error = None;
+    let mut trace = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        if let Some((_, value)) = line.split_once("// expect: ") {
+            output.push_str(value);
+            output.push('\n');
+        }
+        if let Some((_, message)) = line.split_once("// expect runtime error: ") {
+            error = Some(format!("{message}\n[line {}]", index + 1));
+        }
+        if let Some((_, frame)) = line.split_once("// expect trace: ") {
+            trace.push(frame);
+        }
+    }
+
+    // A stack trace replaces the bare line of the error.
+    if let Some(message) = error.as_mut().filter(|_| !trace.is_empty()) {
+        let message_end = message.rfind('\n').unwrap_or(message.len());
+        message.truncate(message_end);
+        for frame in trace {
+            message.push('\n');
+            message.push_str(frame);
+        }
+    }
+
+    (output, error)
+}
+
+#[test]
+fn backends_agree_on_test_programs() {
+    let paths = _programs();
+    assert!(!paths.is_empty());
+
+    _with_stack(move || {
+        for path in paths {
+            let source = std::fs::read_to_string(&path).unwrap();
+            let expected = _expected(&source);
+            let program = _program_at(&path);
+
+            assert_eq!(
+                _tree(&program),
+                expected,
+                "tree backend: {}",
+                path.display()
+            );
+            assert_eq!(
+                _vm_with(&program, |_| {}),
+                expected,
+                "vm backend: {}",
+                path.display()
+            );
+            assert_eq!(
+                _vm_with(&program, |vm| vm.set_gc_stress(true)),
+                expected,
+                "vm backend with --gc-stress: {}",
+                path.display()
+            );
+        }
+    });
+}
+
+#[test]
+fn optimizer_preserves_test_programs() {
+    for path in _programs() {
+        let source = std::fs::read_to_string(&path).unwrap();
+        let output = Tokenizer::tokenize_with(source.clone(), true).unwrap();
+        let mut ast = Parser::parse_program_with(output.get_tokens(), true).unwrap();
+        Optimizer::optimize(&mut ast, OptLevel::Full);
+
+        let function = Compiler::compile(Program::new(ast).unwrap().ast()).unwrap();
+        let mut vm = Vm::with_output(Vec::new());
+        vm.set_loader(Loader::new(true, OptLevel::Full));
+        vm.set_script_path(&path);
+        let error = vm.interpret(function).err().map(|err| err.to_string());
+        let outcome = (String::from_utf8(vm.output().clone()).unwrap(), error);
+
+        assert_eq!(outcome, _expected(&source), "{}", path.display());
+    }
+}
+
+#[test]
+fn standard_lox_reads_double_minus_as_negations() {
+    let source = "print --(3);\nprint ---(3);\nprint 1--1;\nvar a = 2;\nprint a--a;";
+    let output = Tokenizer::tokenize(source.to_string()).unwrap();
+    let program = Parser::parse_program(output.get_tokens())
+        .and_then(Program::new)
+        .unwrap();
+    let expected = ("3\n-3\n2\n4\n".to_string(), None);
+
+    assert_eq!(_tree(&program), expected);
+    assert_eq!(_vm_with(&program, |_| {}), expected);
+}
+
+#[test]
+fn bytecode_round_trips_test_programs() {
+    for path in _programs() {
+        let source = std::fs::read_to_string(path).unwrap();
+        let function = Compiler::compile(_program(&source).ast()).unwrap();
+        let bytes = Bytecode::serialize(&function);
+        let loaded = Bytecode::deserialize(&bytes).unwrap();
+
+        assert_eq!(bytes[4..6], VERSION.to_be_bytes());
+        assert_eq!(loaded, function);
+    }
+
+    // Files from a build with other opcodes must be rejected by version, not
+    // fail on the first unknown instruction.
+    let opcodes = (0..=u8::MAX)
+        .take_while(|byte| OpCode::try_from(*byte).is_ok())
+        .count();
+    assert_eq!(
+        (VERSION, opcodes),
+        (9, 59),
+        "bump VERSION when the opcode set changes"
+    );
+}
+
+#[test]
+fn bytecode_rejects_bad_files() {
+    let function = Compiler::compile(_program("print \"hi\";").ast()).unwrap();
+    let bytes = Bytecode::serialize(&function);
+
+    let mut corrupted = bytes.clone();
+    corrupted[10] ^= 0x40;
+    assert_eq!(
+        Bytecode::deserialize(&corrupted),
+        Err(BytecodeError::ChecksumMismatch)
+    );
+
+    let mut version = bytes.clone();
+    version[5] = 99;
+    assert_eq!(
+        Bytecode::deserialize(&version),
+        Err(BytecodeError::UnsupportedVersion(99))
+    );
+
+    let mut older = bytes.clone();
+    older[5] = 2;
+    let err = Bytecode::deserialize(&older).unwrap_err();
+    assert_eq!(err, BytecodeError::UnsupportedVersion(2));
+    assert_eq!(
+        err.to_string(),
+        "Unsupported bytecode version 2, expected 9. Recompile the source."
+    );
+
+    assert_eq!(
+        Bytecode::deserialize(b"print 1;"),
+        Err(BytecodeError::NotBytecode)
+    );
+    assert_eq!(
+        Bytecode::deserialize(&bytes[..5]),
+        Err(BytecodeError::Truncated)
+    );
+}
+
+#[test]
+fn stack_overflow() {
+    let (_, error) = _vm("fun f() { f(); }\nf();");
+
+    assert_eq!(
+        error,
+        Some(
+            "Stack overflow.\n\
+[line 1] in f()\n\
+[previous line repeated 1022 more times]\n\
+[line 2] in script"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn closure_counters_are_independent() {
+    let source = "\
+fun counter() {
+    var count = 0;
+    fun next() { count = count + 1; return count; }
+    return next;
+}
+var a = counter();
+var b = counter();
+a(); a();
+print a();
+print b();";
+
+    assert_eq!(_vm(source), ("3\n1\n".to_string(), None));
+}
+
+#[test]
+fn closures_share_captured_variable() {
+    let source = "\
+fun pair() {
+    var value = 1;
+    fun get() { return value; }
+    fun set(v) { value = v; }
+    set(2);
+    print get();
+    value = 3;
+    return get;
+}
+print pair()();";
+
+    assert_eq!(_vm(source), ("2\n3\n".to_string(), None));
+}
+
+#[test]
+fn closures_outlive_their_frames() {
+    let source = "\
+fun make(x) {
+    var y = x * 2;
+    fun add(z) { return x + y + z; }
+    return add;
+}
+var add = make(1);
+fun clobber(a, b, c) { return a + b + c; }
+clobber(100, 200, 300);
+print add(10);";
+
+    assert_eq!(_vm(source), ("13\n".to_string(), None));
+}
+
+#[test]
+fn disassemble_closure_captures() {
+    let function = Compiler::compile(_program("{\n  var a;\n  fun g() { a; }\n}").ast());
+
+    assert_eq!(
+        Disassembler::disassemble(&function.unwrap()),
+        "\
+== <script> ==
+0000    2 OP_NIL
+0001    3 OP_CLOSURE          0 '<fn g>'
+0004    |                     local 1
+0006    4 OP_POP
+0007    | OP_CLOSE_UPVALUE
+0008    | OP_NIL
+0009    | OP_RETURN
+
+== <fn g> ==
+0000    3 OP_GET_UPVALUE      0
+0002    | OP_POP
+0003    | OP_NIL
+0004    | OP_RETURN
+"
+    );
+}
+
+#[test]
+fn disassemble_nested_functions() {
+    let function = Compiler::compile(_program("fun f(a) {\n  return a;\n}\nprint f(\"x\");").ast());
+
+    assert_eq!(
+        Disassembler::disassemble(&function.unwrap()),
+        "\
+== <script> ==
+0000    1 OP_CLOSURE          1 '<fn f>'
+0003    | OP_DEFINE_GLOBAL    0 'f'
+0006    4 OP_GET_GLOBAL       0 'f'
+0009    | OP_CONSTANT         2 'x'
+0012    | OP_CALL             1
+0014    | OP_PRINT
+0015    | OP_NIL
+0016    | OP_RETURN
+
+== <fn f> ==
+0000    2 OP_GET_LOCAL        1
+0002    | OP_RETURN
+0003    3 OP_NIL
+0004    | OP_RETURN
+"
+    );
+}
+
+#[test]
+fn disassemble_jumps() {
+    let function = Compiler::compile(_program("while (true) print 1;").ast());
+
+    assert_eq!(
+        Disassembler::disassemble(&function.unwrap()),
+        "\
+== <script> ==
+0000    1 OP_TRUE
+0001    | OP_JUMP_IF_FALSE    1 -> 12
+0004    | OP_POP
+0005    | OP_CONSTANT         0 '1'
+0008    | OP_PRINT
+0009    | OP_LOOP             9 -> 0
+0012    | OP_POP
+0013    | OP_NIL
+0014    | OP_RETURN
+"
+    );
+}
+
+#[test]
+fn trace_execution() {
+    let function = Compiler::compile(_program("print -2;").ast()).unwrap();
+    let mut vm = Vm::with_output(Vec::new());
+    vm.set_trace(true);
+    vm.interpret(function).unwrap();
+
+    assert_eq!(
+        String::from_utf8(vm.output().clone()).unwrap(),
+        "          [ <script> ]
+0000    1 OP_CONSTANT         0 '2'
+          [ <script> ][ 2 ]
+0003    | OP_NEGATE
+          [ <script> ][ -2 ]
+0004    | OP_PRINT
+-2
+          [ <script> ]
+0005    | OP_NIL
+          [ <script> ][ nil ]
+0006    | OP_RETURN
+"
+    );
+}
+
+#[test]
+fn gc_collects_cycles() {
+    let source = "\
+class Node {
+    init() { this.method = this.get; }
+    get() { return this; }
+}
+for (var i = 0; i < 5000; i = i + 1) Node();";
+    let function = Compiler::compile(_program(source).ast()).unwrap();
+    let mut vm = Vm::with_output(Vec::new());
+    vm.interpret(function).unwrap();
+
+    let stats = vm.gc_stats();
+    assert!(stats.collections > 0);
+    assert!(stats.freed >= 5_000);
+    assert!(stats.live < 1024, "{stats}");
+}
+
+#[test]
+fn gc_releases_unreachable_strings() {
+    let source = "\
+var keep = \"kept\";
+for (var i = 0; i < 100; i = i + 1) { var s = \"a\" + \"b\"; s = s + s; }
+print keep;";
+    let function = Compiler::compile(_program(source).ast()).unwrap();
+    let mut vm = Vm::with_output(Vec::new());
+    let natives = vm.gc_stats().live;
+    vm.set_gc_stress(true);
+    vm.interpret(function).unwrap();
+
+    assert_eq!(vm.output(), b"kept\n");
+    assert!(vm.gc_stats().live < natives + 10, "{}", vm.gc_stats());
+}